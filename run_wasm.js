@@ -4,7 +4,9 @@ const {
 
 WebAssembly.instantiate(readFileSync('out.wasm'), {
     host: {
-        print: console.log
+        print: console.log,
+        pow: Math.pow,
+        mod: (a, b) => a % b
     }
 }).then(instance => {}).catch(e => {
     console.error(e);
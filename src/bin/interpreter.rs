@@ -1,12 +1,117 @@
 extern crate elox;
 
 use crate::elox::interpreter::host::Host;
+use crate::elox::runner::capture::Backend;
+use crate::elox::runner::differential::{fuzz, run_differential};
 use crate::elox::runner::interp::EloxInterpreter;
+use crate::elox::runner::test_runner::run_tests;
 use crate::elox::runner::EloxFileAndPromptRunner;
+use crate::elox::vm::EloxVM;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+// elox test path [--vm]
+fn test(path: &Path, vm: bool) {
+    let summary = if vm {
+        run_tests(path, EloxVM::new)
+    } else {
+        run_tests(path, || EloxInterpreter::new(Host::default()))
+    };
+
+    match summary {
+        Ok(summary) if summary.succeeded() => {}
+        Ok(_) => process::exit(1),
+        Err(err) => {
+            eprintln!("Could not run the tests in '{}': {}", path.display(), err);
+            process::exit(1);
+        }
+    }
+}
+
+// elox diff file.elox [--wasm]
+fn diff(path: &Path, wasm: bool) {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("Could not read '{}': {}", path.display(), err);
+            process::exit(1);
+        }
+    };
+
+    let mut backends = vec![Backend::TreeWalker, Backend::Vm];
+    if wasm {
+        backends.push(Backend::Wasm);
+    }
+
+    let divergences = run_differential(&source, &backends);
+
+    for divergence in &divergences {
+        println!("{}", divergence);
+    }
+
+    if !divergences.is_empty() {
+        process::exit(1);
+    }
+}
+
+// elox fuzz [count] [seed]
+fn fuzz_backends(args: &[String]) {
+    let count = args
+        .first()
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(1000);
+    let seed = args.get(1).and_then(|arg| arg.parse().ok()).unwrap_or(0);
+
+    let failures = fuzz(seed, count);
+
+    for failure in &failures {
+        println!("{}", failure);
+    }
+
+    println!("{} programs, {} divergent", count, failures.len());
+
+    if !failures.is_empty() {
+        process::exit(1);
+    }
+}
 
 fn main() {
-    let mut elox = EloxInterpreter::new(Host::default());
-    if let Err(err) = elox.run_from_std_args() {
+    let args: Vec<String> = env::args().collect();
+    // elox script.elox [args...]
+    let script_args = args.iter().skip(2).cloned().collect();
+    let mut elox = EloxInterpreter::new(Host::with_args(script_args));
+
+    if args.len() >= 3 && args[1] == "test" {
+        test(
+            Path::new(&args[2]),
+            args[3..].iter().any(|arg| arg == "--vm"),
+        );
+        return;
+    }
+
+    if args.len() >= 3 && args[1] == "diff" {
+        diff(
+            Path::new(&args[2]),
+            args[3..].iter().any(|arg| arg == "--wasm"),
+        );
+        return;
+    }
+
+    if args.len() >= 2 && args[1] == "fuzz" {
+        fuzz_backends(&args[2..]);
+        return;
+    }
+
+    // elox check file.elox
+    let res = if args.len() == 3 && args[1] == "check" {
+        elox.check_file(Path::new(&args[2]))
+    } else {
+        elox.run_from_std_args()
+    };
+
+    if let Err(err) = res {
         println!("{}", err);
     }
 }
@@ -0,0 +1,64 @@
+// Bitwise operators shared by the tree-walker and the VM,
+// numbers are converted to 32-bit integers the same way JavaScript does
+
+pub fn to_int32(n: f64) -> i32 {
+    to_uint32(n) as i32
+}
+
+pub fn to_uint32(n: f64) -> u32 {
+    if !n.is_finite() {
+        return 0;
+    }
+
+    n.trunc().rem_euclid(4_294_967_296f64) as u32
+}
+
+pub fn and(a: f64, b: f64) -> f64 {
+    f64::from(to_int32(a) & to_int32(b))
+}
+
+pub fn or(a: f64, b: f64) -> f64 {
+    f64::from(to_int32(a) | to_int32(b))
+}
+
+pub fn xor(a: f64, b: f64) -> f64 {
+    f64::from(to_int32(a) ^ to_int32(b))
+}
+
+pub fn not(n: f64) -> f64 {
+    f64::from(!to_int32(n))
+}
+
+// only the 5 lowest bits of the shift count are used
+pub fn shl(a: f64, b: f64) -> f64 {
+    f64::from(to_int32(a).wrapping_shl(to_uint32(b)))
+}
+
+pub fn shr(a: f64, b: f64) -> f64 {
+    f64::from(to_int32(a).wrapping_shr(to_uint32(b)))
+}
+
+pub fn ushr(a: f64, b: f64) -> f64 {
+    f64::from(to_uint32(a).wrapping_shr(to_uint32(b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn int32_conversion() {
+        assert_eq!(to_int32(4_294_967_296f64 + 5f64), 5);
+        assert_eq!(to_int32(2_147_483_648f64), -2_147_483_648);
+        assert_eq!(to_int32(-1.9), -1);
+        assert_eq!(to_uint32(-1f64), 4_294_967_295);
+        assert_eq!(to_int32(f64::NAN), 0);
+    }
+
+    #[test]
+    fn shifts() {
+        assert_eq!(shl(1f64, 33f64), 2f64);
+        assert_eq!(shr(-16f64, 2f64), -4f64);
+        assert_eq!(ushr(-1f64, 28f64), 15f64);
+    }
+}
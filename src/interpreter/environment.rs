@@ -1,7 +1,18 @@
 extern crate fnv;
 
 use super::lox_array::create_elox_array_class;
-use super::natives::Clock;
+use super::lox_enum::register_builtin_enums;
+use super::lox_fs::create_elox_fs_namespace;
+use super::lox_instance::LoxInstance;
+use super::lox_json::create_elox_json_namespace;
+use super::lox_math::create_elox_math_namespace;
+use super::lox_random::create_elox_random_class;
+use super::lox_range::create_elox_range_class;
+use super::lox_string::create_elox_string_class;
+use super::natives::{
+    Args, Assert, AssertEq, Clock, Env, Exit, Num, ReadAll, ReadLine, Str, Test,
+};
+use super::reflection::register_reflection_natives;
 use super::value::{CallableValue, Value};
 use crate::parser::{Identifier, IdentifierHandle, IdentifierHandlesGenerator};
 use fnv::FnvHashMap;
@@ -59,6 +70,103 @@ impl Environment {
                 identifiers,
             )))),
         );
+
+        self.define(
+            identifiers.by_name("Range"),
+            Value::Callable(CallableValue::Class(Rc::new(create_elox_range_class(
+                self,
+                identifiers,
+            )))),
+        );
+
+        self.define(
+            Identifier::string(),
+            Value::Callable(CallableValue::Class(Rc::new(create_elox_string_class(
+                self,
+                identifiers,
+            )))),
+        );
+
+        self.define(
+            identifiers.by_name("str"),
+            Value::Callable(CallableValue::Native(Rc::new(Str::new(identifiers)))),
+        );
+
+        self.define(
+            identifiers.by_name("num"),
+            Value::Callable(CallableValue::Native(Rc::new(Num::new(identifiers)))),
+        );
+
+        self.define(
+            identifiers.by_name("readLine"),
+            Value::Callable(CallableValue::Native(Rc::new(ReadLine::new(identifiers)))),
+        );
+
+        self.define(
+            identifiers.by_name("readAll"),
+            Value::Callable(CallableValue::Native(Rc::new(ReadAll::new(identifiers)))),
+        );
+
+        self.define(
+            identifiers.by_name("args"),
+            Value::Callable(CallableValue::Native(Rc::new(Args::new(identifiers)))),
+        );
+
+        self.define(
+            identifiers.by_name("env"),
+            Value::Callable(CallableValue::Native(Rc::new(Env::new(identifiers)))),
+        );
+
+        self.define(
+            identifiers.by_name("exit"),
+            Value::Callable(CallableValue::Native(Rc::new(Exit::new(identifiers)))),
+        );
+
+        self.define(
+            identifiers.by_name("assert"),
+            Value::Callable(CallableValue::Native(Rc::new(Assert::new(identifiers)))),
+        );
+
+        self.define(
+            identifiers.by_name("assertEq"),
+            Value::Callable(CallableValue::Native(Rc::new(AssertEq::new(identifiers)))),
+        );
+
+        self.define(
+            identifiers.by_name("test"),
+            Value::Callable(CallableValue::Native(Rc::new(Test::new(identifiers)))),
+        );
+
+        self.define(
+            identifiers.by_name("Math"),
+            Value::Instance(create_elox_math_namespace(self, identifiers)),
+        );
+
+        self.define(
+            identifiers.by_name("JSON"),
+            Value::Instance(create_elox_json_namespace(self, identifiers)),
+        );
+
+        // fs returns Result values
+        register_builtin_enums(self, identifiers);
+
+        self.define(
+            identifiers.by_name("fs"),
+            Value::Instance(create_elox_fs_namespace(self, identifiers)),
+        );
+
+        // the default generator is seeded by the host on first use
+        let random = Rc::new(create_elox_random_class(self, identifiers));
+        self.define(
+            identifiers.by_name("random"),
+            Value::Instance(LoxInstance::new_native(Rc::clone(&random.mold))),
+        );
+        self.define(
+            identifiers.by_name("Random"),
+            Value::Callable(CallableValue::Class(random)),
+        );
+
+        register_reflection_natives(self, identifiers);
     }
 
     pub fn define(&self, identifier: IdentifierHandle, value: Value) {
@@ -1,14 +1,19 @@
+use super::bitwise;
 use super::environment::Environment;
 use super::eval_result::{EvalError, EvalResult};
+use super::execute::Exec;
 use super::lox_array::new_elox_array;
 use super::lox_function::LoxFunction;
+use super::lox_range::iterable_values;
 use super::value::{CallableValue, Value};
 use crate::interpreter::Interpreter;
 use crate::parser::expressions::ContextLessFuncParam;
 use crate::parser::expressions::{
-    BinaryOperator, BinaryOperatorCtx, Expr, ExprCtx, Literal, LogicalOperator, UnaryOperator,
+    BinaryOperator, BinaryOperatorCtx, Expr, ExprCtx, Literal, LogicalOperator, MatchArmBody,
+    UnaryOperator,
 };
-use crate::parser::Identifier;
+use crate::parser::{Identifier, IdentifierUse, IdentifierUseHandle};
+use crate::scanner::token::Position;
 use std::ops::Deref;
 use std::rc::Rc;
 
@@ -42,6 +47,17 @@ impl Eval for Interpreter {
                         }
                     }
                     UnaryOperator::Bang => Ok(Value::Boolean(!val.is_truthy())),
+                    UnaryOperator::Tilde => {
+                        if let Value::Number(nb) = val {
+                            Ok(Value::Number(bitwise::not(nb)))
+                        } else {
+                            Err(EvalError::UnexpectedUnaryOperatorOperand(
+                                expr.right.pos,
+                                UnaryOperator::Tilde,
+                                val.type_(),
+                            ))
+                        }
+                    }
                 }
             }
             Expr::Binary(bin_expr) => {
@@ -72,6 +88,30 @@ impl Eval for Interpreter {
                     BinaryOperator::Percent => {
                         arithmetic_op(op_ctx, &a, &b, |a, b| Value::Number(a % b))
                     }
+                    BinaryOperator::StarStar => {
+                        arithmetic_op(op_ctx, &a, &b, |a, b| Value::Number(a.powf(*b)))
+                    }
+                    BinaryOperator::SlashSlash => {
+                        arithmetic_op(op_ctx, &a, &b, |a, b| Value::Number((a / b).floor()))
+                    }
+                    BinaryOperator::Ampersand => {
+                        arithmetic_op(op_ctx, &a, &b, |a, b| Value::Number(bitwise::and(*a, *b)))
+                    }
+                    BinaryOperator::Pipe => {
+                        arithmetic_op(op_ctx, &a, &b, |a, b| Value::Number(bitwise::or(*a, *b)))
+                    }
+                    BinaryOperator::Caret => {
+                        arithmetic_op(op_ctx, &a, &b, |a, b| Value::Number(bitwise::xor(*a, *b)))
+                    }
+                    BinaryOperator::LessLess => {
+                        arithmetic_op(op_ctx, &a, &b, |a, b| Value::Number(bitwise::shl(*a, *b)))
+                    }
+                    BinaryOperator::GreaterGreater => {
+                        arithmetic_op(op_ctx, &a, &b, |a, b| Value::Number(bitwise::shr(*a, *b)))
+                    }
+                    BinaryOperator::GreaterGreaterGreater => {
+                        arithmetic_op(op_ctx, &a, &b, |a, b| Value::Number(bitwise::ushr(*a, *b)))
+                    }
 
                     BinaryOperator::Greater => {
                         arithmetic_op(op_ctx, &a, &b, |a, b| Value::Boolean(a > b))
@@ -87,6 +127,7 @@ impl Eval for Interpreter {
                     }
                     BinaryOperator::EqualEqual => Ok(Value::Boolean(a == b)),
                     BinaryOperator::BangEqual => Ok(Value::Boolean(a != b)),
+                    BinaryOperator::Is => instance_of(op_ctx, &a, &b),
                 }
             }
 
@@ -128,101 +169,40 @@ impl Eval for Interpreter {
                             return Ok(left);
                         }
                     }
+                    LogicalOperator::NilCoalescing => {
+                        if left != Value::Nil {
+                            return Ok(left);
+                        }
+                    }
                 }
 
                 self.eval(env, &expr.right)
             }
-            Expr::Call(call_expr) => {
-                let callee = self.eval(env, &call_expr.callee)?;
-
-                let mut args = Vec::with_capacity(call_expr.args.len());
-
-                for arg in &call_expr.args {
-                    args.push(self.eval(env, arg)?);
-                }
-
-                match callee {
-                    Value::Callable(callable_value) => {
-                        let callable = callable_value.into_callable();
-                        let has_rest_param = callable.has_rest_param();
-                        match callable.params() {
-                            Some(params) => {
-                                // default values
-                                if params.len() != args.len() || has_rest_param {
-                                    for param in params.iter().skip(args.len()) {
-                                        use ContextLessFuncParam::*;
-                                        match param {
-                                            DefaultValued(_, val) => {
-                                                args.push(val.clone());
-                                            }
-                                            _ => break,
-                                        };
-                                    }
-
-                                    // if rest: push the params into a native Array
-                                    if has_rest_param && args.len() >= params.len() {
-                                        // a rest parameter is always the last one
-                                        let rest_params = args.split_off(params.len() - 1);
-                                        args.push(new_elox_array(rest_params, self));
-                                    } else if has_rest_param && args.len() == params.len() - 1 {
-                                        args.push(new_elox_array(vec![], self));
-                                    }
-
-                                    let min_args = params
-                                        .iter()
-                                        .filter(|p| p.is_required())
-                                        .collect::<Vec<_>>()
-                                        .len();
-
-                                    let max_args = if has_rest_param {
-                                        usize::max_value()
-                                    } else {
-                                        params.len()
-                                    };
-
-                                    if min_args == max_args && !has_rest_param {
-                                        return Err(EvalError::WrongNumberOfArgs(
-                                            expr_ctx.pos,
-                                            min_args,
-                                            args.len(),
-                                            callable.name(&self.names),
-                                        ));
-                                    } else if args.len() < min_args || args.len() > max_args {
-                                        return Err(EvalError::WrongNumberOfArgsBetween(
-                                            expr_ctx.pos,
-                                            min_args,
-                                            max_args,
-                                            args.len(),
-                                            callable.name(&self.names),
-                                        ));
-                                    }
-                                }
-                            }
-                            None => {
-                                if !args.is_empty() {
-                                    return Err(EvalError::WrongNumberOfArgs(
-                                        expr_ctx.pos,
-                                        0,
-                                        args.len(),
-                                        callable.name(&self.names),
-                                    ));
-                                }
-                            }
-                        };
-
-                        return Ok(callable.call(&self, env, args, expr_ctx.pos)?);
-                    }
-                    _ => return Err(EvalError::ValueNotCallable(expr_ctx.pos, callee.type_())),
+            Expr::Conditional(expr) => {
+                if self.eval(env, &expr.condition)?.is_truthy() {
+                    self.eval(env, &expr.then_branch)
+                } else {
+                    self.eval(env, &expr.else_branch)
                 }
             }
+            Expr::Call(_) | Expr::Get(_) => Ok(self.eval_chain(env, expr_ctx)?.unwrap_or(Value::Nil)),
             Expr::Func(func_expr) => {
-                let func = LoxFunction::new(
+                let mut func = LoxFunction::new(
                     func_expr.clone(),
                     env.clone(), // inexpensive clone
                     false,
                     func_expr.context_less_params(self, env)?,
                 );
 
+                // a function declared again in the same scope overloads the previous one
+                if let Some(identifier) = func_expr.name {
+                    if let Some(Value::Callable(CallableValue::Function(previous))) =
+                        env.get(0, identifier.name)
+                    {
+                        func.overload = Some(previous);
+                    }
+                }
+
                 let f = Value::Callable(CallableValue::Function(Rc::new(func)));
 
                 // if not anonymous
@@ -232,31 +212,17 @@ impl Eval for Interpreter {
 
                 Ok(f)
             }
-            Expr::Get(get_expr) => {
-                let val = self.eval(env, &get_expr.object)?;
-
-                if let Some(instance) = val.into_instance() {
-                    if let Some(prop_val) = instance.get(get_expr.property.name) {
-                        return Ok(prop_val);
-                    } else {
-                        return Err(EvalError::UndefinedProperty(
-                            expr_ctx.pos,
-                            self.name(get_expr.property.name),
-                        ));
-                    }
-                }
-
-                Err(EvalError::OnlyInstancesHaveProperties(
-                    expr_ctx.pos,
-                    self.eval(env, &get_expr.object).unwrap().type_(),
-                ))
-            }
             Expr::Set(set_expr) => {
                 let obj = self.eval(env, &set_expr.object)?;
 
                 if let Some(instance) = &obj.into_instance() {
+                    self.check_private_access(
+                        instance.private_owner(set_expr.property.name),
+                        &set_expr.property,
+                        expr_ctx.pos,
+                    )?;
                     let val = self.eval(env, &set_expr.value)?;
-                    instance.set(set_expr.property.name, &val);
+                    instance.set(set_expr.property.name, &val, self, expr_ctx.pos)?;
                     return Ok(val);
                 }
 
@@ -281,6 +247,12 @@ impl Eval for Interpreter {
                             if let Some(CallableValue::Class(parent)) =
                                 superclass.into_callable_value()
                             {
+                                self.check_private_access(
+                                    parent.mold.private_owner(super_expr.method.name),
+                                    &super_expr.method,
+                                    expr_ctx.pos,
+                                )?;
+
                                 if let Some(method) = parent.find_method(super_expr.method.name) {
                                     return Ok(Value::Callable(CallableValue::Function(Rc::new(
                                         method.bind(&instance),
@@ -299,17 +271,259 @@ impl Eval for Interpreter {
                 Ok(Value::Nil)
             }
             Expr::ArrayDeclExpr(array_decl) => {
-                let values = array_decl
-                    .values
-                    .iter()
-                    .map(|val| self.eval(env, &val))
-                    .collect::<EvalResult<Vec<_>>>()?;
+                let values = self.eval_spreadable(env, &array_decl.values)?;
                 Ok(new_elox_array(values, self))
             }
+            Expr::Spread(_) => unreachable!("spread outside of an array literal or call arguments"),
+            Expr::Match(match_expr) => {
+                let value = self.eval(env, &match_expr.value)?;
+
+                for arm in &match_expr.arms {
+                    // each arm gets its own scope for the pattern's bindings
+                    let arm_env = Environment::new(Some(env));
+
+                    if !self.match_pattern(&arm_env, &arm.pattern, &value)? {
+                        continue;
+                    }
+
+                    if let Some(guard) = &arm.guard {
+                        if !self.eval(&arm_env, guard)?.is_truthy() {
+                            continue;
+                        }
+                    }
+
+                    return match &arm.body {
+                        MatchArmBody::Expr(expr) => self.eval(&arm_env, expr),
+                        MatchArmBody::Block(block) => {
+                            let block_env = Environment::new(Some(&arm_env));
+
+                            for stmt in &block.stmts {
+                                self.exec(&block_env, stmt)?;
+                            }
+
+                            Ok(Value::Nil)
+                        }
+                    };
+                }
+
+                Err(EvalError::NoMatchingArm(
+                    expr_ctx.pos,
+                    value.to_str(self, expr_ctx.pos)?,
+                ))
+            }
         }
     }
 }
 
+impl Interpreter {
+    // calls a function, method or class, filling in default values and rest parameters
+    pub fn call_value(
+        &self,
+        env: &Environment,
+        callee: Value,
+        mut args: Vec<Value>,
+        call_pos: Position,
+    ) -> EvalResult<Value> {
+        match callee {
+            Value::Callable(callable_value) => {
+                let callable = callable_value.select_overload(args.len()).into_callable();
+                let has_rest_param = callable.has_rest_param();
+                match callable.params() {
+                    Some(params) => {
+                        // default values
+                        if params.len() != args.len() || has_rest_param {
+                            for param in params.iter().skip(args.len()) {
+                                use ContextLessFuncParam::*;
+                                match param {
+                                    DefaultValued(_, val) => {
+                                        args.push(val.clone());
+                                    }
+                                    _ => break,
+                                };
+                            }
+
+                            // if rest: push the params into a native Array
+                            if has_rest_param && args.len() >= params.len() {
+                                // a rest parameter is always the last one
+                                let rest_params = args.split_off(params.len() - 1);
+                                args.push(new_elox_array(rest_params, self));
+                            } else if has_rest_param && args.len() == params.len() - 1 {
+                                args.push(new_elox_array(vec![], self));
+                            }
+
+                            let min_args = params
+                                .iter()
+                                .filter(|p| p.is_required())
+                                .collect::<Vec<_>>()
+                                .len();
+
+                            let max_args = if has_rest_param {
+                                usize::max_value()
+                            } else {
+                                params.len()
+                            };
+
+                            if min_args == max_args && !has_rest_param {
+                                return Err(EvalError::WrongNumberOfArgs(
+                                    call_pos,
+                                    min_args,
+                                    args.len(),
+                                    callable.name(&self.names()),
+                                ));
+                            } else if args.len() < min_args || args.len() > max_args {
+                                return Err(EvalError::WrongNumberOfArgsBetween(
+                                    call_pos,
+                                    min_args,
+                                    max_args,
+                                    args.len(),
+                                    callable.name(&self.names()),
+                                ));
+                            }
+                        }
+                    }
+                    None => {
+                        if !args.is_empty() {
+                            return Err(EvalError::WrongNumberOfArgs(
+                                call_pos,
+                                0,
+                                args.len(),
+                                callable.name(&self.names()),
+                            ));
+                        }
+                    }
+                };
+
+                callable.call(self, env, args, call_pos)
+            }
+            _ => Err(EvalError::ValueNotCallable(call_pos, callee.type_())),
+        }
+    }
+
+    // evaluates a chain of member accesses and calls, None when an optional member
+    // met a nil object: the rest of the chain is skipped, a?.b.c() does not evaluate .c()
+    fn eval_chain(&self, env: &Environment, expr_ctx: &ExprCtx) -> EvalResult<Option<Value>> {
+        match &expr_ctx.expr {
+            Expr::Get(get_expr) => {
+                let object = match self.eval_chain(env, &get_expr.object)? {
+                    Some(object) => object,
+                    None => return Ok(None),
+                };
+
+                if get_expr.optional && object == Value::Nil {
+                    return Ok(None);
+                }
+
+                self.get_property(object, &get_expr.property, expr_ctx.pos).map(Some)
+            }
+            Expr::Call(call_expr) => {
+                let callee = match self.eval_chain(env, &call_expr.callee)? {
+                    Some(callee) => callee,
+                    None => return Ok(None),
+                };
+
+                let args = self.eval_spreadable(env, &call_expr.args)?;
+
+                // calls are located at their callee, like on the VM
+                self.call_value(env, callee, args, call_expr.callee.pos)
+                    .map(Some)
+            }
+            _ => self.eval(env, expr_ctx).map(Some),
+        }
+    }
+
+    // evaluates array elements or call arguments, expanding ...values in place
+    fn eval_spreadable(&self, env: &Environment, exprs: &[ExprCtx]) -> EvalResult<Vec<Value>> {
+        let mut values = Vec::with_capacity(exprs.len());
+
+        for expr in exprs {
+            if let Expr::Spread(spread) = &expr.expr {
+                let value = self.eval(env, &spread.value)?;
+
+                match iterable_values(&value) {
+                    Some(elements) => values.extend(elements),
+                    None => {
+                        return Err(EvalError::SpreadOperandMustBeAnArray(
+                            expr.pos,
+                            value.type_(),
+                        ))
+                    }
+                }
+            } else {
+                values.push(self.eval(env, expr)?);
+            }
+        }
+
+        Ok(values)
+    }
+
+    // private members can only be accessed from inside the class declaring them
+    fn check_private_access(
+        &self,
+        owner: Option<IdentifierUseHandle>,
+        property: &IdentifierUse,
+        pos: Position,
+    ) -> EvalResult<()> {
+        match owner {
+            Some(owner) if self.resolver.access_class(property.use_handle) != Some(owner) => {
+                Err(EvalError::PrivateProperty(pos, self.name(property.name)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn get_property(
+        &self,
+        val: Value,
+        property_use: &IdentifierUse,
+        pos: Position,
+    ) -> EvalResult<Value> {
+        let property = property_use.name;
+
+        if let Value::String(_) = val {
+            // strings are primitives whose methods live in the native String class
+            let method = self
+                .lookup_global(Identifier::string())
+                .and_then(|class| class.into_callable_value())
+                .and_then(|callable| callable.into_class())
+                .and_then(|class| class.find_method(property));
+
+            if let Some(method) = method {
+                return Ok(Value::Callable(CallableValue::Function(Rc::new(
+                    method.bind_this(val),
+                ))));
+            }
+
+            return Err(EvalError::UndefinedProperty(pos, self.name(property)));
+        }
+
+        let type_ = val.type_();
+
+        if let Value::Callable(CallableValue::Class(class)) = &val {
+            self.check_private_access(class.mold.private_owner(property), property_use, pos)?;
+
+            if let Some(method) = class.find_static_method(property) {
+                return Ok(Value::Callable(CallableValue::Function(Rc::new(
+                    method.bind_this(val.clone()),
+                ))));
+            }
+
+            return Err(EvalError::UndefinedProperty(pos, self.name(property)));
+        }
+
+        if let Some(instance) = val.into_instance() {
+            self.check_private_access(instance.private_owner(property), property_use, pos)?;
+
+            if let Some(prop_val) = instance.get(property, self, pos)? {
+                return Ok(prop_val);
+            } else {
+                return Err(EvalError::UndefinedProperty(pos, self.name(property)));
+            }
+        }
+
+        Err(EvalError::OnlyInstancesHaveProperties(pos, type_))
+    }
+}
+
 #[inline]
 fn arithmetic_op<F>(
     op_ctx: &BinaryOperatorCtx,
@@ -330,3 +544,15 @@ where
         )),
     }
 }
+
+// value is Class walks the superclasses of the value's class, strings are instances of String
+fn instance_of(op_ctx: &BinaryOperatorCtx, value: &Value, class: &Value) -> EvalResult<Value> {
+    match class {
+        Value::Callable(CallableValue::Class(class)) => Ok(Value::Boolean(match value {
+            Value::Instance(instance) => instance.is_instance_of(class),
+            Value::String(_) => class.mold.identifier == Identifier::string(),
+            _ => false,
+        })),
+        _ => Err(EvalError::IsOperandMustBeAClass(op_ctx.pos, class.type_())),
+    }
+}
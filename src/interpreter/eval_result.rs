@@ -15,12 +15,32 @@ pub enum EvalError {
     WrongNumberOfArgs(Position, usize, usize, String),
     WrongNumberOfArgsBetween(Position, usize, usize, usize, String),
     CouldNotGetTime(Position),
+    Exit(Position, i32),
+    AssertionFailed(Position, Option<String>),
+    InvalidJson(Position, String, usize, usize),
+    NotJsonSerializable(Position, String),
+    CyclicJson(Position),
+    CyclicArray(Position),
     OnlyInstancesHaveProperties(Position, String),
     UndefinedProperty(Position, String),
     SuperclassMustBeAClass(Position, String),
+    IsOperandMustBeAClass(Position, String),
     ToStringMethodMustReturnAString(Position, String, String),
-    ArrayIndexOutOfBounds(Position, usize, usize),
+    ArrayIndexOutOfBounds(Position, isize, usize),
+    StringIndexOutOfBounds(Position, isize, usize),
+    UnexpectedArgumentType(Position, String, String, String),
     StackOverflow(Position, usize),
+    NoMatchingArm(Position, String),
+    WrongNumberOfPatternFields(Position, String, usize, usize),
+    PatternMustBeAClass(Position, String, String),
+    ReadOnlyProperty(Position, String),
+    PrivateProperty(Position, String),
+    CannotDestructureAsArray(Position, String),
+    SpreadOperandMustBeAnArray(Position, String),
+    UnexpectedCallbackReturnType(Position, String, String, String),
+    CallbackFailed(String, Box<EvalError>),
+    EmptyRandomRange(Position, f64, f64),
+    NotSupportedOnTheVm(Position, String),
     Return(Value),
 }
 
@@ -62,11 +82,25 @@ impl fmt::Display for EvalError {
                 )
             }
             EvalError::CouldNotGetTime(_) => write!(f, "Could not get time"),
+            EvalError::Exit(_, code) => write!(f, "Exited with code {}", code),
+            EvalError::AssertionFailed(_, None) => write!(f, "Assertion failed"),
+            EvalError::AssertionFailed(_, Some(msg)) => write!(f, "Assertion failed: {}", msg),
+            EvalError::InvalidJson(_, msg, line, col) => {
+                write!(f, "Invalid JSON at line {}:{}: {}", line, col, msg)
+            }
+            EvalError::NotJsonSerializable(_, type_) => {
+                write!(f, "Cannot convert a value of type '{}' to JSON", type_)
+            }
+            EvalError::CyclicJson(_) => write!(f, "Cannot convert a cyclic structure to JSON"),
+            EvalError::CyclicArray(_) => write!(f, "Cannot flatten an array containing itself"),
             EvalError::Return(_) => unreachable!(),
             EvalError::OnlyInstancesHaveProperties(_, typ) => {
                 write!(f, "Only instances have properties, found: '{}'", typ)
             }
             EvalError::UndefinedProperty(_, id) => write!(f, "Undefined property: '{}'", id),
+            EvalError::IsOperandMustBeAClass(_, typ) => {
+                write!(f, "Right operand of 'is' must be a class, found '{}'", typ)
+            }
             EvalError::SuperclassMustBeAClass(_, typ) => {
                 write!(f, "Superclass must be a class, found: '{}'", typ)
             }
@@ -80,7 +114,61 @@ impl fmt::Display for EvalError {
                 "Index out of bounds: tried to access value at index {} on an array of length {}",
                 idx, len
             ),
+            EvalError::StringIndexOutOfBounds(_, idx, len) => write!(
+                f,
+                "Index out of bounds: tried to access character at index {} on a string of length {}",
+                idx, len
+            ),
+            EvalError::UnexpectedArgumentType(_, name, expected, found) => write!(
+                f,
+                "'{}' expected an argument of type '{}', found '{}'",
+                name, expected, found
+            ),
             EvalError::StackOverflow(_, max) => write!(f, "Stack overflox: max frames = {}", max),
+            EvalError::NoMatchingArm(_, value) => {
+                write!(f, "No match arm matched the value '{}'", value)
+            }
+            EvalError::WrongNumberOfPatternFields(_, name, expected, got) => {
+                let s = if *expected != 1 { "s" } else { "" };
+                write!(
+                    f,
+                    "Pattern '{}' expected {} field{}, got {}",
+                    name, expected, s, got
+                )
+            }
+            EvalError::PatternMustBeAClass(_, name, typ) => write!(
+                f,
+                "'{}' must be a class or an enum variant to be destructured, found '{}'",
+                name, typ
+            ),
+            EvalError::ReadOnlyProperty(_, name) => write!(
+                f,
+                "Cannot assign to property '{}' which only has a getter",
+                name
+            ),
+            EvalError::PrivateProperty(_, name) => {
+                write!(f, "Undefined property: '{}' is private", name)
+            }
+            EvalError::CannotDestructureAsArray(_, typ) => {
+                write!(f, "Cannot destructure a value of type '{}' as an array", typ)
+            }
+            EvalError::SpreadOperandMustBeAnArray(_, typ) => {
+                write!(f, "Spread operand must be an array, found '{}'", typ)
+            }
+            EvalError::UnexpectedCallbackReturnType(_, name, expected, found) => write!(
+                f,
+                "'{}' expected its callback to return a value of type '{}', found '{}'",
+                name, expected, found
+            ),
+            EvalError::EmptyRandomRange(_, lo, hi) => {
+                write!(f, "There is no integer between {} and {}", lo, hi)
+            }
+            EvalError::NotSupportedOnTheVm(_, name) => {
+                write!(f, "'{}' is not supported on the VM yet", name)
+            }
+            EvalError::CallbackFailed(name, err) => {
+                write!(f, "In the callback passed to '{}': {}", name, err)
+            }
         }
     }
 }
@@ -98,10 +186,31 @@ impl ErrorPosition for EvalError {
             | OnlyInstancesHaveProperties(pos, _)
             | UndefinedProperty(pos, _)
             | SuperclassMustBeAClass(pos, _)
+            | IsOperandMustBeAClass(pos, _)
             | ToStringMethodMustReturnAString(pos, _, _)
             | ArrayIndexOutOfBounds(pos, _, _)
+            | StringIndexOutOfBounds(pos, _, _)
+            | UnexpectedArgumentType(pos, _, _, _)
             | StackOverflow(pos, _)
-            | CouldNotGetTime(pos) => pos,
+            | NoMatchingArm(pos, _)
+            | WrongNumberOfPatternFields(pos, _, _, _)
+            | PatternMustBeAClass(pos, _, _)
+            | ReadOnlyProperty(pos, _)
+            | PrivateProperty(pos, _)
+            | CannotDestructureAsArray(pos, _)
+            | SpreadOperandMustBeAnArray(pos, _)
+            | UnexpectedCallbackReturnType(pos, _, _, _)
+            | EmptyRandomRange(pos, _, _)
+            | NotSupportedOnTheVm(pos, _)
+            | CouldNotGetTime(pos)
+            | Exit(pos, _)
+            | AssertionFailed(pos, _)
+            | InvalidJson(pos, _, _, _)
+            | NotJsonSerializable(pos, _)
+            | CyclicJson(pos)
+            | CyclicArray(pos) => pos,
+            // reported where the callback failed
+            CallbackFailed(_, err) => err.position(),
             Return(_) => unreachable!(),
         }
     }
@@ -1,14 +1,17 @@
 use super::environment::Environment;
 use super::eval::Eval;
-use super::lox_class::LoxClass;
+use super::lox_array::new_elox_array;
+use super::lox_class::{ClassMembers, LoxClass, Methods};
+use super::lox_enum::create_elox_enum;
 
 use super::lox_function::LoxFunction;
+use super::lox_range::iterable_values;
 use super::value::{CallableValue, Value};
 use crate::interpreter::eval_result::{EvalError, EvalResult};
 use crate::interpreter::Interpreter;
-use crate::parser::expressions::{Expr, ExprCtx};
-use crate::parser::statements::Stmt;
-use crate::parser::{Identifier, IdentifierHandle};
+use crate::parser::expressions::{Expr, ExprCtx, FuncExpr};
+use crate::parser::statements::{Destructuring, Stmt};
+use crate::parser::Identifier;
 use crate::runner::EloxError;
 use fnv::FnvHashMap;
 use std::rc::Rc;
@@ -45,6 +48,42 @@ impl Exec for Interpreter {
                 env.define(decl.identifier.name, value);
                 Ok(())
             }
+            Stmt::DestructuringDecl(decl) => {
+                let value = self.eval(env, &decl.initializer)?;
+
+                match &decl.target {
+                    Destructuring::Array(names, rest) => {
+                        let values = match iterable_values(&value) {
+                            Some(values) => values,
+                            None => {
+                                return Err(EvalError::CannotDestructureAsArray(
+                                    decl.pos,
+                                    value.type_(),
+                                ))
+                            }
+                        };
+
+                        // missing elements are nil
+                        for (i, identifier) in names.iter().enumerate() {
+                            let element = values.get(i).cloned().unwrap_or(Value::Nil);
+                            env.define(identifier.name, element);
+                        }
+
+                        if let Some(rest) = rest {
+                            let rest_values = values.iter().skip(names.len()).cloned().collect();
+                            env.define(rest.name, new_elox_array(rest_values, self));
+                        }
+                    }
+                    Destructuring::Object(fields) => {
+                        for field in fields {
+                            let field_value = self.get_property(value.clone(), field, decl.pos)?;
+                            env.define(field.name, field_value);
+                        }
+                    }
+                }
+
+                Ok(())
+            }
             Stmt::Block(block) => {
                 let inner_env = Environment::new(Some(env));
 
@@ -120,24 +159,46 @@ impl Exec for Interpreter {
                     );
                 }
 
-                let mut methods: FnvHashMap<IdentifierHandle, Rc<LoxFunction>> =
-                    FnvHashMap::default();
+                let define_methods = |funcs: &[FuncExpr], is_static: bool| -> EvalResult<Methods> {
+                    let mut methods: Methods = FnvHashMap::default();
+
+                    for method in funcs {
+                        let name_handle = method.name.unwrap(); // anonymous methods caught by the parser
+                        let mut func = LoxFunction::new(
+                            method.clone(),
+                            environment.clone(),
+                            !is_static && name_handle.name == Identifier::init(),
+                            method.context_less_params(self, env)?,
+                        );
+                        func.overload = methods.remove(&name_handle.name);
+                        methods.insert(name_handle.name, Rc::new(func));
+                    }
 
-                for method in &class_decl.methods {
-                    let name_handle = method.name.unwrap(); // anonymous methods caught by the parser
-                    let func = LoxFunction::new(
-                        method.clone(),
-                        environment.clone(),
-                        name_handle.name == Identifier::init(),
-                        method.context_less_params(self, env)?,
-                    );
-                    methods.insert(name_handle.name, Rc::new(func));
-                }
+                    Ok(methods)
+                };
 
-                let lox_class = Rc::new(LoxClass::new(
+                let members = ClassMembers {
+                    static_methods: define_methods(&class_decl.static_methods, true)?,
+                    getters: define_methods(&class_decl.getters, false)?,
+                    setters: define_methods(&class_decl.setters, false)?,
+                    fields: class_decl
+                        .fields
+                        .iter()
+                        .filter_map(|field| {
+                            let initializer = field.initializer.clone()?;
+                            Some((field.identifier.name, initializer))
+                        })
+                        .collect(),
+                    env: Some(environment.clone()),
+                    private: class_decl.private_members.clone(),
+                    declaration: Some(class_decl.identifier.use_handle),
+                };
+
+                let lox_class = Rc::new(LoxClass::with_members(
                     class_decl.identifier.name,
                     superclass,
-                    methods,
+                    define_methods(&class_decl.methods, false)?,
+                    members,
                 ));
                 let callable_class = Value::Callable(CallableValue::Class(lox_class));
 
@@ -147,6 +208,32 @@ impl Exec for Interpreter {
 
                 environment.assign(0, class_decl.identifier.name, callable_class.clone());
 
+                Ok(())
+            }
+            Stmt::EnumDecl(enum_decl) => {
+                let variants = enum_decl
+                    .variants
+                    .iter()
+                    .map(|variant| {
+                        (
+                            variant.identifier.name,
+                            variant
+                                .fields
+                                .as_ref()
+                                .map(|fields| fields.iter().map(|field| field.name).collect()),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+
+                let (enum_class, values) =
+                    create_elox_enum(enum_decl.identifier.name, &variants, env);
+
+                env.define(enum_decl.identifier.name, enum_class);
+
+                for (variant, value) in values {
+                    env.define(variant, value);
+                }
+
                 Ok(())
             }
         }
@@ -1,4 +1,11 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::default::Default;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process;
 use std::time::SystemTime;
 use std::rc::Rc;
 use crate::scanner::token::Position;
@@ -8,7 +15,135 @@ use crate::interpreter::eval_result::EvalError;
 pub struct Host {
     pub print: Rc<(Fn(Position, String) -> EloxResult)>,               // msg
     pub error: Rc<(Fn(Position, String, usize, usize) -> EloxResult)>, // err_msg, line, col
+    pub warn: Rc<(Fn(Position, String, usize, usize) -> EloxResult)>,  // warning_msg, line, col
     pub clock: Rc<(Fn(Position) -> Result<f64, EloxError>)>,
+    pub seed: Rc<dyn Fn() -> u64>, // seeds the default random generator
+    pub fs: FileSystem,
+    pub read_line: Rc<dyn Fn() -> Option<String>>, // None at the end of the input
+    pub read_all: Rc<dyn Fn() -> String>,
+    pub args: Rc<dyn Fn() -> Vec<String>>, // the arguments following the script path
+    pub env_var: EnvLookup,                // name
+    pub exit: Rc<dyn Fn(i32)>,             // code, the script is stopped if the host does not exit
+}
+
+pub type EnvLookup = Rc<dyn Fn(&str) -> Option<String>>;
+
+// failures are reported to scripts as Err(msg) values
+pub type FsResult<T> = Result<T, String>;
+
+pub type FsQuery<T> = Rc<dyn Fn(&str) -> FsResult<T>>; // path
+pub type FsWrite = Rc<dyn Fn(&str, &str) -> FsResult<()>>; // path, contents
+
+// every file system access of the fs module goes through these callbacks
+pub struct FileSystem {
+    pub read_file: FsQuery<String>,
+    pub write_file: FsWrite,
+    pub append_file: FsWrite,
+    pub exists: Rc<dyn Fn(&str) -> bool>,
+    pub list_dir: FsQuery<Vec<String>>,
+    pub remove: FsQuery<()>,
+}
+
+impl FileSystem {
+    // the real file system
+    pub fn std() -> FileSystem {
+        FileSystem {
+            read_file: Rc::new(|path| fs::read_to_string(path).map_err(|err| err.to_string())),
+            write_file: Rc::new(|path, contents| fs::write(path, contents).map_err(|err| err.to_string())),
+            append_file: Rc::new(|path, contents| {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .and_then(|mut file| file.write_all(contents.as_bytes()))
+                    .map_err(|err| err.to_string())
+            }),
+            exists: Rc::new(|path| Path::new(path).exists()),
+            list_dir: Rc::new(|path| {
+                let mut names = fs::read_dir(path)
+                    .and_then(|entries| {
+                        entries
+                            .map(|entry| entry.map(|e| e.file_name().to_string_lossy().into_owned()))
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                    .map_err(|err| err.to_string())?;
+
+                names.sort();
+                Ok(names)
+            }),
+            remove: Rc::new(|path| {
+                if Path::new(path).is_dir() {
+                    fs::remove_dir(path)
+                } else {
+                    fs::remove_file(path)
+                }
+                .map_err(|err| err.to_string())
+            }),
+        }
+    }
+
+    // every operation fails, for hosts without a file system
+    pub fn denied() -> FileSystem {
+        fn denied<T>(path: &str) -> FsResult<T> {
+            Err(format!("Access to '{}' denied by the host", path))
+        }
+
+        FileSystem {
+            read_file: Rc::new(denied),
+            write_file: Rc::new(|path, _| denied(path)),
+            append_file: Rc::new(|path, _| denied(path)),
+            exists: Rc::new(|_| false),
+            list_dir: Rc::new(denied),
+            remove: Rc::new(denied),
+        }
+    }
+
+    // a flat in-memory file system, lost when the host is dropped
+    pub fn in_memory() -> FileSystem {
+        let files: Rc<RefCell<BTreeMap<String, String>>> = Rc::new(RefCell::new(BTreeMap::new()));
+        let not_found = |path: &str| format!("No such file: '{}'", path);
+
+        let read = Rc::clone(&files);
+        let write = Rc::clone(&files);
+        let append = Rc::clone(&files);
+        let exists = Rc::clone(&files);
+        let list = Rc::clone(&files);
+        let remove = files;
+
+        FileSystem {
+            read_file: Rc::new(move |path| read.borrow().get(path).cloned().ok_or_else(|| not_found(path))),
+            write_file: Rc::new(move |path, contents| {
+                write.borrow_mut().insert(path.into(), contents.into());
+                Ok(())
+            }),
+            append_file: Rc::new(move |path, contents| {
+                append.borrow_mut().entry(path.into()).or_default().push_str(contents);
+                Ok(())
+            }),
+            exists: Rc::new(move |path| exists.borrow().contains_key(path)),
+            list_dir: Rc::new(move |path| {
+                let dir = path.trim_end_matches('/');
+                let prefix = if dir.is_empty() || dir == "." { String::new() } else { format!("{}/", dir) };
+
+                Ok(list
+                    .borrow()
+                    .keys()
+                    .filter_map(|file| file.strip_prefix(&prefix).map(String::from))
+                    .collect())
+            }),
+            remove: Rc::new(move |path| remove.borrow_mut().remove(path).map(|_| ()).ok_or_else(|| not_found(path))),
+        }
+    }
+}
+
+impl Host {
+    // the standard host, with the arguments given to the script
+    pub fn with_args(args: Vec<String>) -> Host {
+        Host {
+            args: Rc::new(move || args.clone()),
+            ..Host::default()
+        }
+    }
 }
 
 impl Default for Host {
@@ -29,6 +164,41 @@ impl Default for Host {
                 eprintln!("Error: [line {}:{}]: {}", line, col, err);
                 Ok(())
             }),
+            warn: Rc::new(|_, warning, line, col| {
+                eprintln!("Warning: [line {}:{}]: {}", line, col, warning);
+                Ok(())
+            }),
+            seed: Rc::new(|| {
+                // ELOX_SEED makes runs using the default generator reproducible
+                if let Some(seed) = env::var("ELOX_SEED").ok().and_then(|s| s.parse().ok()) {
+                    return seed;
+                }
+
+                SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .map_or(0, |now| now.as_nanos() as u64)
+            }),
+            fs: FileSystem::std(),
+            read_line: Rc::new(|| {
+                let mut line = String::new();
+
+                match io::stdin().read_line(&mut line) {
+                    Ok(0) | Err(_) => None,
+                    Ok(_) => {
+                        let len = line.trim_end_matches(&['\r', '\n'][..]).len();
+                        line.truncate(len);
+                        Some(line)
+                    }
+                }
+            }),
+            read_all: Rc::new(|| {
+                let mut input = String::new();
+                io::stdin().read_to_string(&mut input).ok();
+                input
+            }),
+            args: Rc::new(Vec::new),
+            env_var: Rc::new(|name| env::var(name).ok()),
+            exit: Rc::new(|code| process::exit(code)),
         }
     }
 }
@@ -1,16 +1,21 @@
 use super::eval_result::{EvalError, EvalResult};
+use super::lox_callable::LoxCallable;
 use super::lox_class::LoxClass;
-use super::lox_function::LoxFunction;
+use super::lox_function::{LoxFunction, LoxFunctionParams, NativeFunction, NativeMethod};
 use super::lox_instance::{LoxInstance, NativesMap};
+use super::lox_range::{iterable_values, range_bounds};
+use super::lox_string::number_arg;
 use super::natives::NativeValue;
 use super::value::Value;
 use super::Environment;
 use super::Interpreter;
+use crate::parser::expressions::BinaryOperator;
 use crate::parser::expressions::ContextLessFuncParam::*;
-use crate::parser::{Identifier, IdentifierHandlesGenerator};
+use crate::parser::{Identifier, IdentifierHandle, IdentifierHandlesGenerator};
 use crate::scanner::token::Position;
 use fnv::FnvHashMap;
 use std::cell::RefCell;
+use std::cmp::Ordering;
 use std::rc::Rc;
 
 fn vec_handle() -> usize {
@@ -31,6 +36,271 @@ pub fn new_elox_array(values: Vec<Value>, interpreter: &Interpreter) -> Value {
     ))
 }
 
+// the values of an Array instance, None for any other value
+pub fn array_values(value: &Value) -> Option<Vec<Value>> {
+    shared_array_values(value).map(|values| values.borrow().clone())
+}
+
+// the values of an Array instance to be modified in place
+pub fn shared_array_values(value: &Value) -> Option<Rc<RefCell<Vec<Value>>>> {
+    match value {
+        Value::Instance(instance) if instance.class_name() == Identifier::array() => {
+            match instance.clone().get_native(vec_handle())? {
+                NativeValue::Vector(values) => Some(values),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+// negative indices count from the end of the array
+fn normalize_index(idx: f64, len: usize) -> f64 {
+    if idx < 0f64 {
+        idx + len as f64
+    } else {
+        idx
+    }
+}
+
+// the position of an existing element, None when idx is out of bounds
+pub fn element_index(idx: f64, len: usize) -> Option<usize> {
+    let idx = normalize_index(idx, len);
+
+    if idx < 0f64 || idx >= len as f64 {
+        None
+    } else {
+        Some(idx as usize)
+    }
+}
+
+// clamps the bounds of a slice to the array, an empty slice if they cross
+fn slice_bounds(start: f64, end: f64, inclusive: bool, len: usize) -> (usize, usize) {
+    let clamp = |idx: f64| idx.max(0f64).min(len as f64).floor() as usize;
+
+    let start = clamp(normalize_index(start, len));
+    let end = normalize_index(end, len);
+    let end = clamp(if inclusive { end + 1f64 } else { end });
+
+    (start, end.max(start))
+}
+
+fn array_method(
+    env: &Environment,
+    name: IdentifierHandle,
+    params: LoxFunctionParams,
+    method: Rc<NativeMethod>,
+) -> Rc<LoxFunction> {
+    Rc::new(LoxFunction::new_native_method(
+        method,
+        env.clone(),
+        false,
+        params,
+        name,
+    ))
+}
+
+// higher-order methods read 'this' from their environment instead of borrowing
+// the instance natives, so that callbacks can use the array they are iterating
+fn higher_order_method(
+    env: &Environment,
+    name: IdentifierHandle,
+    params: LoxFunctionParams,
+    method: Rc<NativeFunction>,
+) -> Rc<LoxFunction> {
+    Rc::new(LoxFunction::new_native(
+        method,
+        env.clone(),
+        false,
+        params,
+        name,
+    ))
+}
+
+// the values of the array a higher-order method is called on
+fn this_values(func: &LoxFunction) -> Rc<RefCell<Vec<Value>>> {
+    let this = func
+        .env
+        .get(0, Identifier::this())
+        .expect("Could not find 'this'")
+        .into_instance()
+        .unwrap();
+
+    match this.get_native(vec_handle()) {
+        Some(NativeValue::Vector(values)) => values,
+        _ => panic!("Could not fetch natives from native method"),
+    }
+}
+
+fn callback_arg(
+    func: &LoxFunction,
+    interpreter: &Interpreter,
+    arg: &Value,
+    call_pos: Position,
+) -> EvalResult<Value> {
+    match arg {
+        Value::Callable(_) => Ok(arg.clone()),
+        _ => Err(EvalError::UnexpectedArgumentType(
+            call_pos,
+            func.name(&interpreter.names()),
+            "function".into(),
+            arg.type_(),
+        )),
+    }
+}
+
+// calls back with as many of args as the callback accepts, so that both
+// fun (value) and fun (value, idx) can be passed to map
+fn call_back(
+    func: &LoxFunction,
+    interpreter: &Interpreter,
+    env: &Environment,
+    callback: &Value,
+    mut args: Vec<Value>,
+    call_pos: Position,
+) -> EvalResult<Value> {
+    if let Value::Callable(callable) = callback {
+        let callable = callable.clone().into_callable();
+
+        if !callable.has_rest_param() {
+            let accepted = callable.params().map_or(0, |params| params.len());
+            args.truncate(accepted);
+        }
+    }
+
+    interpreter
+        .call_value(env, callback.clone(), args, call_pos)
+        .map_err(|err| EvalError::CallbackFailed(func.name(&interpreter.names()), Box::new(err)))
+}
+
+// predicates passed to filter, find, some and every must return booleans
+fn call_predicate(
+    func: &LoxFunction,
+    interpreter: &Interpreter,
+    env: &Environment,
+    predicate: &Value,
+    args: Vec<Value>,
+    call_pos: Position,
+) -> EvalResult<bool> {
+    match call_back(func, interpreter, env, predicate, args, call_pos)? {
+        Value::Boolean(b) => Ok(b),
+        val => Err(EvalError::UnexpectedCallbackReturnType(
+            call_pos,
+            func.name(&interpreter.names()),
+            "boolean".into(),
+            val.type_(),
+        )),
+    }
+}
+
+// the order of two values when sort is called without a comparator
+fn default_order(a: &Value, b: &Value, call_pos: Position) -> EvalResult<Ordering> {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => Ok(a.partial_cmp(b).unwrap_or(Ordering::Equal)),
+        (Value::String(a), Value::String(b)) => Ok(a.cmp(b)),
+        _ => Err(EvalError::UnexpectedBinaryOperatorOperands(
+            call_pos,
+            BinaryOperator::Less,
+            a.type_(),
+            b.type_(),
+        )),
+    }
+}
+
+// the order given by a comparator returning a negative, zero or positive number
+fn comparator_order(
+    func: &LoxFunction,
+    interpreter: &Interpreter,
+    env: &Environment,
+    comparator: &Value,
+    (a, b): (&Value, &Value),
+    call_pos: Position,
+) -> EvalResult<Ordering> {
+    let args = vec![a.clone(), b.clone()];
+
+    match call_back(func, interpreter, env, comparator, args, call_pos)? {
+        Value::Number(n) => Ok(n.partial_cmp(&0f64).unwrap_or(Ordering::Equal)),
+        val => Err(EvalError::UnexpectedCallbackReturnType(
+            call_pos,
+            func.name(&interpreter.names()),
+            "number".into(),
+            val.type_(),
+        )),
+    }
+}
+
+// a stable sort which stops at the first error, unlike sort_by it can't
+// panic when a comparator isn't a total order
+fn merge_sort<F>(mut values: Vec<Value>, order: &mut F) -> EvalResult<Vec<Value>>
+where
+    F: FnMut(&Value, &Value) -> EvalResult<Ordering>,
+{
+    if values.len() <= 1 {
+        return Ok(values);
+    }
+
+    let right = values.split_off(values.len() / 2);
+    let left = merge_sort(values, order)?;
+    let right = merge_sort(right, order)?;
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+
+    while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+        // equal values keep their order
+        if order(b, a)? == Ordering::Less {
+            merged.extend(right.next());
+        } else {
+            merged.extend(left.next());
+        }
+    }
+
+    merged.extend(left);
+    merged.extend(right);
+    Ok(merged)
+}
+
+// parents are the arrays being flattened, an array nested in itself is a cycle
+fn flatten(
+    parents: &mut Vec<Rc<RefCell<Vec<Value>>>>,
+    depth: f64,
+    flattened: &mut Vec<Value>,
+    call_pos: Position,
+) -> EvalResult<()> {
+    let values = parents
+        .last()
+        .expect("flattening an array")
+        .borrow()
+        .clone();
+
+    for value in values {
+        match shared_array_values(&value) {
+            Some(nested) if depth >= 1f64 => {
+                if parents.iter().any(|parent| Rc::ptr_eq(parent, &nested)) {
+                    return Err(EvalError::CyclicArray(call_pos));
+                }
+
+                parents.push(nested);
+                flatten(parents, depth - 1f64, flattened, call_pos)?;
+                parents.pop();
+            }
+            _ => flattened.push(value),
+        }
+    }
+
+    Ok(())
+}
+
+// values passed to concat, with the array being concatenated to itself
+// read from its already borrowed natives
+fn concatenated_values(this: &LoxInstance, own: &[Value], value: &Value) -> Vec<Value> {
+    match value {
+        Value::Instance(instance) if instance == this => own.to_vec(),
+        _ => array_values(value).unwrap_or_else(|| vec![value.clone()]),
+    }
+}
+
 pub fn create_elox_array_class(
     env: &Environment,
     identifiers: &mut IdentifierHandlesGenerator,
@@ -105,30 +375,35 @@ pub fn create_elox_array_class(
                 |_this: &LoxInstance,
                  natives: &mut NativesMap,
                  _func: &LoxFunction,
-                 _interpreter: &Interpreter,
+                 interpreter: &Interpreter,
                  _env: &Environment,
                  args: Vec<Value>,
                  call_pos: Position| {
                     let values = natives.get(&vec_handle()).unwrap().into_vec().borrow();
 
+                    // arr[a..b] is a slice
+                    if let Some((start, end, inclusive)) = range_bounds(&args[0]) {
+                        let (start, end) = slice_bounds(start, end, inclusive, values.len());
+                        return Ok(new_elox_array(values[start..end].to_vec(), interpreter));
+                    }
+
                     match args[0] {
                         Value::Number(n) => {
-                            let idx = n.floor() as usize;
-                            if idx >= values.len() {
-                                return Err(EvalError::ArrayIndexOutOfBounds(
-                                    call_pos,
-                                    idx,
-                                    values.len(),
-                                ));
-                            }
-                            if n < 0f64 || idx >= values.len() || n % 1f64 != 0f64 {
+                            if n % 1f64 != 0f64 {
                                 return Ok(Value::Nil);
                             }
 
-                            return Ok(values[idx].clone());
+                            match element_index(n, values.len()) {
+                                Some(idx) => Ok(values[idx].clone()),
+                                None => Err(EvalError::ArrayIndexOutOfBounds(
+                                    call_pos,
+                                    n as isize,
+                                    values.len(),
+                                )),
+                            }
                         }
-                        _ => return Ok(Value::Nil),
-                    };
+                        _ => Ok(Value::Nil),
+                    }
                 },
             ),
             env.clone(),
@@ -154,17 +429,18 @@ pub fn create_elox_array_class(
                     let mut values = natives.get(&vec_handle()).unwrap().into_vec().borrow_mut();
 
                     match args[0] {
-                        Value::Number(n) => {
-                            let idx = n.floor() as usize;
-                            if n < 0f64 || idx >= values.len() || n % 1f64 != 0f64 {
-                                return Ok(Value::Boolean(false));
+                        Value::Number(n) if n % 1f64 == 0f64 => {
+                            match element_index(n, values.len()) {
+                                Some(idx) => {
+                                    values[idx] = args[1].clone();
+                                    Ok(Value::Boolean(true))
+                                }
+                                None => Ok(Value::Boolean(false)),
                             }
-
-                            values[idx] = args[1].clone();
-                            return Ok(Value::Boolean(true));
                         }
-                        _ => return Ok(Value::Nil),
-                    };
+                        Value::Number(_) => Ok(Value::Boolean(false)),
+                        _ => Ok(Value::Nil),
+                    }
                 },
             ),
             env.clone(),
@@ -177,6 +453,32 @@ pub fn create_elox_array_class(
         )),
     );
 
+    let index_of_handle = identifiers.by_name("indexOf");
+
+    methods.insert(
+        index_of_handle,
+        Rc::new(LoxFunction::new_native_method(
+            Rc::new(
+                |_this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 _func: &LoxFunction,
+                 _interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 _call_pos: Position| {
+                    let values = natives.get(&vec_handle()).unwrap().into_vec().borrow();
+                    let idx = values.iter().position(|val| *val == args[0]);
+
+                    Ok(idx.map_or(Value::Nil, |idx| Value::Number(idx as f64)))
+                },
+            ),
+            env.clone(),
+            false,
+            Some(Rc::new(vec![Required(identifiers.by_name("value"))])),
+            index_of_handle,
+        )),
+    );
+
     let length_handle = identifiers.by_name("length");
 
     methods.insert(
@@ -233,5 +535,534 @@ pub fn create_elox_array_class(
         )),
     );
 
+    let join_handle = identifiers.by_name("join");
+
+    methods.insert(
+        join_handle,
+        Rc::new(LoxFunction::new_native_method(
+            Rc::new(
+                |_this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 _func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let values = natives.get(&vec_handle()).unwrap().into_vec().borrow();
+                    let separator = args[0].to_str(interpreter, call_pos)?;
+
+                    let strings = values
+                        .iter()
+                        .map(|val| val.to_str(interpreter, call_pos))
+                        .collect::<EvalResult<Vec<String>>>()?;
+
+                    Ok(Value::String(strings.join(&separator)))
+                },
+            ),
+            env.clone(),
+            false,
+            Some(Rc::new(vec![DefaultValued(
+                identifiers.by_name("separator"),
+                Value::String(String::new()),
+            )])),
+            join_handle,
+        )),
+    );
+
+    let slice_handle = identifiers.by_name("slice");
+
+    methods.insert(
+        slice_handle,
+        array_method(
+            env,
+            slice_handle,
+            Some(Rc::new(vec![
+                DefaultValued(identifiers.by_name("start"), Value::Number(0f64)),
+                DefaultValued(identifiers.by_name("end"), Value::Nil),
+            ])),
+            Rc::new(
+                |_this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let values = natives.get(&vec_handle()).unwrap().into_vec().borrow();
+                    let start = number_arg(func, interpreter, &args[0], call_pos)?;
+                    let end = match args[1] {
+                        Value::Nil => values.len() as f64,
+                        ref end => number_arg(func, interpreter, end, call_pos)?,
+                    };
+
+                    let (start, end) = slice_bounds(start, end, false, values.len());
+                    Ok(new_elox_array(values[start..end].to_vec(), interpreter))
+                },
+            ),
+        ),
+    );
+
+    let reverse_handle = identifiers.by_name("reverse");
+
+    methods.insert(
+        reverse_handle,
+        array_method(
+            env,
+            reverse_handle,
+            None,
+            Rc::new(
+                |this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 _func: &LoxFunction,
+                 _interpreter: &Interpreter,
+                 _env: &Environment,
+                 _args: Vec<Value>,
+                 _call_pos: Position| {
+                    let mut values = natives.get(&vec_handle()).unwrap().into_vec().borrow_mut();
+                    values.reverse();
+
+                    Ok(Value::Instance(this.clone()))
+                },
+            ),
+        ),
+    );
+
+    let concat_handle = identifiers.by_name("concat");
+
+    methods.insert(
+        concat_handle,
+        array_method(
+            env,
+            concat_handle,
+            Some(Rc::new(vec![Rest(identifiers.by_name("values"))])),
+            Rc::new(
+                |this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 _func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 _call_pos: Position| {
+                    let values = natives.get(&vec_handle()).unwrap().into_vec().borrow();
+                    let mut concatenated = values.clone();
+
+                    // arrays are flattened one level, any other value is appended as is
+                    for value in array_values(&args[0]).unwrap() {
+                        concatenated.extend(concatenated_values(this, &values, &value));
+                    }
+
+                    Ok(new_elox_array(concatenated, interpreter))
+                },
+            ),
+        ),
+    );
+
+    let insert_handle = identifiers.by_name("insert");
+
+    methods.insert(
+        insert_handle,
+        array_method(
+            env,
+            insert_handle,
+            Some(Rc::new(vec![
+                Required(identifiers.by_name("idx")),
+                Required(identifiers.by_name("value")),
+            ])),
+            Rc::new(
+                |this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let mut values = natives.get(&vec_handle()).unwrap().into_vec().borrow_mut();
+                    let n = number_arg(func, interpreter, &args[0], call_pos)?.floor();
+
+                    // inserting at the length appends the value
+                    let idx = normalize_index(n, values.len());
+                    if idx < 0f64 || idx > values.len() as f64 {
+                        return Err(EvalError::ArrayIndexOutOfBounds(
+                            call_pos,
+                            n as isize,
+                            values.len(),
+                        ));
+                    }
+
+                    values.insert(idx as usize, args[1].clone());
+                    Ok(Value::Instance(this.clone()))
+                },
+            ),
+        ),
+    );
+
+    let remove_at_handle = identifiers.by_name("removeAt");
+
+    methods.insert(
+        remove_at_handle,
+        array_method(
+            env,
+            remove_at_handle,
+            Some(Rc::new(vec![Required(identifiers.by_name("idx"))])),
+            Rc::new(
+                |_this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let mut values = natives.get(&vec_handle()).unwrap().into_vec().borrow_mut();
+                    let n = number_arg(func, interpreter, &args[0], call_pos)?.floor();
+
+                    match element_index(n, values.len()) {
+                        Some(idx) => Ok(values.remove(idx)),
+                        None => Err(EvalError::ArrayIndexOutOfBounds(
+                            call_pos,
+                            n as isize,
+                            values.len(),
+                        )),
+                    }
+                },
+            ),
+        ),
+    );
+
+    let map_handle = identifiers.by_name("map");
+
+    methods.insert(
+        map_handle,
+        higher_order_method(
+            env,
+            map_handle,
+            Some(Rc::new(vec![Required(identifiers.by_name("callback"))])),
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let callback = callback_arg(func, interpreter, &args[0], call_pos)?;
+                    let values = this_values(func).borrow().clone();
+
+                    let mapped = values
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, value)| {
+                            let args = vec![value, Value::Number(idx as f64)];
+                            call_back(func, interpreter, env, &callback, args, call_pos)
+                        })
+                        .collect::<EvalResult<Vec<Value>>>()?;
+
+                    Ok(new_elox_array(mapped, interpreter))
+                },
+            ),
+        ),
+    );
+
+    let filter_handle = identifiers.by_name("filter");
+
+    methods.insert(
+        filter_handle,
+        higher_order_method(
+            env,
+            filter_handle,
+            Some(Rc::new(vec![Required(identifiers.by_name("predicate"))])),
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let predicate = callback_arg(func, interpreter, &args[0], call_pos)?;
+                    let values = this_values(func).borrow().clone();
+                    let mut filtered = vec![];
+
+                    for (idx, value) in values.into_iter().enumerate() {
+                        let args = vec![value.clone(), Value::Number(idx as f64)];
+                        if call_predicate(func, interpreter, env, &predicate, args, call_pos)? {
+                            filtered.push(value);
+                        }
+                    }
+
+                    Ok(new_elox_array(filtered, interpreter))
+                },
+            ),
+        ),
+    );
+
+    let reduce_handle = identifiers.by_name("reduce");
+
+    methods.insert(
+        reduce_handle,
+        higher_order_method(
+            env,
+            reduce_handle,
+            Some(Rc::new(vec![
+                Required(identifiers.by_name("callback")),
+                Rest(identifiers.by_name("initial")),
+            ])),
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let callback = callback_arg(func, interpreter, &args[0], call_pos)?;
+                    let values = this_values(func).borrow().clone();
+                    let mut values = values.into_iter().enumerate();
+
+                    // without an initial value, the first element is the initial accumulator
+                    let initial = array_values(&args[1]).unwrap().into_iter().next();
+                    let mut acc = match initial.or_else(|| values.next().map(|(_, val)| val)) {
+                        Some(acc) => acc,
+                        None => return Ok(Value::Nil),
+                    };
+
+                    for (idx, value) in values {
+                        let args = vec![acc, value, Value::Number(idx as f64)];
+                        acc = call_back(func, interpreter, env, &callback, args, call_pos)?;
+                    }
+
+                    Ok(acc)
+                },
+            ),
+        ),
+    );
+
+    let for_each_handle = identifiers.by_name("forEach");
+
+    methods.insert(
+        for_each_handle,
+        higher_order_method(
+            env,
+            for_each_handle,
+            Some(Rc::new(vec![Required(identifiers.by_name("callback"))])),
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let callback = callback_arg(func, interpreter, &args[0], call_pos)?;
+                    let values = this_values(func).borrow().clone();
+
+                    for (idx, value) in values.into_iter().enumerate() {
+                        let args = vec![value, Value::Number(idx as f64)];
+                        call_back(func, interpreter, env, &callback, args, call_pos)?;
+                    }
+
+                    Ok(Value::Nil)
+                },
+            ),
+        ),
+    );
+
+    let find_handle = identifiers.by_name("find");
+
+    methods.insert(
+        find_handle,
+        higher_order_method(
+            env,
+            find_handle,
+            Some(Rc::new(vec![Required(identifiers.by_name("predicate"))])),
+            Rc::new(
+                move |func: &LoxFunction,
+                      interpreter: &Interpreter,
+                      env: &Environment,
+                      args: Vec<Value>,
+                      call_pos: Position| {
+                    let predicate = callback_arg(func, interpreter, &args[0], call_pos)?;
+                    let values = this_values(func).borrow().clone();
+
+                    for (idx, value) in values.into_iter().enumerate() {
+                        let args = vec![value.clone(), Value::Number(idx as f64)];
+                        if call_predicate(func, interpreter, env, &predicate, args, call_pos)? {
+                            return Ok(value);
+                        }
+                    }
+
+                    Ok(Value::Nil)
+                },
+            ),
+        ),
+    );
+
+    let some_handle = identifiers.by_name("some");
+
+    methods.insert(
+        some_handle,
+        higher_order_method(
+            env,
+            some_handle,
+            Some(Rc::new(vec![Required(identifiers.by_name("predicate"))])),
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let predicate = callback_arg(func, interpreter, &args[0], call_pos)?;
+                    let values = this_values(func).borrow().clone();
+
+                    for (idx, value) in values.into_iter().enumerate() {
+                        let args = vec![value, Value::Number(idx as f64)];
+                        if call_predicate(func, interpreter, env, &predicate, args, call_pos)? {
+                            return Ok(Value::Boolean(true));
+                        }
+                    }
+
+                    Ok(Value::Boolean(false))
+                },
+            ),
+        ),
+    );
+
+    let every_handle = identifiers.by_name("every");
+
+    methods.insert(
+        every_handle,
+        higher_order_method(
+            env,
+            every_handle,
+            Some(Rc::new(vec![Required(identifiers.by_name("predicate"))])),
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let predicate = callback_arg(func, interpreter, &args[0], call_pos)?;
+                    let values = this_values(func).borrow().clone();
+
+                    for (idx, value) in values.into_iter().enumerate() {
+                        let args = vec![value, Value::Number(idx as f64)];
+                        if !call_predicate(func, interpreter, env, &predicate, args, call_pos)? {
+                            return Ok(Value::Boolean(false));
+                        }
+                    }
+
+                    Ok(Value::Boolean(true))
+                },
+            ),
+        ),
+    );
+
+    let sort_handle = identifiers.by_name("sort");
+
+    methods.insert(
+        sort_handle,
+        higher_order_method(
+            env,
+            sort_handle,
+            Some(Rc::new(vec![DefaultValued(
+                identifiers.by_name("comparator"),
+                Value::Nil,
+            )])),
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let comparator = match args[0] {
+                        Value::Nil => None,
+                        ref arg => Some(callback_arg(func, interpreter, arg, call_pos)?),
+                    };
+
+                    let array = this_values(func);
+                    let values = array.borrow().clone();
+
+                    let values = merge_sort(values, &mut |a, b| match &comparator {
+                        Some(comparator) => {
+                            comparator_order(func, interpreter, env, comparator, (a, b), call_pos)
+                        }
+                        None => default_order(a, b, call_pos),
+                    })?;
+
+                    *array.borrow_mut() = values;
+                    Ok(func.env.get(0, Identifier::this()).unwrap())
+                },
+            ),
+        ),
+    );
+
+    let flat_handle = identifiers.by_name("flat");
+
+    methods.insert(
+        flat_handle,
+        higher_order_method(
+            env,
+            flat_handle,
+            Some(Rc::new(vec![DefaultValued(
+                identifiers.by_name("depth"),
+                Value::Number(1f64),
+            )])),
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let depth = number_arg(func, interpreter, &args[0], call_pos)?;
+                    let mut flattened = vec![];
+
+                    flatten(
+                        &mut vec![this_values(func)],
+                        depth,
+                        &mut flattened,
+                        call_pos,
+                    )?;
+                    Ok(new_elox_array(flattened, interpreter))
+                },
+            ),
+        ),
+    );
+
+    let zip_handle = identifiers.by_name("zip");
+
+    methods.insert(
+        zip_handle,
+        higher_order_method(
+            env,
+            zip_handle,
+            Some(Rc::new(vec![Rest(identifiers.by_name("others"))])),
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let mut arrays = vec![this_values(func).borrow().clone()];
+
+                    for other in array_values(&args[0]).unwrap() {
+                        match iterable_values(&other) {
+                            Some(values) => arrays.push(values),
+                            None => {
+                                return Err(EvalError::UnexpectedArgumentType(
+                                    call_pos,
+                                    func.name(&interpreter.names()),
+                                    "Array".into(),
+                                    other.type_(),
+                                ))
+                            }
+                        }
+                    }
+
+                    // as long as the shortest array
+                    let len = arrays.iter().map(|values| values.len()).min().unwrap();
+                    let zipped = (0..len)
+                        .map(|idx| {
+                            let tuple = arrays.iter().map(|values| values[idx].clone());
+                            new_elox_array(tuple.collect(), interpreter)
+                        })
+                        .collect();
+
+                    Ok(new_elox_array(zipped, interpreter))
+                },
+            ),
+        ),
+    );
+
     LoxClass::new_native(Identifier::array(), None, methods)
 }
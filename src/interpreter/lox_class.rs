@@ -1,3 +1,4 @@
+use super::eval::Eval;
 use super::eval_result::EvalResult;
 use super::lox_callable::LoxCallable;
 use super::lox_function::LoxFunction;
@@ -6,23 +7,85 @@ use super::lox_instance::LoxInstance;
 use super::Environment;
 use super::Interpreter;
 use super::Value;
-use crate::parser::{Identifier, IdentifierHandle, IdentifierNames};
+use crate::parser::expressions::ExprCtx;
+use crate::parser::{Identifier, IdentifierHandle, IdentifierNames, IdentifierUseHandle};
 use crate::scanner::token::Position;
 use fnv::FnvHashMap;
 use std::fmt;
 use std::rc::Rc;
 
+pub type Methods = FnvHashMap<IdentifierHandle, Rc<LoxFunction>>;
+
+// the members of a class besides its instance methods
+#[derive(Clone, Default)]
+pub struct ClassMembers {
+    pub static_methods: Methods,
+    pub getters: Methods,
+    pub setters: Methods,
+    pub fields: Vec<(IdentifierHandle, ExprCtx)>, // initialized before init is called
+    pub env: Option<Environment>,                 // where the field initializers are evaluated
+    pub private: Vec<IdentifierHandle>,
+    pub declaration: Option<IdentifierUseHandle>, // private members belong to it, not to the name
+}
+
 #[derive(Clone)]
 pub struct _LoxClass {
     pub identifier: IdentifierHandle,
     pub superclass: Option<Rc<LoxClass>>,
-    pub methods: FnvHashMap<IdentifierHandle, Rc<LoxFunction>>,
+    pub methods: Methods,
+    pub members: ClassMembers,
+}
+
+impl _LoxClass {
+    fn find_member(
+        &self,
+        name: IdentifierHandle,
+        members: fn(&_LoxClass) -> &Methods,
+    ) -> Option<Rc<LoxFunction>> {
+        if let Some(func) = members(self).get(&name) {
+            return Some(Rc::clone(func));
+        }
+
+        match &self.superclass {
+            Some(parent) => parent.mold.find_member(name, members),
+            None => None,
+        }
+    }
+
+    pub fn find_getter(&self, name: IdentifierHandle) -> Option<Rc<LoxFunction>> {
+        self.find_member(name, |class| &class.members.getters)
+    }
+
+    pub fn find_setter(&self, name: IdentifierHandle) -> Option<Rc<LoxFunction>> {
+        self.find_member(name, |class| &class.members.setters)
+    }
+
+    // the declaration of the class declaring 'name' as private,
+    // unless a subclass redeclares it as public
+    pub fn private_owner(&self, name: IdentifierHandle) -> Option<IdentifierUseHandle> {
+        if self.members.private.contains(&name) {
+            return self.members.declaration;
+        }
+
+        let members = &self.members;
+        let is_public = self.methods.contains_key(&name)
+            || members.static_methods.contains_key(&name)
+            || members.getters.contains_key(&name)
+            || members.setters.contains_key(&name)
+            || members.fields.iter().any(|(field, _)| *field == name);
+
+        match &self.superclass {
+            Some(parent) if !is_public => parent.mold.private_owner(name),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct LoxClass {
     pub mold: Rc<_LoxClass>,
     use_natives: bool,
+    initializer: Option<Rc<LoxFunction>>, // the overload of init selected for a call
 }
 
 impl LoxClass {
@@ -30,14 +93,25 @@ impl LoxClass {
         identifier: IdentifierHandle,
         superclass: Option<Rc<LoxClass>>,
         methods: FnvHashMap<IdentifierHandle, Rc<LoxFunction>>,
+    ) -> LoxClass {
+        LoxClass::with_members(identifier, superclass, methods, ClassMembers::default())
+    }
+
+    pub fn with_members(
+        identifier: IdentifierHandle,
+        superclass: Option<Rc<LoxClass>>,
+        methods: FnvHashMap<IdentifierHandle, Rc<LoxFunction>>,
+        members: ClassMembers,
     ) -> LoxClass {
         LoxClass {
             mold: Rc::new(_LoxClass {
                 identifier,
                 superclass,
                 methods,
+                members,
             }),
             use_natives: false,
+            initializer: None,
         }
     }
 
@@ -51,8 +125,26 @@ impl LoxClass {
                 identifier,
                 superclass,
                 methods,
+                members: ClassMembers::default(),
             }),
             use_natives: false,
+            initializer: None,
+        }
+    }
+
+    // the same class, constructed with an overload of init
+    pub fn with_initializer(&self, initializer: Rc<LoxFunction>) -> LoxClass {
+        LoxClass {
+            mold: Rc::clone(&self.mold),
+            use_natives: self.use_natives,
+            initializer: Some(initializer),
+        }
+    }
+
+    fn find_initializer(&self) -> Option<Rc<LoxFunction>> {
+        match &self.initializer {
+            Some(initializer) => Some(Rc::clone(initializer)),
+            None => self.find_method(Identifier::init()),
         }
     }
 
@@ -69,6 +161,30 @@ impl LoxClass {
 
         None
     }
+
+    pub fn find_static_method(&self, name: IdentifierHandle) -> Option<Rc<LoxFunction>> {
+        self.mold
+            .find_member(name, |class| &class.members.static_methods)
+    }
+
+    // the fields of the superclasses are initialized first
+    fn init_fields(&self, instance: &LoxInstance, interpreter: &Interpreter) -> EvalResult<()> {
+        if let Some(parent) = &self.mold.superclass {
+            parent.init_fields(instance, interpreter)?;
+        }
+
+        if let Some(env) = &self.mold.members.env {
+            let env = Environment::new(Some(env));
+            env.define(Identifier::this(), Value::Instance(instance.clone()));
+
+            for (field, initializer) in &self.mold.members.fields {
+                let value = interpreter.eval(&env, initializer)?;
+                instance.set_field(*field, &value);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl LoxCallable for LoxClass {
@@ -84,7 +200,10 @@ impl LoxCallable for LoxClass {
         } else {
             LoxInstance::new_native(Rc::clone(&self.mold))
         };
-        if let Some(initializer) = self.find_method(Identifier::init()) {
+
+        self.init_fields(&instance, interpreter)?;
+
+        if let Some(initializer) = self.find_initializer() {
             let bound_init = initializer.bind(&instance);
             bound_init.call(interpreter, &bound_init.env, args, call_pos)?;
         }
@@ -93,7 +212,7 @@ impl LoxCallable for LoxClass {
     }
 
     fn params(&self) -> LoxFunctionParams {
-        if let Some(initializer) = self.find_method(Identifier::init()) {
+        if let Some(initializer) = self.find_initializer() {
             return initializer.params();
         }
 
@@ -105,7 +224,7 @@ impl LoxCallable for LoxClass {
     }
 
     fn has_rest_param(&self) -> bool {
-        if let Some(initializer) = self.find_method(Identifier::init()) {
+        if let Some(initializer) = self.find_initializer() {
             return initializer.has_rest_param();
         }
 
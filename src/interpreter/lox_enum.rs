@@ -0,0 +1,183 @@
+use super::eval_result::EvalResult;
+use super::lox_callable::LoxCallable;
+use super::lox_class::LoxClass;
+use super::lox_function::LoxFunction;
+use super::lox_instance::LoxInstance;
+use super::value::{CallableValue, Value};
+use super::Environment;
+use super::Interpreter;
+use crate::parser::expressions::ContextLessFuncParam::Required;
+use crate::parser::{Identifier, IdentifierHandle, IdentifierHandlesGenerator};
+use crate::resolver::BUILTIN_ENUMS;
+use crate::scanner::token::Position;
+use fnv::FnvHashMap;
+use std::rc::Rc;
+
+// An enum is a class whose variants are subclasses:
+// enum Shape { Circle(r), Empty } defines the class Shape, the Circle constructor
+// and Empty, the only instance of the Empty variant class
+
+// name, fields (None for a variant without fields)
+pub type EnumVariantDecl = (IdentifierHandle, Option<Vec<IdentifierHandle>>);
+
+fn this_instance(func: &LoxFunction) -> LoxInstance {
+    func.env
+        .get(0, Identifier::this())
+        .and_then(|this| this.into_instance())
+        .expect("Could not find 'this' in enum variant method")
+}
+
+fn variant_init(env: &Environment, fields: &[IdentifierHandle]) -> Rc<LoxFunction> {
+    let params = fields.iter().map(|field| Required(*field)).collect();
+    let fields = fields.to_vec();
+
+    Rc::new(LoxFunction::new_native(
+        Rc::new(
+            move |func: &LoxFunction,
+                  _interpreter: &Interpreter,
+                  _env: &Environment,
+                  args: Vec<Value>,
+                  _call_pos: Position| {
+                let this = this_instance(func);
+
+                for (field, value) in fields.iter().zip(args) {
+                    this.set_field(*field, &value);
+                }
+
+                Ok(Value::Instance(this))
+            },
+        ),
+        env.clone(),
+        true,
+        Some(Rc::new(params)),
+        Identifier::init(),
+    ))
+}
+
+// Circle(2), Empty
+fn variant_to_str(
+    env: &Environment,
+    variant: IdentifierHandle,
+    fields: Option<Vec<IdentifierHandle>>,
+) -> Rc<LoxFunction> {
+    Rc::new(LoxFunction::new_native(
+        Rc::new(
+            move |func: &LoxFunction,
+                  interpreter: &Interpreter,
+                  _env: &Environment,
+                  _args: Vec<Value>,
+                  call_pos: Position| {
+                let name = interpreter.name(variant);
+
+                match &fields {
+                    Some(fields) => {
+                        let this = this_instance(func);
+                        let values = fields
+                            .iter()
+                            .map(|field| this.get_field(*field).unwrap_or(Value::Nil))
+                            .map(|val| val.to_str(interpreter, call_pos))
+                            .collect::<EvalResult<Vec<String>>>()?;
+
+                        Ok(Value::String(format!("{}({})", name, values.join(", "))))
+                    }
+                    None => Ok(Value::String(name)),
+                }
+            },
+        ),
+        env.clone(),
+        false,
+        None,
+        Identifier::str_(),
+    ))
+}
+
+// returns the enum class and the value bound to each variant name
+pub fn create_elox_enum(
+    identifier: IdentifierHandle,
+    variants: &[EnumVariantDecl],
+    env: &Environment,
+) -> (Value, Vec<(IdentifierHandle, Value)>) {
+    let enum_class = Rc::new(LoxClass::new(identifier, None, FnvHashMap::default()));
+
+    let values = variants
+        .iter()
+        .map(|(name, fields)| {
+            let mut methods = FnvHashMap::default();
+            methods.insert(Identifier::str_(), variant_to_str(env, *name, fields.clone()));
+
+            if let Some(fields) = fields {
+                methods.insert(Identifier::init(), variant_init(env, fields));
+            }
+
+            let class = Rc::new(LoxClass::new(*name, Some(Rc::clone(&enum_class)), methods));
+
+            let value = if fields.is_some() {
+                Value::Callable(CallableValue::Class(class))
+            } else {
+                Value::Instance(LoxInstance::new(Rc::clone(&class.mold)))
+            };
+
+            (*name, value)
+        })
+        .collect();
+
+    (Value::Callable(CallableValue::Class(enum_class)), values)
+}
+
+pub fn register_builtin_enums(env: &Environment, identifiers: &mut IdentifierHandlesGenerator) {
+    for (name, variants) in BUILTIN_ENUMS {
+        let variants = variants
+            .iter()
+            .map(|(variant, fields)| {
+                (
+                    identifiers.by_name(variant),
+                    fields.map(|fields| fields.iter().map(|f| identifiers.by_name(f)).collect()),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let (enum_class, values) = create_elox_enum(identifiers.by_name(name), &variants, env);
+        env.define(identifiers.by_name(name), enum_class);
+
+        for (variant, value) in values {
+            env.define(variant, value);
+        }
+    }
+}
+
+// lets natives return Ok(value) or Err(error), the variant classes are kept
+// when the natives are created so that scripts can shadow Ok and Err
+pub struct EloxResultEnum {
+    ok: Rc<dyn LoxCallable>,
+    err: Rc<dyn LoxCallable>,
+}
+
+impl EloxResultEnum {
+    // the builtin enums must already be registered in env
+    pub fn new(env: &Environment, identifiers: &mut IdentifierHandlesGenerator) -> EloxResultEnum {
+        let mut variant = |name| match env.get(0, identifiers.by_name(name)) {
+            Some(Value::Callable(variant)) => variant.into_callable(),
+            _ => panic!("Result variants must be classes defined in the global scope"),
+        };
+
+        EloxResultEnum {
+            ok: variant("Ok"),
+            err: variant("Err"),
+        }
+    }
+
+    pub fn wrap(
+        &self,
+        value: Result<Value, Value>,
+        interpreter: &Interpreter,
+        env: &Environment,
+        call_pos: Position,
+    ) -> EvalResult<Value> {
+        let (variant, value) = match value {
+            Ok(value) => (&self.ok, value),
+            Err(error) => (&self.err, error),
+        };
+
+        variant.call(interpreter, env, vec![value], call_pos)
+    }
+}
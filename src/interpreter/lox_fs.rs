@@ -0,0 +1,183 @@
+use super::host::{FsResult, FsWrite};
+use super::lox_array::new_elox_array;
+use super::lox_class::LoxClass;
+use super::lox_enum::EloxResultEnum;
+use super::lox_function::{LoxFunction, LoxFunctionParams, NativeFunction};
+use super::lox_instance::LoxInstance;
+use super::lox_string::string_arg;
+use super::value::Value;
+use super::Environment;
+use super::Interpreter;
+use crate::parser::expressions::ContextLessFuncParam::*;
+use crate::parser::{IdentifierHandle, IdentifierHandlesGenerator};
+use crate::scanner::token::Position;
+use fnv::FnvHashMap;
+use std::rc::Rc;
+
+fn fs_function(
+    env: &Environment,
+    name: IdentifierHandle,
+    params: LoxFunctionParams,
+    method: Rc<NativeFunction>,
+) -> Rc<LoxFunction> {
+    Rc::new(LoxFunction::new_native(
+        method,
+        env.clone(),
+        false,
+        params,
+        name,
+    ))
+}
+
+type WriteOperation = fn(&Interpreter) -> FsWrite;
+
+// host failures are returned to the script as Err(msg)
+fn into_result(res: FsResult<Value>) -> Result<Value, Value> {
+    res.map_err(Value::String)
+}
+
+// fs is an instance of a native class whose operations are delegated to the host
+pub fn create_elox_fs_namespace(
+    env: &Environment,
+    identifiers: &mut IdentifierHandlesGenerator,
+) -> LoxInstance {
+    let mut methods = FnvHashMap::default();
+    let result = Rc::new(EloxResultEnum::new(env, identifiers));
+    let path = identifiers.by_name("path");
+    let contents = identifiers.by_name("contents");
+
+    let read_file_handle = identifiers.by_name("readFile");
+    let res = Rc::clone(&result);
+
+    methods.insert(
+        read_file_handle,
+        fs_function(
+            env,
+            read_file_handle,
+            Some(Rc::new(vec![Required(path)])),
+            Rc::new(
+                move |func: &LoxFunction,
+                      interpreter: &Interpreter,
+                      env: &Environment,
+                      args: Vec<Value>,
+                      call_pos: Position| {
+                    let path = string_arg(func, interpreter, &args[0], call_pos)?;
+                    let read = (interpreter.host.fs.read_file)(&path).map(Value::String);
+
+                    res.wrap(into_result(read), interpreter, env, call_pos)
+                },
+            ),
+        ),
+    );
+
+    let writes: [(&str, WriteOperation); 2] = [
+        ("writeFile", |interpreter| {
+            Rc::clone(&interpreter.host.fs.write_file)
+        }),
+        ("appendFile", |interpreter| {
+            Rc::clone(&interpreter.host.fs.append_file)
+        }),
+    ];
+
+    for (name, operation) in writes.iter() {
+        let handle = identifiers.by_name(name);
+        let operation = *operation;
+        let res = Rc::clone(&result);
+
+        methods.insert(
+            handle,
+            fs_function(
+                env,
+                handle,
+                Some(Rc::new(vec![Required(path), Required(contents)])),
+                Rc::new(
+                    move |func: &LoxFunction,
+                          interpreter: &Interpreter,
+                          env: &Environment,
+                          args: Vec<Value>,
+                          call_pos: Position| {
+                        let path = string_arg(func, interpreter, &args[0], call_pos)?;
+                        let contents = args[1].to_str(interpreter, call_pos)?;
+                        let written = operation(interpreter)(&path, &contents).map(|_| Value::Nil);
+
+                        res.wrap(into_result(written), interpreter, env, call_pos)
+                    },
+                ),
+            ),
+        );
+    }
+
+    let exists_handle = identifiers.by_name("exists");
+
+    methods.insert(
+        exists_handle,
+        fs_function(
+            env,
+            exists_handle,
+            Some(Rc::new(vec![Required(path)])),
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let path = string_arg(func, interpreter, &args[0], call_pos)?;
+                    Ok(Value::Boolean((interpreter.host.fs.exists)(&path)))
+                },
+            ),
+        ),
+    );
+
+    let list_dir_handle = identifiers.by_name("listDir");
+    let res = Rc::clone(&result);
+
+    methods.insert(
+        list_dir_handle,
+        fs_function(
+            env,
+            list_dir_handle,
+            Some(Rc::new(vec![Required(path)])),
+            Rc::new(
+                move |func: &LoxFunction,
+                      interpreter: &Interpreter,
+                      env: &Environment,
+                      args: Vec<Value>,
+                      call_pos: Position| {
+                    let path = string_arg(func, interpreter, &args[0], call_pos)?;
+                    let names = (interpreter.host.fs.list_dir)(&path).map(|names| {
+                        new_elox_array(names.into_iter().map(Value::String).collect(), interpreter)
+                    });
+
+                    res.wrap(into_result(names), interpreter, env, call_pos)
+                },
+            ),
+        ),
+    );
+
+    let remove_handle = identifiers.by_name("remove");
+
+    methods.insert(
+        remove_handle,
+        fs_function(
+            env,
+            remove_handle,
+            Some(Rc::new(vec![Required(path)])),
+            Rc::new(
+                move |func: &LoxFunction,
+                      interpreter: &Interpreter,
+                      env: &Environment,
+                      args: Vec<Value>,
+                      call_pos: Position| {
+                    let path = string_arg(func, interpreter, &args[0], call_pos)?;
+                    let removed = (interpreter.host.fs.remove)(&path).map(|_| Value::Nil);
+
+                    result.wrap(into_result(removed), interpreter, env, call_pos)
+                },
+            ),
+        ),
+    );
+
+    let class = LoxClass::new(identifiers.by_name("fs"), None, methods);
+
+    LoxInstance::new(Rc::clone(&class.mold))
+}
@@ -47,6 +47,7 @@ pub struct LoxFunction {
     pub name: Option<IdentifierHandle>,
     pub env: Environment,
     pub is_initializer: bool,
+    pub overload: Option<Rc<LoxFunction>>, // the previous declaration with the same name
     has_rest_param: bool,
     params: LoxFunctionParams,
 }
@@ -62,6 +63,7 @@ impl LoxFunction {
             func: Func::Expr(func),
             env,
             is_initializer,
+            overload: None,
             has_rest_param: has_rest_param(&params),
             params,
             name: None,
@@ -79,6 +81,7 @@ impl LoxFunction {
             func: Func::Native(func),
             env,
             is_initializer,
+            overload: None,
             has_rest_param: has_rest_param(&params),
             params,
             name: Some(name),
@@ -96,17 +99,59 @@ impl LoxFunction {
             func: Func::NativeMethod(method),
             env,
             is_initializer,
+            overload: None,
             has_rest_param: has_rest_param(&params),
             params,
             name: Some(name),
         }
     }
 
+    // the minimum and maximum number of arguments accepted, ignoring overloads
+    pub fn arity(&self) -> (usize, usize) {
+        match &self.params {
+            Some(params) => {
+                let min = params.iter().filter(|p| p.is_required()).count();
+                let max = if self.has_rest_param {
+                    usize::MAX
+                } else {
+                    params.len()
+                };
+
+                (min, max)
+            }
+            None => (0, 0),
+        }
+    }
+
+    // the most recent overload accepting arg_count arguments,
+    // self if there are none so that the arity error mentions the last declaration
+    pub fn select_overload(self: &Rc<Self>, arg_count: usize) -> Rc<LoxFunction> {
+        let mut current = self;
+
+        loop {
+            let (min, max) = current.arity();
+
+            if arg_count >= min && arg_count <= max {
+                return Rc::clone(current);
+            }
+
+            match &current.overload {
+                Some(overload) => current = overload,
+                None => return Rc::clone(self),
+            }
+        }
+    }
+
     pub fn bind(&self, instance: &LoxInstance) -> LoxFunction {
+        self.bind_this(Value::Instance(instance.clone()))
+    }
+
+    // binds 'this' to any value, used by native classes wrapping primitives
+    pub fn bind_this(&self, this: Value) -> LoxFunction {
         let new_env = Environment::new(Some(&self.env));
-        new_env.define(Identifier::this(), Value::Instance(instance.clone()));
+        new_env.define(Identifier::this(), this.clone());
 
-        match &self.func {
+        let mut bound = match &self.func {
             Func::Expr(func_expr) => LoxFunction::new(
                 func_expr.clone(),
                 new_env,
@@ -127,7 +172,14 @@ impl LoxFunction {
                 self.params.clone(),
                 self.name.unwrap(),
             ),
-        }
+        };
+
+        bound.overload = self
+            .overload
+            .as_ref()
+            .map(|overload| Rc::new(overload.bind_this(this)));
+
+        bound
     }
 
     pub fn pos(&self) -> Option<Position> {
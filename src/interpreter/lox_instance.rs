@@ -1,11 +1,11 @@
-use super::eval_result::EvalResult;
+use super::eval_result::{EvalError, EvalResult};
 use super::lox_callable::LoxCallable;
-use super::lox_class::_LoxClass;
+use super::lox_class::{LoxClass, _LoxClass};
 use super::lox_function::LoxFunction;
 use super::natives::NativeValue;
 use super::value::{CallableValue, Value};
 use super::Interpreter;
-use crate::parser::IdentifierHandle;
+use crate::parser::{IdentifierHandle, IdentifierUseHandle};
 use crate::scanner::token::Position;
 use fnv::FnvHashMap;
 use std::cell::RefCell;
@@ -18,6 +18,8 @@ pub type InstanceFields = FnvHashMap<IdentifierHandle, Value>;
 pub struct _Instance {
     mold: Rc<_LoxClass>,
     fields: InstanceFields,
+    order: Vec<IdentifierHandle>,     // the fields in insertion order
+    accessing: Vec<IdentifierHandle>, // properties whose accessor is running
     pub natives: Option<NativesMap>,
 }
 
@@ -44,6 +46,8 @@ impl LoxInstance {
                 instance: Rc::new(RefCell::new(_Instance {
                     mold: Rc::clone(&class.mold),
                     fields: FnvHashMap::default(),
+                    order: Vec::new(),
+                    accessing: Vec::new(),
                     natives,
                 })),
             };
@@ -60,6 +64,8 @@ impl LoxInstance {
             instance: Rc::new(RefCell::new(_Instance {
                 mold: Rc::clone(&mold),
                 fields: FnvHashMap::default(),
+                order: Vec::new(),
+                accessing: Vec::new(),
                 natives: None,
             })),
         }
@@ -70,12 +76,54 @@ impl LoxInstance {
             instance: Rc::new(RefCell::new(_Instance {
                 mold: Rc::clone(&mold),
                 fields: FnvHashMap::default(),
+                order: Vec::new(),
+                accessing: Vec::new(),
                 natives: Some(FnvHashMap::default()),
             })),
         }
     }
 
-    pub fn get(&self, prop: IdentifierHandle) -> Option<Value> {
+    // getters are called first, then fields and methods are looked up
+    pub fn get(
+        &self,
+        prop: IdentifierHandle,
+        interpreter: &Interpreter,
+        pos: Position,
+    ) -> EvalResult<Option<Value>> {
+        let getter = self.instance.borrow().mold.find_getter(prop);
+
+        if let Some(getter) = getter.filter(|_| !self.is_accessing(prop)) {
+            return self
+                .call_accessor(&getter, prop, vec![], interpreter, pos)
+                .map(Some);
+        }
+
+        Ok(self.get_field(prop))
+    }
+
+    // inside the accessors of prop, this.prop is the field backing the property
+    fn is_accessing(&self, prop: IdentifierHandle) -> bool {
+        self.instance.borrow().accessing.contains(&prop)
+    }
+
+    fn call_accessor(
+        &self,
+        accessor: &LoxFunction,
+        prop: IdentifierHandle,
+        args: Vec<Value>,
+        interpreter: &Interpreter,
+        pos: Position,
+    ) -> EvalResult<Value> {
+        self.instance.borrow_mut().accessing.push(prop);
+        let bound = accessor.bind(self);
+        let res = bound.call(interpreter, &bound.env, args, pos);
+        self.instance.borrow_mut().accessing.pop();
+
+        res
+    }
+
+    // reads a field or a method without calling getters
+    pub fn get_field(&self, prop: IdentifierHandle) -> Option<Value> {
         if let Some(val) = self.instance.borrow().fields.get(&prop) {
             return Some(val.clone());
         }
@@ -89,10 +137,41 @@ impl LoxInstance {
         None
     }
 
+    // in the order they were first set
+    pub fn fields(&self) -> Vec<(IdentifierHandle, Value)> {
+        let instance = self.instance.borrow();
+
+        instance
+            .order
+            .iter()
+            .map(|prop| (*prop, instance.fields[prop].clone()))
+            .collect()
+    }
+
     pub fn class_name(&self) -> IdentifierHandle {
         self.instance.borrow().mold.identifier
     }
 
+    pub fn private_owner(&self, prop: IdentifierHandle) -> Option<IdentifierUseHandle> {
+        self.instance.borrow().mold.private_owner(prop)
+    }
+
+    // true if the instance's class is class or one of its subclasses
+    pub fn is_instance_of(&self, class: &LoxClass) -> bool {
+        let mut mold = Rc::clone(&self.instance.borrow().mold);
+
+        loop {
+            if Rc::ptr_eq(&mold, &class.mold) {
+                return true;
+            }
+
+            mold = match &mold.superclass {
+                Some(parent) => Rc::clone(&parent.mold),
+                None => return false,
+            };
+        }
+    }
+
     pub fn get_native(self, handle: usize) -> Option<NativeValue> {
         if let Some(natives) = &self.instance.borrow().natives {
             if let Some(native) = natives.get(&handle) {
@@ -139,11 +218,44 @@ impl LoxInstance {
         None
     }
 
-    pub fn set(&self, prop: IdentifierHandle, value: &Value) {
-        self.instance
-            .borrow_mut()
-            .fields
-            .insert(prop, value.clone());
+    // calls the setter of prop if there is one, a property with only a getter is read-only
+    pub fn set(
+        &self,
+        prop: IdentifierHandle,
+        value: &Value,
+        interpreter: &Interpreter,
+        pos: Position,
+    ) -> EvalResult<()> {
+        let (setter, getter) = {
+            let mold = &self.instance.borrow().mold;
+            (mold.find_setter(prop), mold.find_getter(prop))
+        };
+
+        if self.is_accessing(prop) {
+            self.set_field(prop, value);
+            return Ok(());
+        }
+
+        if let Some(setter) = setter {
+            self.call_accessor(&setter, prop, vec![value.clone()], interpreter, pos)?;
+            return Ok(());
+        }
+
+        if getter.is_some() {
+            return Err(EvalError::ReadOnlyProperty(pos, interpreter.name(prop)));
+        }
+
+        self.set_field(prop, value);
+        Ok(())
+    }
+
+    // writes a field without calling setters
+    pub fn set_field(&self, prop: IdentifierHandle, value: &Value) {
+        let mut instance = self.instance.borrow_mut();
+
+        if instance.fields.insert(prop, value.clone()).is_none() {
+            instance.order.push(prop);
+        }
     }
 }
 
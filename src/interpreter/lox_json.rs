@@ -0,0 +1,558 @@
+use super::eval_result::{EvalError, EvalResult};
+use super::lox_array::new_elox_array;
+use super::lox_callable::LoxCallable;
+use super::lox_class::LoxClass;
+use super::lox_function::{LoxFunction, LoxFunctionParams, NativeFunction};
+use super::lox_instance::LoxInstance;
+use super::lox_range::iterable_values;
+use super::lox_string::string_arg;
+use super::value::{CallableValue, Value};
+use super::Environment;
+use super::Interpreter;
+use crate::parser::expressions::ContextLessFuncParam::*;
+use crate::parser::{IdentifierHandle, IdentifierHandlesGenerator};
+use crate::scanner::token::Position;
+use fnv::FnvHashMap;
+use std::iter::Peekable;
+use std::rc::Rc;
+use std::str::Chars;
+
+// deeper documents are rejected instead of overflowing the stack
+const MAX_DEPTH: usize = 512;
+
+// JSON objects are parsed into instances of the Object class
+struct JsonParser<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    col: usize,
+    object: &'a LoxClass,
+    interpreter: &'a Interpreter,
+    call_pos: Position,
+}
+
+impl<'a> JsonParser<'a> {
+    fn error<T>(&self, msg: impl Into<String>) -> EvalResult<T> {
+        Err(EvalError::InvalidJson(
+            self.call_pos,
+            msg.into(),
+            self.line,
+            self.col,
+        ))
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ') | Some('\t') | Some('\n') | Some('\r') = self.chars.peek() {
+            self.advance();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> EvalResult<()> {
+        self.skip_whitespace();
+
+        match self.chars.peek() {
+            Some(&c) if c == expected => {
+                self.advance();
+                Ok(())
+            }
+            Some(&c) => self.error(format!("Expected '{}', found '{}'", expected, c)),
+            None => self.error(format!(
+                "Expected '{}', found the end of the input",
+                expected
+            )),
+        }
+    }
+
+    fn document(&mut self) -> EvalResult<Value> {
+        let value = self.value(0)?;
+        self.skip_whitespace();
+
+        match self.chars.peek() {
+            Some(&c) => self.error(format!("Unexpected '{}' after the value", c)),
+            None => Ok(value),
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> EvalResult<Value> {
+        if depth > MAX_DEPTH {
+            return self.error("Too deeply nested");
+        }
+
+        self.skip_whitespace();
+
+        match self.chars.peek() {
+            Some('{') => self.object(depth),
+            Some('[') => self.array(depth),
+            Some('"') => Ok(Value::String(self.string()?)),
+            Some('t') => self.keyword("true", Value::Boolean(true)),
+            Some('f') => self.keyword("false", Value::Boolean(false)),
+            Some('n') => self.keyword("null", Value::Nil),
+            Some(&c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(&c) => self.error(format!("Unexpected '{}'", c)),
+            None => self.error("Unexpected end of the input"),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Value) -> EvalResult<Value> {
+        let start = (self.line, self.col);
+
+        for expected in keyword.chars() {
+            if self.chars.peek() != Some(&expected) {
+                // reported at the start of the keyword
+                let (line, col) = start;
+                return Err(EvalError::InvalidJson(
+                    self.call_pos,
+                    format!("Expected '{}'", keyword),
+                    line,
+                    col,
+                ));
+            }
+
+            self.advance();
+        }
+
+        Ok(value)
+    }
+
+    fn digits(&mut self, text: &mut String) -> usize {
+        let mut count = 0;
+
+        while let Some(&c) = self.chars.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+
+            text.push(c);
+            self.advance();
+            count += 1;
+        }
+
+        count
+    }
+
+    fn number(&mut self) -> EvalResult<Value> {
+        let mut text = String::new();
+
+        if self.chars.peek() == Some(&'-') {
+            text.push('-');
+            self.advance();
+        }
+
+        let leading_zero = self.chars.peek() == Some(&'0');
+        let int_digits = self.digits(&mut text);
+
+        if int_digits == 0 {
+            return self.error("Expected a digit");
+        }
+
+        if leading_zero && int_digits > 1 {
+            return self.error("Numbers cannot have leading zeros");
+        }
+
+        if self.chars.peek() == Some(&'.') {
+            text.push('.');
+            self.advance();
+
+            if self.digits(&mut text) == 0 {
+                return self.error("Expected a digit after the decimal point");
+            }
+        }
+
+        if let Some('e') | Some('E') = self.chars.peek() {
+            text.push('e');
+            self.advance();
+
+            if let Some(&sign) = self.chars.peek() {
+                if sign == '+' || sign == '-' {
+                    text.push(sign);
+                    self.advance();
+                }
+            }
+
+            if self.digits(&mut text) == 0 {
+                return self.error("Expected a digit in the exponent");
+            }
+        }
+
+        match text.parse() {
+            Ok(n) => Ok(Value::Number(n)),
+            Err(_) => self.error(format!("Invalid number '{}'", text)),
+        }
+    }
+
+    fn hex_escape(&mut self) -> EvalResult<u32> {
+        let mut code = 0;
+
+        for _ in 0..4 {
+            match self.advance().and_then(|c| c.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit,
+                None => return self.error("Expected 4 hexadecimal digits after '\\u'"),
+            }
+        }
+
+        Ok(code)
+    }
+
+    fn unicode_escape(&mut self) -> EvalResult<char> {
+        let code = self.hex_escape()?;
+
+        // characters outside the BMP are encoded as surrogate pairs
+        let code = if (0xD800..0xDC00).contains(&code) {
+            if self.advance() != Some('\\') || self.advance() != Some('u') {
+                return self.error("Expected a low surrogate after a high surrogate");
+            }
+
+            let low = self.hex_escape()?;
+
+            if !(0xDC00..0xE000).contains(&low) {
+                return self.error("Invalid low surrogate");
+            }
+
+            0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            code
+        };
+
+        match std::char::from_u32(code) {
+            Some(c) => Ok(c),
+            None => self.error("Invalid unicode escape"),
+        }
+    }
+
+    fn string(&mut self) -> EvalResult<String> {
+        self.expect('"')?;
+        let mut s = String::new();
+
+        loop {
+            match self.advance() {
+                Some('"') => return Ok(s),
+                Some('\\') => {
+                    let c = match self.advance() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape()?,
+                        Some(c) => return self.error(format!("Invalid escape '\\{}'", c)),
+                        None => return self.error("Unterminated string"),
+                    };
+
+                    s.push(c);
+                }
+                Some(c) if (c as u32) < 0x20 => {
+                    return self.error("Control characters must be escaped in strings")
+                }
+                Some(c) => s.push(c),
+                None => return self.error("Unterminated string"),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> EvalResult<Value> {
+        self.expect('[')?;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+
+        if self.chars.peek() == Some(&']') {
+            self.advance();
+        } else {
+            loop {
+                values.push(self.value(depth + 1)?);
+                self.skip_whitespace();
+
+                match self.advance() {
+                    Some(',') => continue,
+                    Some(']') => break,
+                    _ => return self.error("Expected ',' or ']' in an array"),
+                }
+            }
+        }
+
+        Ok(new_elox_array(values, self.interpreter))
+    }
+
+    fn object(&mut self, depth: usize) -> EvalResult<Value> {
+        self.expect('{')?;
+        let object = LoxInstance::new(Rc::clone(&self.object.mold));
+        self.skip_whitespace();
+
+        if self.chars.peek() == Some(&'}') {
+            self.advance();
+        } else {
+            loop {
+                self.skip_whitespace();
+                let key = self.string()?;
+                self.expect(':')?;
+                let value = self.value(depth + 1)?;
+                object.set_field(self.interpreter.intern(&key), &value);
+                self.skip_whitespace();
+
+                match self.advance() {
+                    Some(',') => continue,
+                    Some('}') => break,
+                    _ => return self.error("Expected ',' or '}' in an object"),
+                }
+            }
+        }
+
+        Ok(Value::Instance(object))
+    }
+}
+
+struct JsonWriter<'a> {
+    interpreter: &'a Interpreter,
+    call_pos: Position,
+    json: IdentifierHandle,
+    indent: String,
+    parents: Vec<LoxInstance>, // detects cycles
+    out: String,
+}
+
+impl<'a> JsonWriter<'a> {
+    fn newline(&mut self, depth: usize) {
+        if !self.indent.is_empty() {
+            self.out.push('\n');
+
+            for _ in 0..depth {
+                self.out.push_str(&self.indent);
+            }
+        }
+    }
+
+    fn string(&mut self, s: &str) {
+        self.out.push('"');
+
+        for c in s.chars() {
+            match c {
+                '"' => self.out.push_str("\\\""),
+                '\\' => self.out.push_str("\\\\"),
+                '\n' => self.out.push_str("\\n"),
+                '\r' => self.out.push_str("\\r"),
+                '\t' => self.out.push_str("\\t"),
+                '\u{8}' => self.out.push_str("\\b"),
+                '\u{c}' => self.out.push_str("\\f"),
+                c if (c as u32) < 0x20 => self.out.push_str(&format!("\\u{:04x}", c as u32)),
+                c => self.out.push(c),
+            }
+        }
+
+        self.out.push('"');
+    }
+
+    // writes "[...]" or "{...}" with one entry per line when indenting
+    fn entries<T>(
+        &mut self,
+        entries: Vec<T>,
+        delimiters: (char, char),
+        depth: usize,
+        mut write: impl FnMut(&mut Self, T) -> EvalResult<()>,
+    ) -> EvalResult<()> {
+        self.out.push(delimiters.0);
+        let empty = entries.is_empty();
+
+        for (idx, entry) in entries.into_iter().enumerate() {
+            if idx > 0 {
+                self.out.push(',');
+            }
+
+            self.newline(depth + 1);
+            write(self, entry)?;
+        }
+
+        if !empty {
+            self.newline(depth);
+        }
+
+        self.out.push(delimiters.1);
+        Ok(())
+    }
+
+    fn value(&mut self, value: &Value, depth: usize) -> EvalResult<()> {
+        match value {
+            Value::Nil => self.out.push_str("null"),
+            Value::Boolean(b) => self.out.push_str(&b.to_string()),
+            // like JavaScript, non finite numbers have no JSON representation
+            Value::Number(n) if !n.is_finite() => self.out.push_str("null"),
+            Value::Number(n) => self.out.push_str(&n.to_string()),
+            Value::String(s) => self.string(s),
+            Value::Callable(callable) => {
+                let type_ = match callable {
+                    CallableValue::Class(_) => "class",
+                    _ => "function",
+                };
+
+                return Err(EvalError::NotJsonSerializable(self.call_pos, type_.into()));
+            }
+            Value::Instance(instance) => {
+                if self.parents.contains(instance) {
+                    return Err(EvalError::CyclicJson(self.call_pos));
+                }
+
+                self.parents.push(instance.clone());
+                self.instance(instance, value, depth)?;
+                self.parents.pop();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn instance(&mut self, instance: &LoxInstance, value: &Value, depth: usize) -> EvalResult<()> {
+        // #json returns the value to serialize in place of the instance
+        if let Some(res) = instance.call(self.json, self.interpreter, vec![], self.call_pos) {
+            return self.value(&res?, depth);
+        }
+
+        if let Some(values) = iterable_values(value) {
+            return self.entries(values, ('[', ']'), depth, |writer, value| {
+                writer.value(&value, depth + 1)
+            });
+        }
+
+        // private fields stay private, keys keep the order the fields were set in
+        let fields = instance
+            .fields()
+            .into_iter()
+            .filter(|(prop, _)| instance.private_owner(*prop).is_none())
+            .map(|(prop, value)| (self.interpreter.name(prop), value))
+            .collect::<Vec<_>>();
+
+        self.entries(fields, ('{', '}'), depth, |writer, (key, value)| {
+            writer.string(&key);
+            writer.out.push(':');
+
+            if !writer.indent.is_empty() {
+                writer.out.push(' ');
+            }
+
+            writer.value(&value, depth + 1)
+        })
+    }
+}
+
+fn json_function(
+    env: &Environment,
+    name: IdentifierHandle,
+    params: LoxFunctionParams,
+    method: Rc<NativeFunction>,
+) -> Rc<LoxFunction> {
+    Rc::new(LoxFunction::new_native(
+        method,
+        env.clone(),
+        false,
+        params,
+        name,
+    ))
+}
+
+pub fn create_elox_json_namespace(
+    env: &Environment,
+    identifiers: &mut IdentifierHandlesGenerator,
+) -> LoxInstance {
+    let mut methods = FnvHashMap::default();
+    let object = Rc::new(LoxClass::new(
+        identifiers.by_name("Object"),
+        None,
+        FnvHashMap::default(),
+    ));
+
+    let parse_handle = identifiers.by_name("parse");
+
+    methods.insert(
+        parse_handle,
+        json_function(
+            env,
+            parse_handle,
+            Some(Rc::new(vec![Required(identifiers.by_name("text"))])),
+            Rc::new(
+                move |func: &LoxFunction,
+                      interpreter: &Interpreter,
+                      _env: &Environment,
+                      args: Vec<Value>,
+                      call_pos: Position| {
+                    let text = string_arg(func, interpreter, &args[0], call_pos)?;
+
+                    JsonParser {
+                        chars: text.chars().peekable(),
+                        line: 1,
+                        col: 1,
+                        object: &object,
+                        interpreter,
+                        call_pos,
+                    }
+                    .document()
+                },
+            ),
+        ),
+    );
+
+    let stringify_handle = identifiers.by_name("stringify");
+    let json = identifiers.by_name("#json");
+
+    methods.insert(
+        stringify_handle,
+        json_function(
+            env,
+            stringify_handle,
+            Some(Rc::new(vec![
+                Required(identifiers.by_name("value")),
+                DefaultValued(identifiers.by_name("indent"), Value::Nil),
+            ])),
+            Rc::new(
+                move |func: &LoxFunction,
+                      interpreter: &Interpreter,
+                      _env: &Environment,
+                      args: Vec<Value>,
+                      call_pos: Position| {
+                    // a number of spaces or the string used to indent,
+                    // like JavaScript at most 10 characters are used
+                    let indent = match &args[1] {
+                        Value::Nil => String::new(),
+                        Value::Number(n) => " ".repeat(n.clamp(0f64, 10f64) as usize),
+                        Value::String(s) => s.chars().take(10).collect(),
+                        arg => {
+                            return Err(EvalError::UnexpectedArgumentType(
+                                call_pos,
+                                func.name(&interpreter.names()),
+                                "number".into(),
+                                arg.type_(),
+                            ))
+                        }
+                    };
+
+                    let mut writer = JsonWriter {
+                        interpreter,
+                        call_pos,
+                        json,
+                        indent,
+                        parents: Vec::new(),
+                        out: String::new(),
+                    };
+
+                    writer.value(&args[0], 0)?;
+                    Ok(Value::String(writer.out))
+                },
+            ),
+        ),
+    );
+
+    let class = LoxClass::new(identifiers.by_name("JSON"), None, methods);
+
+    LoxInstance::new(Rc::clone(&class.mold))
+}
@@ -0,0 +1,281 @@
+use super::eval_result::EvalResult;
+use super::lox_array::array_values;
+use super::lox_class::{ClassMembers, LoxClass, Methods};
+use super::lox_function::{LoxFunction, LoxFunctionParams, NativeFunction};
+use super::lox_instance::LoxInstance;
+use super::lox_string::{integer_arg, number_arg};
+use super::value::Value;
+use super::Environment;
+use super::Interpreter;
+use crate::parser::expressions::ContextLessFuncParam::*;
+use crate::parser::{IdentifierHandle, IdentifierHandlesGenerator};
+use crate::scanner::token::Position;
+use fnv::FnvHashMap;
+use std::f64::consts;
+use std::rc::Rc;
+
+// the Math namespace is shared by the tree-walker and the VM
+
+pub type UnaryFunction = fn(f64) -> f64;
+pub type BinaryFunction = fn(f64, f64) -> f64;
+pub type Predicate = fn(f64) -> bool;
+
+pub const CONSTANTS: &[(&str, f64)] = &[("PI", consts::PI), ("E", consts::E)];
+
+pub const UNARY_FUNCTIONS: &[(&str, UnaryFunction)] = &[
+    ("floor", f64::floor),
+    ("ceil", f64::ceil),
+    ("round", f64::round),
+    ("trunc", f64::trunc),
+    ("abs", f64::abs),
+    ("sign", sign),
+    ("sqrt", f64::sqrt),
+    ("cbrt", f64::cbrt),
+    ("exp", f64::exp),
+    ("log", f64::ln),
+    ("log2", f64::log2),
+    ("log10", f64::log10),
+    ("sin", f64::sin),
+    ("cos", f64::cos),
+    ("tan", f64::tan),
+    ("asin", f64::asin),
+    ("acos", f64::acos),
+    ("atan", f64::atan),
+];
+
+pub const BINARY_FUNCTIONS: &[(&str, BinaryFunction)] = &[
+    ("pow", f64::powf),
+    ("atan2", f64::atan2),
+    ("hypot", f64::hypot),
+];
+
+// they loop until a remainder is 0, which never happens for NaN or infinities
+pub const INTEGER_FUNCTIONS: &[(&str, BinaryFunction)] = &[("gcd", gcd), ("lcm", lcm)];
+
+pub const PREDICATES: &[(&str, Predicate)] = &[
+    ("isNaN", f64::is_nan),
+    ("isFinite", f64::is_finite),
+    ("isInteger", is_integer),
+];
+
+// min and max take one or more numbers
+pub const FOLDS: &[(&str, BinaryFunction)] = &[("min", f64::min), ("max", f64::max)];
+
+// 0 for both zeros, unlike f64::signum
+fn sign(x: f64) -> f64 {
+    if x == 0f64 || x.is_nan() {
+        x
+    } else {
+        x.signum()
+    }
+}
+
+pub fn is_integer(x: f64) -> bool {
+    x.is_finite() && x.fract() == 0f64
+}
+
+pub fn gcd(a: f64, b: f64) -> f64 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+
+    while b != 0f64 {
+        let rem = a % b;
+        a = b;
+        b = rem;
+    }
+
+    a
+}
+
+pub fn lcm(a: f64, b: f64) -> f64 {
+    if a == 0f64 || b == 0f64 {
+        return 0f64;
+    }
+
+    (a * b).abs() / gcd(a, b)
+}
+
+fn math_function(
+    env: &Environment,
+    name: IdentifierHandle,
+    params: LoxFunctionParams,
+    method: Rc<NativeFunction>,
+) -> Rc<LoxFunction> {
+    Rc::new(LoxFunction::new_native(
+        method,
+        env.clone(),
+        false,
+        params,
+        name,
+    ))
+}
+
+// Math is an instance of a native class, its constants are read-only getters
+pub fn create_elox_math_namespace(
+    env: &Environment,
+    identifiers: &mut IdentifierHandlesGenerator,
+) -> LoxInstance {
+    let mut methods = FnvHashMap::default();
+    let mut getters: Methods = FnvHashMap::default();
+
+    for (name, value) in CONSTANTS.iter() {
+        let handle = identifiers.by_name(name);
+        let value = *value;
+
+        getters.insert(
+            handle,
+            math_function(
+                env,
+                handle,
+                None,
+                Rc::new(
+                    move |_func: &LoxFunction,
+                          _interpreter: &Interpreter,
+                          _env: &Environment,
+                          _args: Vec<Value>,
+                          _call_pos: Position| Ok(Value::Number(value)),
+                ),
+            ),
+        );
+    }
+
+    let x = identifiers.by_name("x");
+    let y = identifiers.by_name("y");
+
+    for (name, f) in UNARY_FUNCTIONS.iter() {
+        let handle = identifiers.by_name(name);
+        let f = *f;
+
+        methods.insert(
+            handle,
+            math_function(
+                env,
+                handle,
+                Some(Rc::new(vec![Required(x)])),
+                Rc::new(
+                    move |func: &LoxFunction,
+                          interpreter: &Interpreter,
+                          _env: &Environment,
+                          args: Vec<Value>,
+                          call_pos: Position| {
+                        let x = number_arg(func, interpreter, &args[0], call_pos)?;
+                        Ok(Value::Number(f(x)))
+                    },
+                ),
+            ),
+        );
+    }
+
+    for (name, f) in BINARY_FUNCTIONS.iter() {
+        let handle = identifiers.by_name(name);
+        let f = *f;
+
+        methods.insert(
+            handle,
+            math_function(
+                env,
+                handle,
+                Some(Rc::new(vec![Required(x), Required(y)])),
+                Rc::new(
+                    move |func: &LoxFunction,
+                          interpreter: &Interpreter,
+                          _env: &Environment,
+                          args: Vec<Value>,
+                          call_pos: Position| {
+                        let x = number_arg(func, interpreter, &args[0], call_pos)?;
+                        let y = number_arg(func, interpreter, &args[1], call_pos)?;
+                        Ok(Value::Number(f(x, y)))
+                    },
+                ),
+            ),
+        );
+    }
+
+    for (name, f) in INTEGER_FUNCTIONS.iter() {
+        let handle = identifiers.by_name(name);
+        let f = *f;
+
+        methods.insert(
+            handle,
+            math_function(
+                env,
+                handle,
+                Some(Rc::new(vec![Required(x), Required(y)])),
+                Rc::new(
+                    move |func: &LoxFunction,
+                          interpreter: &Interpreter,
+                          _env: &Environment,
+                          args: Vec<Value>,
+                          call_pos: Position| {
+                        let x = integer_arg(func, interpreter, &args[0], call_pos)?;
+                        let y = integer_arg(func, interpreter, &args[1], call_pos)?;
+                        Ok(Value::Number(f(x, y)))
+                    },
+                ),
+            ),
+        );
+    }
+
+    for (name, predicate) in PREDICATES.iter() {
+        let handle = identifiers.by_name(name);
+        let predicate = *predicate;
+
+        methods.insert(
+            handle,
+            math_function(
+                env,
+                handle,
+                Some(Rc::new(vec![Required(x)])),
+                Rc::new(
+                    move |func: &LoxFunction,
+                          interpreter: &Interpreter,
+                          _env: &Environment,
+                          args: Vec<Value>,
+                          call_pos: Position| {
+                        let x = number_arg(func, interpreter, &args[0], call_pos)?;
+                        Ok(Value::Boolean(predicate(x)))
+                    },
+                ),
+            ),
+        );
+    }
+
+    let values = identifiers.by_name("values");
+
+    for (name, fold) in FOLDS.iter() {
+        let handle = identifiers.by_name(name);
+        let fold = *fold;
+
+        methods.insert(
+            handle,
+            math_function(
+                env,
+                handle,
+                Some(Rc::new(vec![Required(x), Rest(values)])),
+                Rc::new(
+                    move |func: &LoxFunction,
+                          interpreter: &Interpreter,
+                          _env: &Environment,
+                          args: Vec<Value>,
+                          call_pos: Position| {
+                        let first = number_arg(func, interpreter, &args[0], call_pos)?;
+
+                        array_values(&args[1])
+                            .unwrap()
+                            .iter()
+                            .map(|val| number_arg(func, interpreter, val, call_pos))
+                            .collect::<EvalResult<Vec<f64>>>()
+                            .map(|rest| Value::Number(rest.into_iter().fold(first, fold)))
+                    },
+                ),
+            ),
+        );
+    }
+
+    let members = ClassMembers {
+        getters,
+        ..ClassMembers::default()
+    };
+    let class = LoxClass::with_members(identifiers.by_name("Math"), None, methods, members);
+
+    LoxInstance::new(Rc::clone(&class.mold))
+}
@@ -0,0 +1,270 @@
+use super::eval_result::{EvalError, EvalResult};
+use super::lox_array::{array_values, shared_array_values};
+use super::lox_callable::LoxCallable;
+use super::lox_class::LoxClass;
+use super::lox_function::{LoxFunction, LoxFunctionParams, NativeMethod};
+use super::lox_instance::{LoxInstance, NativesMap};
+use super::lox_string::{integer_arg, number_arg};
+use super::natives::NativeValue;
+use super::value::Value;
+use super::Environment;
+use super::Interpreter;
+use crate::parser::expressions::ContextLessFuncParam::*;
+use crate::parser::{Identifier, IdentifierHandle, IdentifierHandlesGenerator};
+use crate::scanner::token::Position;
+use fnv::FnvHashMap;
+use std::rc::Rc;
+
+// PCG-XSH-RR 32 bits generator, see https://www.pcg-random.org
+#[derive(Clone, Debug)]
+pub struct Pcg32 {
+    state: u64,
+    inc: u64,
+}
+
+impl Pcg32 {
+    pub fn new(seed: u64) -> Pcg32 {
+        let mut rng = Pcg32 {
+            state: 0,
+            inc: (0xda3e_39cb_94b9_5bdb << 1) | 1,
+        };
+
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(self.inc);
+
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as u32;
+
+        xorshifted.rotate_right(rot)
+    }
+
+    // uniformly distributed in [0, 1) with 53 bits of precision
+    pub fn next_f64(&mut self) -> f64 {
+        let high = u64::from(self.next_u32() >> 5);
+        let low = u64::from(self.next_u32() >> 6);
+
+        ((high << 26) | low) as f64 / (1u64 << 53) as f64
+    }
+
+    // uniformly distributed in [0, bound)
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_f64() * bound as f64) as usize
+    }
+}
+
+fn rng_handle() -> usize {
+    0
+}
+
+// generators created without a seed are seeded by the host on first use
+fn rng<'a>(natives: &'a mut NativesMap, interpreter: &Interpreter) -> &'a mut Pcg32 {
+    let rng = natives
+        .entry(rng_handle())
+        .or_insert_with(|| NativeValue::Random(Pcg32::new((interpreter.host.seed)())));
+
+    match rng {
+        NativeValue::Random(rng) => rng,
+        _ => panic!("Could not fetch the state of a random generator"),
+    }
+}
+
+fn array_arg(
+    func: &LoxFunction,
+    interpreter: &Interpreter,
+    arg: &Value,
+    call_pos: Position,
+) -> EvalResult<Vec<Value>> {
+    match array_values(arg) {
+        Some(values) => Ok(values),
+        None => Err(EvalError::UnexpectedArgumentType(
+            call_pos,
+            func.name(&interpreter.names()),
+            "Array".into(),
+            arg.type_(),
+        )),
+    }
+}
+
+fn random_method(
+    env: &Environment,
+    name: IdentifierHandle,
+    params: LoxFunctionParams,
+    method: Rc<NativeMethod>,
+) -> Rc<LoxFunction> {
+    Rc::new(LoxFunction::new_native_method(
+        method,
+        env.clone(),
+        name == Identifier::init(),
+        params,
+        name,
+    ))
+}
+
+pub fn create_elox_random_class(
+    env: &Environment,
+    identifiers: &mut IdentifierHandlesGenerator,
+) -> LoxClass {
+    let mut methods = FnvHashMap::default();
+
+    methods.insert(
+        Identifier::init(),
+        random_method(
+            env,
+            Identifier::init(),
+            Some(Rc::new(vec![DefaultValued(
+                identifiers.by_name("seed"),
+                Value::Nil,
+            )])),
+            Rc::new(
+                |this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    if args[0] != Value::Nil {
+                        let seed = number_arg(func, interpreter, &args[0], call_pos)?;
+                        natives.insert(
+                            rng_handle(),
+                            NativeValue::Random(Pcg32::new(seed.to_bits())),
+                        );
+                    }
+
+                    Ok(Value::Instance(this.clone()))
+                },
+            ),
+        ),
+    );
+
+    let next_handle = identifiers.by_name("next");
+
+    methods.insert(
+        next_handle,
+        random_method(
+            env,
+            next_handle,
+            None,
+            Rc::new(
+                |_this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 _func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 _args: Vec<Value>,
+                 _call_pos: Position| {
+                    Ok(Value::Number(rng(natives, interpreter).next_f64()))
+                },
+            ),
+        ),
+    );
+
+    let int_handle = identifiers.by_name("int");
+
+    methods.insert(
+        int_handle,
+        random_method(
+            env,
+            int_handle,
+            Some(Rc::new(vec![
+                Required(identifiers.by_name("lo")),
+                Required(identifiers.by_name("hi")),
+            ])),
+            Rc::new(
+                |_this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let lo = integer_arg(func, interpreter, &args[0], call_pos)?;
+                    let hi = integer_arg(func, interpreter, &args[1], call_pos)?;
+
+                    // both bounds are included
+                    if hi < lo {
+                        return Err(EvalError::EmptyRandomRange(call_pos, lo, hi));
+                    }
+
+                    let n = rng(natives, interpreter).next_f64();
+                    Ok(Value::Number(lo + (n * (hi - lo + 1f64)).floor()))
+                },
+            ),
+        ),
+    );
+
+    let choice_handle = identifiers.by_name("choice");
+
+    methods.insert(
+        choice_handle,
+        random_method(
+            env,
+            choice_handle,
+            Some(Rc::new(vec![Required(identifiers.by_name("values"))])),
+            Rc::new(
+                |_this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let values = array_arg(func, interpreter, &args[0], call_pos)?;
+
+                    if values.is_empty() {
+                        return Ok(Value::Nil);
+                    }
+
+                    let idx = rng(natives, interpreter).below(values.len());
+                    Ok(values[idx].clone())
+                },
+            ),
+        ),
+    );
+
+    let shuffle_handle = identifiers.by_name("shuffle");
+
+    methods.insert(
+        shuffle_handle,
+        random_method(
+            env,
+            shuffle_handle,
+            Some(Rc::new(vec![Required(identifiers.by_name("values"))])),
+            Rc::new(
+                |_this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    array_arg(func, interpreter, &args[0], call_pos)?;
+
+                    let values = shared_array_values(&args[0]).unwrap();
+                    let mut values = values.borrow_mut();
+                    let rng = rng(natives, interpreter);
+
+                    // Fisher-Yates, in place
+                    for i in (1..values.len()).rev() {
+                        let j = rng.below(i + 1);
+                        values.swap(i, j);
+                    }
+
+                    Ok(args[0].clone())
+                },
+            ),
+        ),
+    );
+
+    LoxClass::new_native(identifiers.by_name("Random"), None, methods)
+}
@@ -0,0 +1,260 @@
+use super::eval_result::EvalError;
+use super::lox_array::{array_values, new_elox_array};
+use super::lox_class::LoxClass;
+use super::lox_function::{LoxFunction, LoxFunctionParams, NativeMethod};
+use super::lox_instance::{LoxInstance, NativesMap};
+use super::lox_string::number_arg;
+use super::natives::NativeValue;
+use super::value::Value;
+use super::Environment;
+use super::Interpreter;
+use crate::parser::expressions::ContextLessFuncParam::*;
+use crate::parser::{Identifier, IdentifierHandle, IdentifierHandlesGenerator};
+use crate::scanner::token::Position;
+use fnv::FnvHashMap;
+use std::rc::Rc;
+
+fn range_handle() -> usize {
+    0
+}
+
+fn bounds(natives: &NativesMap) -> (f64, f64, bool) {
+    match natives.get(&range_handle()) {
+        Some(NativeValue::Range(start, end, inclusive)) => (*start, *end, *inclusive),
+        _ => panic!("Could not fetch the bounds of a range"),
+    }
+}
+
+// a range holds every value from start stepping by 1 while it stays below end
+// (or up to end for an inclusive range)
+fn range_length(start: f64, end: f64, inclusive: bool) -> usize {
+    let length = if inclusive {
+        (end - start).floor() + 1f64
+    } else {
+        (end - start).ceil()
+    };
+
+    length.max(0f64) as usize
+}
+
+fn range_values_of(start: f64, end: f64, inclusive: bool) -> Vec<Value> {
+    (0..range_length(start, end, inclusive))
+        .map(|idx| Value::Number(start + idx as f64))
+        .collect()
+}
+
+// the start, end and inclusiveness of a Range instance, None for any other value
+pub fn range_bounds(value: &Value) -> Option<(f64, f64, bool)> {
+    match value {
+        Value::Instance(instance) => match instance.clone().get_native(range_handle())? {
+            NativeValue::Range(start, end, inclusive) => Some((start, end, inclusive)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// the values of a Range instance, None for any other value
+pub fn range_values(value: &Value) -> Option<Vec<Value>> {
+    let (start, end, inclusive) = range_bounds(value)?;
+    Some(range_values_of(start, end, inclusive))
+}
+
+// the values of anything that can be iterated over: arrays and ranges
+pub fn iterable_values(value: &Value) -> Option<Vec<Value>> {
+    array_values(value).or_else(|| range_values(value))
+}
+
+fn range_method(
+    env: &Environment,
+    name: IdentifierHandle,
+    params: LoxFunctionParams,
+    method: Rc<NativeMethod>,
+) -> Rc<LoxFunction> {
+    Rc::new(LoxFunction::new_native_method(
+        method,
+        env.clone(),
+        name == Identifier::init(),
+        params,
+        name,
+    ))
+}
+
+pub fn create_elox_range_class(
+    env: &Environment,
+    identifiers: &mut IdentifierHandlesGenerator,
+) -> LoxClass {
+    let mut methods = FnvHashMap::default();
+
+    methods.insert(
+        Identifier::init(),
+        range_method(
+            env,
+            Identifier::init(),
+            Some(Rc::new(vec![
+                Required(identifiers.by_name("start")),
+                Required(identifiers.by_name("end")),
+                DefaultValued(identifiers.by_name("inclusive"), Value::Boolean(false)),
+            ])),
+            Rc::new(
+                |this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let start = number_arg(func, interpreter, &args[0], call_pos)?;
+                    let end = number_arg(func, interpreter, &args[1], call_pos)?;
+                    let inclusive = args[2].is_truthy();
+
+                    natives.insert(range_handle(), NativeValue::Range(start, end, inclusive));
+                    Ok(Value::Instance(this.clone()))
+                },
+            ),
+        ),
+    );
+
+    let contains_handle = identifiers.by_name("contains");
+
+    methods.insert(
+        contains_handle,
+        range_method(
+            env,
+            contains_handle,
+            Some(Rc::new(vec![Required(identifiers.by_name("value"))])),
+            Rc::new(
+                |_this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 _func: &LoxFunction,
+                 _interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 _call_pos: Position| {
+                    let (start, end, inclusive) = bounds(natives);
+
+                    let contained = match args[0] {
+                        Value::Number(n) if inclusive => n >= start && n <= end,
+                        Value::Number(n) => n >= start && n < end,
+                        _ => false,
+                    };
+
+                    Ok(Value::Boolean(contained))
+                },
+            ),
+        ),
+    );
+
+    let length_handle = identifiers.by_name("length");
+
+    methods.insert(
+        length_handle,
+        range_method(
+            env,
+            length_handle,
+            None,
+            Rc::new(
+                |_this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 _func: &LoxFunction,
+                 _interpreter: &Interpreter,
+                 _env: &Environment,
+                 _args: Vec<Value>,
+                 _call_pos: Position| {
+                    let (start, end, inclusive) = bounds(natives);
+                    Ok(Value::Number(range_length(start, end, inclusive) as f64))
+                },
+            ),
+        ),
+    );
+
+    methods.insert(
+        Identifier::get(),
+        range_method(
+            env,
+            Identifier::get(),
+            Some(Rc::new(vec![Required(identifiers.by_name("idx"))])),
+            Rc::new(
+                |_this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 _func: &LoxFunction,
+                 _interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let (start, end, inclusive) = bounds(natives);
+                    let length = range_length(start, end, inclusive);
+
+                    match args[0] {
+                        Value::Number(n) if n % 1f64 == 0f64 => {
+                            if n < 0f64 || n as usize >= length {
+                                return Err(EvalError::ArrayIndexOutOfBounds(
+                                    call_pos, n as isize, length,
+                                ));
+                            }
+
+                            Ok(Value::Number(start + n))
+                        }
+                        _ => Ok(Value::Nil),
+                    }
+                },
+            ),
+        ),
+    );
+
+    let to_array_handle = identifiers.by_name("toArray");
+
+    methods.insert(
+        to_array_handle,
+        range_method(
+            env,
+            to_array_handle,
+            None,
+            Rc::new(
+                |_this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 _func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 _args: Vec<Value>,
+                 _call_pos: Position| {
+                    let (start, end, inclusive) = bounds(natives);
+                    Ok(new_elox_array(
+                        range_values_of(start, end, inclusive),
+                        interpreter,
+                    ))
+                },
+            ),
+        ),
+    );
+
+    methods.insert(
+        Identifier::str_(),
+        range_method(
+            env,
+            Identifier::str_(),
+            None,
+            Rc::new(
+                |_this: &LoxInstance,
+                 natives: &mut NativesMap,
+                 _func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 _args: Vec<Value>,
+                 call_pos: Position| {
+                    let (start, end, inclusive) = bounds(natives);
+                    let op = if inclusive { "..=" } else { ".." };
+
+                    Ok(Value::String(format!(
+                        "{}{}{}",
+                        Value::Number(start).to_str(interpreter, call_pos)?,
+                        op,
+                        Value::Number(end).to_str(interpreter, call_pos)?
+                    )))
+                },
+            ),
+        ),
+    );
+
+    LoxClass::new_native(identifiers.by_name("Range"), None, methods)
+}
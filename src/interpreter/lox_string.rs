@@ -0,0 +1,389 @@
+use super::eval_result::{EvalError, EvalResult};
+use super::lox_array::new_elox_array;
+use super::lox_callable::LoxCallable;
+use super::lox_class::LoxClass;
+use super::lox_function::{LoxFunction, LoxFunctionParams, NativeFunction};
+use super::lox_math::is_integer;
+use super::value::Value;
+use super::Environment;
+use super::Interpreter;
+use crate::parser::expressions::ContextLessFuncParam::*;
+use crate::parser::{Identifier, IdentifierHandle, IdentifierHandlesGenerator};
+use crate::scanner::token::Position;
+use fnv::FnvHashMap;
+use std::rc::Rc;
+
+// String primitives shared by the tree-walker and the VM,
+// all indices are expressed in characters, not in bytes
+
+pub fn char_count(s: &str) -> usize {
+    s.chars().count()
+}
+
+pub fn char_at(s: &str, idx: f64) -> Option<String> {
+    if idx < 0f64 || idx % 1f64 != 0f64 {
+        return None;
+    }
+
+    s.chars().nth(idx as usize).map(|c| c.to_string())
+}
+
+// negative or out of range bounds are clamped
+pub fn substring(s: &str, start: f64, end: f64) -> String {
+    let len = char_count(s);
+    let clamp = |idx: f64| (idx.max(0f64).floor() as usize).min(len);
+    let (start, end) = (clamp(start), clamp(end));
+
+    if start >= end {
+        return String::new();
+    }
+
+    s.chars().skip(start).take(end - start).collect()
+}
+
+pub fn index_of(s: &str, needle: &str) -> Option<usize> {
+    s.find(needle).map(|byte_idx| char_count(&s[..byte_idx]))
+}
+
+// an empty separator splits the string into its characters
+pub fn split(s: &str, separator: &str) -> Vec<String> {
+    if separator.is_empty() {
+        return s.chars().map(|c| c.to_string()).collect();
+    }
+
+    s.split(separator).map(String::from).collect()
+}
+
+pub fn parse_number(s: &str) -> Option<f64> {
+    s.trim().parse::<f64>().ok()
+}
+
+fn this_string(func: &LoxFunction, interpreter: &Interpreter, call_pos: Position) -> EvalResult<String> {
+    match func.env.get(0, Identifier::this()) {
+        Some(Value::String(s)) => Ok(s),
+        Some(val) => Err(EvalError::UnexpectedArgumentType(
+            call_pos,
+            func.name(&interpreter.names()),
+            "string".into(),
+            val.type_(),
+        )),
+        None => panic!("Could not find 'this'"),
+    }
+}
+
+pub fn number_arg(
+    func: &LoxFunction,
+    interpreter: &Interpreter,
+    arg: &Value,
+    call_pos: Position,
+) -> EvalResult<f64> {
+    match arg {
+        Value::Number(n) => Ok(*n),
+        _ => Err(EvalError::UnexpectedArgumentType(
+            call_pos,
+            func.name(&interpreter.names()),
+            "number".into(),
+            arg.type_(),
+        )),
+    }
+}
+
+// a finite number without a fractional part
+pub fn integer_arg(
+    func: &LoxFunction,
+    interpreter: &Interpreter,
+    arg: &Value,
+    call_pos: Position,
+) -> EvalResult<f64> {
+    match arg {
+        Value::Number(n) if is_integer(*n) => Ok(*n),
+        _ => Err(EvalError::UnexpectedArgumentType(
+            call_pos,
+            func.name(&interpreter.names()),
+            "integer".into(),
+            arg.type_(),
+        )),
+    }
+}
+
+pub fn string_arg(
+    func: &LoxFunction,
+    interpreter: &Interpreter,
+    arg: &Value,
+    call_pos: Position,
+) -> EvalResult<String> {
+    match arg {
+        Value::String(s) => Ok(s.clone()),
+        _ => Err(EvalError::UnexpectedArgumentType(
+            call_pos,
+            func.name(&interpreter.names()),
+            "string".into(),
+            arg.type_(),
+        )),
+    }
+}
+
+fn string_method(
+    env: &Environment,
+    name: IdentifierHandle,
+    params: LoxFunctionParams,
+    method: Rc<NativeFunction>,
+) -> Rc<LoxFunction> {
+    Rc::new(LoxFunction::new_native(method, env.clone(), false, params, name))
+}
+
+pub fn create_elox_string_class(
+    env: &Environment,
+    identifiers: &mut IdentifierHandlesGenerator,
+) -> LoxClass {
+    let mut methods = FnvHashMap::default();
+
+    let length_handle = identifiers.by_name("length");
+
+    methods.insert(
+        length_handle,
+        string_method(
+            env,
+            length_handle,
+            None,
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 _args: Vec<Value>,
+                 call_pos: Position| {
+                    let s = this_string(func, interpreter, call_pos)?;
+                    Ok(Value::Number(char_count(&s) as f64))
+                },
+            ),
+        ),
+    );
+
+    let get_handle = Identifier::get();
+
+    methods.insert(
+        get_handle,
+        string_method(
+            env,
+            get_handle,
+            Some(Rc::new(vec![Required(identifiers.by_name("idx"))])),
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let s = this_string(func, interpreter, call_pos)?;
+                    let idx = number_arg(func, interpreter, &args[0], call_pos)?;
+
+                    match char_at(&s, idx) {
+                        Some(c) => Ok(Value::String(c)),
+                        None => Err(EvalError::StringIndexOutOfBounds(
+                            call_pos,
+                            idx as isize,
+                            char_count(&s),
+                        )),
+                    }
+                },
+            ),
+        ),
+    );
+
+    let char_at_handle = identifiers.by_name("charAt");
+
+    methods.insert(
+        char_at_handle,
+        string_method(
+            env,
+            char_at_handle,
+            Some(Rc::new(vec![Required(identifiers.by_name("idx"))])),
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let s = this_string(func, interpreter, call_pos)?;
+                    let idx = number_arg(func, interpreter, &args[0], call_pos)?;
+
+                    Ok(char_at(&s, idx).map_or(Value::Nil, Value::String))
+                },
+            ),
+        ),
+    );
+
+    let substring_handle = identifiers.by_name("substring");
+
+    methods.insert(
+        substring_handle,
+        string_method(
+            env,
+            substring_handle,
+            Some(Rc::new(vec![
+                Required(identifiers.by_name("start")),
+                DefaultValued(identifiers.by_name("end"), Value::Nil),
+            ])),
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let s = this_string(func, interpreter, call_pos)?;
+                    let start = number_arg(func, interpreter, &args[0], call_pos)?;
+                    let end = match &args[1] {
+                        Value::Nil => char_count(&s) as f64,
+                        end => number_arg(func, interpreter, end, call_pos)?,
+                    };
+
+                    Ok(Value::String(substring(&s, start, end)))
+                },
+            ),
+        ),
+    );
+
+    let index_of_handle = identifiers.by_name("indexOf");
+
+    methods.insert(
+        index_of_handle,
+        string_method(
+            env,
+            index_of_handle,
+            Some(Rc::new(vec![Required(identifiers.by_name("needle"))])),
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let s = this_string(func, interpreter, call_pos)?;
+                    let needle = string_arg(func, interpreter, &args[0], call_pos)?;
+
+                    Ok(index_of(&s, &needle).map_or(Value::Nil, |idx| Value::Number(idx as f64)))
+                },
+            ),
+        ),
+    );
+
+    let split_handle = identifiers.by_name("split");
+
+    methods.insert(
+        split_handle,
+        string_method(
+            env,
+            split_handle,
+            Some(Rc::new(vec![DefaultValued(
+                identifiers.by_name("separator"),
+                Value::String(String::new()),
+            )])),
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let s = this_string(func, interpreter, call_pos)?;
+                    let separator = string_arg(func, interpreter, &args[0], call_pos)?;
+                    let parts = split(&s, &separator)
+                        .into_iter()
+                        .map(Value::String)
+                        .collect();
+
+                    Ok(new_elox_array(parts, interpreter))
+                },
+            ),
+        ),
+    );
+
+    let to_upper_case_handle = identifiers.by_name("toUpperCase");
+
+    methods.insert(
+        to_upper_case_handle,
+        string_method(
+            env,
+            to_upper_case_handle,
+            None,
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 _args: Vec<Value>,
+                 call_pos: Position| {
+                    let s = this_string(func, interpreter, call_pos)?;
+                    Ok(Value::String(s.to_uppercase()))
+                },
+            ),
+        ),
+    );
+
+    let to_lower_case_handle = identifiers.by_name("toLowerCase");
+
+    methods.insert(
+        to_lower_case_handle,
+        string_method(
+            env,
+            to_lower_case_handle,
+            None,
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 _args: Vec<Value>,
+                 call_pos: Position| {
+                    let s = this_string(func, interpreter, call_pos)?;
+                    Ok(Value::String(s.to_lowercase()))
+                },
+            ),
+        ),
+    );
+
+    let trim_handle = identifiers.by_name("trim");
+
+    methods.insert(
+        trim_handle,
+        string_method(
+            env,
+            trim_handle,
+            None,
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 _args: Vec<Value>,
+                 call_pos: Position| {
+                    let s = this_string(func, interpreter, call_pos)?;
+                    Ok(Value::String(s.trim().into()))
+                },
+            ),
+        ),
+    );
+
+    let replace_handle = identifiers.by_name("replace");
+
+    methods.insert(
+        replace_handle,
+        string_method(
+            env,
+            replace_handle,
+            Some(Rc::new(vec![
+                Required(identifiers.by_name("pattern")),
+                Required(identifiers.by_name("replacement")),
+            ])),
+            Rc::new(
+                |func: &LoxFunction,
+                 interpreter: &Interpreter,
+                 _env: &Environment,
+                 args: Vec<Value>,
+                 call_pos: Position| {
+                    let s = this_string(func, interpreter, call_pos)?;
+                    let pattern = string_arg(func, interpreter, &args[0], call_pos)?;
+                    let replacement = string_arg(func, interpreter, &args[1], call_pos)?;
+
+                    Ok(Value::String(s.replace(&pattern, &replacement)))
+                },
+            ),
+        ),
+    );
+
+    LoxClass::new_native(Identifier::string(), None, methods)
+}
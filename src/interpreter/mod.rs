@@ -1,34 +1,56 @@
+pub mod bitwise;
 pub mod environment;
 
 pub mod eval;
 pub mod eval_result;
 mod execute;
 pub mod host;
-pub mod lexical_scope;
 pub mod lox_array;
 pub mod lox_callable;
 mod lox_class;
+pub mod lox_enum;
+pub mod lox_fs;
 pub mod lox_function;
 mod lox_instance;
+pub mod lox_json;
+pub mod lox_math;
+pub mod lox_random;
+pub mod lox_range;
+pub mod lox_string;
 mod natives;
+mod pattern;
+mod reflection;
+pub mod type_checker;
 pub mod value;
 
 use crate::parser::{
     statements::Stmt, IdentifierHandle, IdentifierNames, IdentifierUse,
 };
+use crate::resolver::lexical_scope::Resolver;
+use crate::scanner::token::Position;
 use environment::Environment;
 use eval_result::EvalResult;
 use execute::Exec;
+use fnv::FnvHashMap;
 use host::Host;
+use std::cell::RefCell;
 use std::rc::Rc;
 use value::Value;
-use lexical_scope::Resolver;
+
+// a test registered with test(name, fn)
+pub struct RegisteredTest {
+    pub name: String,
+    pub func: Value,
+    pub pos: Position,
+}
 
 pub struct Interpreter {
     global: Environment,
     resolver: Resolver,
     host: Rc<Host>,
-    names: Rc<IdentifierNames>,
+    names: RefCell<Rc<IdentifierNames>>, // grows when natives create new property names
+    handles: RefCell<FnvHashMap<String, IdentifierHandle>>, // the index of names
+    tests: RefCell<Vec<RegisteredTest>>,
 }
 
 impl Interpreter {
@@ -37,7 +59,15 @@ impl Interpreter {
             global: env,
             resolver,
             host: Rc::clone(host),
-            names: Rc::clone(names),
+            names: RefCell::new(Rc::clone(names)),
+            handles: RefCell::new(
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(handle, name)| (name.clone(), handle))
+                    .collect(),
+            ),
+            tests: RefCell::new(Vec::new()),
         }
     }
 
@@ -49,12 +79,48 @@ impl Interpreter {
         Ok(())
     }
 
+    pub fn register_test(&self, name: String, func: Value, pos: Position) {
+        self.tests.borrow_mut().push(RegisteredTest { name, func, pos });
+    }
+
+    // the names and positions of the registered tests, in registration order
+    pub fn registered_tests(&self) -> Vec<(String, Position)> {
+        self.tests
+            .borrow()
+            .iter()
+            .map(|test| (test.name.clone(), test.pos))
+            .collect()
+    }
+
+    pub fn run_test(&self, idx: usize) -> EvalResult<()> {
+        let (func, pos) = {
+            let tests = self.tests.borrow();
+            (tests[idx].func.clone(), tests[idx].pos)
+        };
+
+        self.call_value(&self.global, func, vec![], pos)?;
+        Ok(())
+    }
+
     pub fn name(&self, handle: IdentifierHandle) -> String {
-        self.names[handle].clone()
+        self.names.borrow()[handle].clone()
     }
 
     pub fn names(&self) -> Rc<IdentifierNames> {
-        Rc::clone(&self.names)
+        Rc::clone(&self.names.borrow())
+    }
+
+    // the handle of a name, the name is added if it was never used before
+    pub fn intern(&self, name: &str) -> IdentifierHandle {
+        if let Some(&handle) = self.handles.borrow().get(name) {
+            return handle;
+        }
+
+        let mut names = self.names.borrow_mut();
+        let handle = names.len();
+        Rc::make_mut(&mut names).push(name.into());
+        self.handles.borrow_mut().insert(name.into(), handle);
+        handle
     }
 
     pub fn lookup_variable(&self, env: &Environment, identifier: &IdentifierUse) -> Option<Value> {
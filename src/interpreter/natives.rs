@@ -1,10 +1,14 @@
+use super::lox_array::new_elox_array;
 use super::lox_callable::LoxCallable;
 use super::lox_function::LoxFunctionParams;
+use super::lox_random::Pcg32;
+use super::lox_string::parse_number;
 use super::value::Value;
 use super::Environment;
 use super::Interpreter;
 use super::{eval_result::EvalError, EvalResult};
-use crate::parser::{Identifier, IdentifierNames};
+use crate::parser::expressions::ContextLessFuncParam::{DefaultValued, Required};
+use crate::parser::{Identifier, IdentifierHandle, IdentifierHandlesGenerator, IdentifierNames};
 use crate::scanner::token::Position;
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -12,9 +16,10 @@ use std::rc::Rc;
 #[derive(Clone, Debug)]
 pub enum NativeValue {
     Vector(Rc<RefCell<Vec<Value>>>),
+    Range(f64, f64, bool),
+    Random(Pcg32),
 }
 
-#[allow(irrefutable_let_patterns)]
 impl NativeValue {
     pub fn into_vec(&self) -> &RefCell<Vec<Value>> {
         if let NativeValue::Vector(vec) = &self {
@@ -58,3 +63,488 @@ impl LoxCallable for Clock {
         false
     }
 }
+
+// str(value): converts any value to a string, honouring the #str trait
+#[derive(Debug)]
+pub struct Str {
+    name: IdentifierHandle,
+    value: IdentifierHandle,
+}
+
+impl Str {
+    pub fn new(identifiers: &mut IdentifierHandlesGenerator) -> Str {
+        Str {
+            name: identifiers.by_name("str"),
+            value: identifiers.by_name("value"),
+        }
+    }
+}
+
+impl LoxCallable for Str {
+    fn call(
+        &self,
+        interpreter: &Interpreter,
+        _env: &Environment,
+        args: Vec<Value>,
+        call_pos: Position,
+    ) -> EvalResult<Value> {
+        Ok(Value::String(args[0].to_str(interpreter, call_pos)?))
+    }
+
+    fn params(&self) -> LoxFunctionParams {
+        Some(Rc::new(vec![Required(self.value)]))
+    }
+
+    fn name(&self, names: &Rc<IdentifierNames>) -> String {
+        names[self.name].clone()
+    }
+
+    fn has_rest_param(&self) -> bool {
+        false
+    }
+}
+
+// num(value): parses a string into a number, nil if it is not a valid number
+#[derive(Debug)]
+pub struct Num {
+    name: IdentifierHandle,
+    value: IdentifierHandle,
+}
+
+impl Num {
+    pub fn new(identifiers: &mut IdentifierHandlesGenerator) -> Num {
+        Num {
+            name: identifiers.by_name("num"),
+            value: identifiers.by_name("value"),
+        }
+    }
+}
+
+impl LoxCallable for Num {
+    fn call(
+        &self,
+        _interpreter: &Interpreter,
+        _env: &Environment,
+        args: Vec<Value>,
+        _call_pos: Position,
+    ) -> EvalResult<Value> {
+        Ok(match &args[0] {
+            Value::Number(n) => Value::Number(*n),
+            Value::String(s) => parse_number(s).map_or(Value::Nil, Value::Number),
+            _ => Value::Nil,
+        })
+    }
+
+    fn params(&self) -> LoxFunctionParams {
+        Some(Rc::new(vec![Required(self.value)]))
+    }
+
+    fn name(&self, names: &Rc<IdentifierNames>) -> String {
+        names[self.name].clone()
+    }
+
+    fn has_rest_param(&self) -> bool {
+        false
+    }
+}
+
+// readLine(): the next line of the input without its line break, nil at the end of the input
+#[derive(Debug)]
+pub struct ReadLine {
+    name: IdentifierHandle,
+}
+
+impl ReadLine {
+    pub fn new(identifiers: &mut IdentifierHandlesGenerator) -> ReadLine {
+        ReadLine {
+            name: identifiers.by_name("readLine"),
+        }
+    }
+}
+
+impl LoxCallable for ReadLine {
+    fn call(
+        &self,
+        interpreter: &Interpreter,
+        _env: &Environment,
+        _args: Vec<Value>,
+        _call_pos: Position,
+    ) -> EvalResult<Value> {
+        Ok((interpreter.host.read_line)().map_or(Value::Nil, Value::String))
+    }
+
+    fn params(&self) -> LoxFunctionParams {
+        None
+    }
+
+    fn name(&self, names: &Rc<IdentifierNames>) -> String {
+        names[self.name].clone()
+    }
+
+    fn has_rest_param(&self) -> bool {
+        false
+    }
+}
+
+// readAll(): the rest of the input
+#[derive(Debug)]
+pub struct ReadAll {
+    name: IdentifierHandle,
+}
+
+impl ReadAll {
+    pub fn new(identifiers: &mut IdentifierHandlesGenerator) -> ReadAll {
+        ReadAll {
+            name: identifiers.by_name("readAll"),
+        }
+    }
+}
+
+impl LoxCallable for ReadAll {
+    fn call(
+        &self,
+        interpreter: &Interpreter,
+        _env: &Environment,
+        _args: Vec<Value>,
+        _call_pos: Position,
+    ) -> EvalResult<Value> {
+        Ok(Value::String((interpreter.host.read_all)()))
+    }
+
+    fn params(&self) -> LoxFunctionParams {
+        None
+    }
+
+    fn name(&self, names: &Rc<IdentifierNames>) -> String {
+        names[self.name].clone()
+    }
+
+    fn has_rest_param(&self) -> bool {
+        false
+    }
+}
+
+// args(): the command line arguments passed to the script
+#[derive(Debug)]
+pub struct Args {
+    name: IdentifierHandle,
+}
+
+impl Args {
+    pub fn new(identifiers: &mut IdentifierHandlesGenerator) -> Args {
+        Args {
+            name: identifiers.by_name("args"),
+        }
+    }
+}
+
+impl LoxCallable for Args {
+    fn call(
+        &self,
+        interpreter: &Interpreter,
+        _env: &Environment,
+        _args: Vec<Value>,
+        _call_pos: Position,
+    ) -> EvalResult<Value> {
+        let args = (interpreter.host.args)()
+            .into_iter()
+            .map(Value::String)
+            .collect();
+
+        Ok(new_elox_array(args, interpreter))
+    }
+
+    fn params(&self) -> LoxFunctionParams {
+        None
+    }
+
+    fn name(&self, names: &Rc<IdentifierNames>) -> String {
+        names[self.name].clone()
+    }
+
+    fn has_rest_param(&self) -> bool {
+        false
+    }
+}
+
+// env(name): the value of an environment variable, nil if it is not set
+#[derive(Debug)]
+pub struct Env {
+    name: IdentifierHandle,
+    var: IdentifierHandle,
+}
+
+impl Env {
+    pub fn new(identifiers: &mut IdentifierHandlesGenerator) -> Env {
+        Env {
+            name: identifiers.by_name("env"),
+            var: identifiers.by_name("name"),
+        }
+    }
+}
+
+impl LoxCallable for Env {
+    fn call(
+        &self,
+        interpreter: &Interpreter,
+        _env: &Environment,
+        args: Vec<Value>,
+        call_pos: Position,
+    ) -> EvalResult<Value> {
+        match &args[0] {
+            Value::String(var) => {
+                Ok((interpreter.host.env_var)(var).map_or(Value::Nil, Value::String))
+            }
+            arg => Err(EvalError::UnexpectedArgumentType(
+                call_pos,
+                self.name(&interpreter.names()),
+                "string".into(),
+                arg.type_(),
+            )),
+        }
+    }
+
+    fn params(&self) -> LoxFunctionParams {
+        Some(Rc::new(vec![Required(self.var)]))
+    }
+
+    fn name(&self, names: &Rc<IdentifierNames>) -> String {
+        names[self.name].clone()
+    }
+
+    fn has_rest_param(&self) -> bool {
+        false
+    }
+}
+
+// exit(code = 0): ends the script with an exit code
+#[derive(Debug)]
+pub struct Exit {
+    name: IdentifierHandle,
+    code: IdentifierHandle,
+}
+
+impl Exit {
+    pub fn new(identifiers: &mut IdentifierHandlesGenerator) -> Exit {
+        Exit {
+            name: identifiers.by_name("exit"),
+            code: identifiers.by_name("code"),
+        }
+    }
+}
+
+impl LoxCallable for Exit {
+    fn call(
+        &self,
+        interpreter: &Interpreter,
+        _env: &Environment,
+        args: Vec<Value>,
+        call_pos: Position,
+    ) -> EvalResult<Value> {
+        match args[0] {
+            Value::Number(code) if code.fract() == 0f64 => {
+                (interpreter.host.exit)(code as i32);
+                // the host did not exit, stop the script
+                Err(EvalError::Exit(call_pos, code as i32))
+            }
+            _ => Err(EvalError::UnexpectedArgumentType(
+                call_pos,
+                self.name(&interpreter.names()),
+                "integer".into(),
+                args[0].type_(),
+            )),
+        }
+    }
+
+    fn params(&self) -> LoxFunctionParams {
+        Some(Rc::new(vec![DefaultValued(self.code, Value::Number(0f64))]))
+    }
+
+    fn name(&self, names: &Rc<IdentifierNames>) -> String {
+        names[self.name].clone()
+    }
+
+    fn has_rest_param(&self) -> bool {
+        false
+    }
+}
+
+// assert(condition, message = nil): fails when the condition is falsy
+#[derive(Debug)]
+pub struct Assert {
+    name: IdentifierHandle,
+    condition: IdentifierHandle,
+    message: IdentifierHandle,
+}
+
+impl Assert {
+    pub fn new(identifiers: &mut IdentifierHandlesGenerator) -> Assert {
+        Assert {
+            name: identifiers.by_name("assert"),
+            condition: identifiers.by_name("condition"),
+            message: identifiers.by_name("message"),
+        }
+    }
+}
+
+impl LoxCallable for Assert {
+    fn call(
+        &self,
+        interpreter: &Interpreter,
+        _env: &Environment,
+        args: Vec<Value>,
+        call_pos: Position,
+    ) -> EvalResult<Value> {
+        if args[0].is_truthy() {
+            return Ok(Value::Nil);
+        }
+
+        let message = match &args[1] {
+            Value::Nil => None,
+            message => Some(message.to_str(interpreter, call_pos)?),
+        };
+
+        Err(EvalError::AssertionFailed(call_pos, message))
+    }
+
+    fn params(&self) -> LoxFunctionParams {
+        Some(Rc::new(vec![
+            Required(self.condition),
+            DefaultValued(self.message, Value::Nil),
+        ]))
+    }
+
+    fn name(&self, names: &Rc<IdentifierNames>) -> String {
+        names[self.name].clone()
+    }
+
+    fn has_rest_param(&self) -> bool {
+        false
+    }
+}
+
+// strings are quoted so that "1" and 1 can be told apart in failure messages
+fn assertion_repr(value: &Value, interpreter: &Interpreter, pos: Position) -> EvalResult<String> {
+    match value {
+        Value::String(s) => Ok(format!("\"{}\"", s)),
+        _ => value.to_str(interpreter, pos),
+    }
+}
+
+// assertEq(actual, expected): fails when actual != expected
+#[derive(Debug)]
+pub struct AssertEq {
+    name: IdentifierHandle,
+    actual: IdentifierHandle,
+    expected: IdentifierHandle,
+}
+
+impl AssertEq {
+    pub fn new(identifiers: &mut IdentifierHandlesGenerator) -> AssertEq {
+        AssertEq {
+            name: identifiers.by_name("assertEq"),
+            actual: identifiers.by_name("actual"),
+            expected: identifiers.by_name("expected"),
+        }
+    }
+}
+
+impl LoxCallable for AssertEq {
+    fn call(
+        &self,
+        interpreter: &Interpreter,
+        _env: &Environment,
+        args: Vec<Value>,
+        call_pos: Position,
+    ) -> EvalResult<Value> {
+        if args[0] == args[1] {
+            return Ok(Value::Nil);
+        }
+
+        Err(EvalError::AssertionFailed(
+            call_pos,
+            Some(format!(
+                "expected {}, got {}",
+                assertion_repr(&args[1], interpreter, call_pos)?,
+                assertion_repr(&args[0], interpreter, call_pos)?,
+            )),
+        ))
+    }
+
+    fn params(&self) -> LoxFunctionParams {
+        Some(Rc::new(vec![Required(self.actual), Required(self.expected)]))
+    }
+
+    fn name(&self, names: &Rc<IdentifierNames>) -> String {
+        names[self.name].clone()
+    }
+
+    fn has_rest_param(&self) -> bool {
+        false
+    }
+}
+
+// test(name, fn): registers a test, only `elox test` runs it
+#[derive(Debug)]
+pub struct Test {
+    name: IdentifierHandle,
+    test_name: IdentifierHandle,
+    func: IdentifierHandle,
+}
+
+impl Test {
+    pub fn new(identifiers: &mut IdentifierHandlesGenerator) -> Test {
+        Test {
+            name: identifiers.by_name("test"),
+            test_name: identifiers.by_name("name"),
+            func: identifiers.by_name("fn"),
+        }
+    }
+}
+
+impl LoxCallable for Test {
+    fn call(
+        &self,
+        interpreter: &Interpreter,
+        _env: &Environment,
+        args: Vec<Value>,
+        call_pos: Position,
+    ) -> EvalResult<Value> {
+        let name = match &args[0] {
+            Value::String(name) => name.clone(),
+            other => {
+                return Err(EvalError::UnexpectedArgumentType(
+                    call_pos,
+                    self.name(&interpreter.names()),
+                    "string".into(),
+                    other.type_(),
+                ))
+            }
+        };
+
+        match &args[1] {
+            Value::Callable(_) => {
+                interpreter.register_test(name, args[1].clone(), call_pos);
+                Ok(Value::Nil)
+            }
+            other => Err(EvalError::UnexpectedArgumentType(
+                call_pos,
+                self.name(&interpreter.names()),
+                "function".into(),
+                other.type_(),
+            )),
+        }
+    }
+
+    fn params(&self) -> LoxFunctionParams {
+        Some(Rc::new(vec![Required(self.test_name), Required(self.func)]))
+    }
+
+    fn name(&self, names: &Rc<IdentifierNames>) -> String {
+        names[self.name].clone()
+    }
+
+    fn has_rest_param(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,157 @@
+use super::environment::Environment;
+use super::eval_result::{EvalError, EvalResult};
+use super::lox_array::{array_values, new_elox_array};
+use super::lox_callable::LoxCallable;
+use super::lox_class::LoxClass;
+use super::value::{CallableValue, Value};
+use super::Interpreter;
+use crate::parser::expressions::{Literal, Pattern};
+use crate::parser::IdentifierUse;
+use std::rc::Rc;
+
+fn literal_value(literal: &Literal) -> Value {
+    match literal {
+        Literal::Number(n) => Value::Number(*n),
+        Literal::String(s) => Value::String(s.clone()),
+        Literal::Nil => Value::Nil,
+        Literal::Boolean(b) => Value::Boolean(*b),
+    }
+}
+
+fn is_instance_of(value: &Value, class: &LoxClass) -> bool {
+    match value {
+        Value::Instance(instance) => instance.is_instance_of(class),
+        _ => false,
+    }
+}
+
+impl Interpreter {
+    // defines the pattern's bindings in env, Ok(false) if the value doesn't match
+    pub fn match_pattern(
+        &self,
+        env: &Environment,
+        pattern: &Pattern,
+        value: &Value,
+    ) -> EvalResult<bool> {
+        match pattern {
+            Pattern::Wildcard => Ok(true),
+            Pattern::Literal(literal) => Ok(*value == literal_value(literal)),
+            Pattern::Binding(identifier) => {
+                env.define(identifier.name, value.clone());
+                Ok(true)
+            }
+            Pattern::Constant(identifier) => match self.pattern_name(env, identifier)? {
+                // a class matches all of its instances
+                Value::Callable(CallableValue::Class(class)) => Ok(is_instance_of(value, &class)),
+                constant => Ok(*value == constant),
+            },
+            Pattern::Variant(identifier, patterns) => {
+                let class = self.pattern_class(env, identifier)?;
+
+                if !is_instance_of(value, &class) {
+                    return Ok(false);
+                }
+
+                // fields are destructured in the order of the constructor's parameters
+                let fields = class
+                    .params()
+                    .map(|params| params.iter().map(|p| p.identifier()).collect())
+                    .unwrap_or_else(Vec::new);
+
+                if fields.len() != patterns.len() {
+                    return Err(EvalError::WrongNumberOfPatternFields(
+                        identifier.pos,
+                        self.name(identifier.name),
+                        fields.len(),
+                        patterns.len(),
+                    ));
+                }
+
+                let instance = value.clone().into_instance().unwrap();
+
+                for (field, pattern) in fields.iter().zip(patterns) {
+                    let field_value = instance
+                        .get(*field, self, identifier.pos)?
+                        .unwrap_or(Value::Nil);
+
+                    if !self.match_pattern(env, pattern, &field_value)? {
+                        return Ok(false);
+                    }
+                }
+
+                Ok(true)
+            }
+            Pattern::Instance(identifier, fields) => {
+                let class = self.pattern_class(env, identifier)?;
+
+                if !is_instance_of(value, &class) {
+                    return Ok(false);
+                }
+
+                let instance = value.clone().into_instance().unwrap();
+
+                for (field, pattern) in fields {
+                    match instance.get(field.name, self, field.pos)? {
+                        Some(field_value) => {
+                            if !self.match_pattern(env, pattern, &field_value)? {
+                                return Ok(false);
+                            }
+                        }
+                        None => return Ok(false),
+                    }
+                }
+
+                Ok(true)
+            }
+            Pattern::Array(patterns, rest) => {
+                let values = match array_values(value) {
+                    Some(values) => values,
+                    None => return Ok(false),
+                };
+
+                if values.len() < patterns.len() || (rest.is_none() && values.len() != patterns.len())
+                {
+                    return Ok(false);
+                }
+
+                for (pattern, value) in patterns.iter().zip(&values) {
+                    if !self.match_pattern(env, pattern, value)? {
+                        return Ok(false);
+                    }
+                }
+
+                if let Some(rest) = rest {
+                    let rest_values = values[patterns.len()..].to_vec();
+                    return self.match_pattern(env, rest, &new_elox_array(rest_values, self));
+                }
+
+                Ok(true)
+            }
+        }
+    }
+
+    fn pattern_name(&self, env: &Environment, identifier: &IdentifierUse) -> EvalResult<Value> {
+        match self.lookup_variable(env, identifier) {
+            Some(value) => Ok(value),
+            None => Err(EvalError::UndefinedVariable(
+                identifier.pos,
+                self.name(identifier.name),
+            )),
+        }
+    }
+
+    fn pattern_class(
+        &self,
+        env: &Environment,
+        identifier: &IdentifierUse,
+    ) -> EvalResult<Rc<LoxClass>> {
+        match self.pattern_name(env, identifier)? {
+            Value::Callable(CallableValue::Class(class)) => Ok(class),
+            value => Err(EvalError::PatternMustBeAClass(
+                identifier.pos,
+                self.name(identifier.name),
+                value.type_(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use super::eval_result::{EvalError, EvalResult};
+use super::lox_array::new_elox_array;
+use super::lox_callable::LoxCallable;
+use super::lox_class::LoxClass;
+use super::lox_function::{LoxFunction, LoxFunctionParams, NativeFunction};
+use super::lox_instance::LoxInstance;
+use super::lox_string::string_arg;
+use super::value::{CallableValue, Value};
+use super::Environment;
+use super::Interpreter;
+use crate::parser::expressions::ContextLessFuncParam::*;
+use crate::parser::{Identifier, IdentifierHandle, IdentifierHandlesGenerator};
+use crate::scanner::token::Position;
+use std::rc::Rc;
+
+// the name returned by type(value), arrays, namespaces and functions are told apart
+pub fn type_name(value: &Value, namespaces: &[LoxInstance]) -> String {
+    match value {
+        Value::Instance(instance) if instance.class_name() == Identifier::array() => "array".into(),
+        Value::Instance(instance) if namespaces.contains(instance) => "namespace".into(),
+        Value::Callable(CallableValue::Class(_)) => "class".into(),
+        Value::Callable(_) => "function".into(),
+        _ => value.type_(),
+    }
+}
+
+fn instance_arg(
+    func: &LoxFunction,
+    interpreter: &Interpreter,
+    arg: &Value,
+    call_pos: Position,
+) -> EvalResult<LoxInstance> {
+    match arg {
+        Value::Instance(instance) => Ok(instance.clone()),
+        _ => Err(EvalError::UnexpectedArgumentType(
+            call_pos,
+            func.name(&interpreter.names()),
+            "instance".into(),
+            arg.type_(),
+        )),
+    }
+}
+
+fn class_arg(
+    func: &LoxFunction,
+    interpreter: &Interpreter,
+    arg: &Value,
+    call_pos: Position,
+) -> EvalResult<Rc<LoxClass>> {
+    match arg {
+        Value::Callable(CallableValue::Class(class)) => Ok(Rc::clone(class)),
+        _ => Err(EvalError::UnexpectedArgumentType(
+            call_pos,
+            func.name(&interpreter.names()),
+            "class".into(),
+            arg.type_(),
+        )),
+    }
+}
+
+// private members cannot be reached through reflection
+fn public_field(
+    func: &LoxFunction,
+    interpreter: &Interpreter,
+    instance: &LoxInstance,
+    name: &Value,
+    call_pos: Position,
+) -> EvalResult<IdentifierHandle> {
+    let name = string_arg(func, interpreter, name, call_pos)?;
+    let prop = interpreter.intern(&name);
+
+    if instance.private_owner(prop).is_some() {
+        return Err(EvalError::PrivateProperty(call_pos, name));
+    }
+
+    Ok(prop)
+}
+
+fn sorted_names(interpreter: &Interpreter, handles: Vec<IdentifierHandle>) -> Value {
+    let mut names = handles
+        .into_iter()
+        .map(|handle| interpreter.name(handle))
+        .collect::<Vec<_>>();
+
+    names.sort();
+    names.dedup();
+
+    new_elox_array(names.into_iter().map(Value::String).collect(), interpreter)
+}
+
+fn define_function(
+    env: &Environment,
+    identifiers: &mut IdentifierHandlesGenerator,
+    name: &str,
+    params: &[&str],
+    func: Rc<NativeFunction>,
+) {
+    let handle = identifiers.by_name(name);
+    let params: LoxFunctionParams = Some(Rc::new(
+        params
+            .iter()
+            .map(|param| Required(identifiers.by_name(param)))
+            .collect(),
+    ));
+
+    env.define(
+        handle,
+        Value::Callable(CallableValue::Function(Rc::new(LoxFunction::new_native(
+            func,
+            env.clone(),
+            false,
+            params,
+            handle,
+        )))),
+    );
+}
+
+pub fn register_reflection_natives(
+    env: &Environment,
+    identifiers: &mut IdentifierHandlesGenerator,
+) {
+    // the native namespaces are instances, kept to tell them apart
+    let namespaces: Vec<LoxInstance> = ["Math", "JSON", "fs"]
+        .iter()
+        .filter_map(|name| env.get(0, identifiers.by_name(name)))
+        .filter_map(Value::into_instance)
+        .collect();
+
+    define_function(
+        env,
+        identifiers,
+        "type",
+        &["value"],
+        Rc::new(
+            move |_func: &LoxFunction,
+                  _interpreter: &Interpreter,
+                  _env: &Environment,
+                  args: Vec<Value>,
+                  _call_pos: Position| {
+                Ok(Value::String(type_name(&args[0], &namespaces)))
+            },
+        ),
+    );
+
+    define_function(
+        env,
+        identifiers,
+        "fields",
+        &["object"],
+        Rc::new(
+            |func: &LoxFunction,
+             interpreter: &Interpreter,
+             _env: &Environment,
+             args: Vec<Value>,
+             call_pos: Position| {
+                let instance = instance_arg(func, interpreter, &args[0], call_pos)?;
+                let fields = instance
+                    .fields()
+                    .into_iter()
+                    .map(|(prop, _)| prop)
+                    .filter(|prop| instance.private_owner(*prop).is_none())
+                    .collect();
+
+                Ok(sorted_names(interpreter, fields))
+            },
+        ),
+    );
+
+    // the public methods of a class, including the inherited ones
+    define_function(
+        env,
+        identifiers,
+        "methods",
+        &["class"],
+        Rc::new(
+            |func: &LoxFunction,
+             interpreter: &Interpreter,
+             _env: &Environment,
+             args: Vec<Value>,
+             call_pos: Position| {
+                let class = class_arg(func, interpreter, &args[0], call_pos)?;
+                let mut methods = Vec::new();
+                let mut mold = Some(Rc::clone(&class.mold));
+
+                while let Some(current) = mold {
+                    methods.extend(current.methods.keys().copied());
+                    mold = current
+                        .superclass
+                        .as_ref()
+                        .map(|parent| Rc::clone(&parent.mold));
+                }
+
+                methods.retain(|method| class.mold.private_owner(*method).is_none());
+                Ok(sorted_names(interpreter, methods))
+            },
+        ),
+    );
+
+    define_function(
+        env,
+        identifiers,
+        "hasField",
+        &["object", "name"],
+        Rc::new(
+            |func: &LoxFunction,
+             interpreter: &Interpreter,
+             _env: &Environment,
+             args: Vec<Value>,
+             call_pos: Position| {
+                let instance = instance_arg(func, interpreter, &args[0], call_pos)?;
+                let name = string_arg(func, interpreter, &args[1], call_pos)?;
+                let prop = interpreter.intern(&name);
+
+                let has_field = instance.private_owner(prop).is_none()
+                    && instance.fields().iter().any(|(field, _)| *field == prop);
+
+                Ok(Value::Boolean(has_field))
+            },
+        ),
+    );
+
+    // reads a property like obj.name does, nil if it is not defined
+    define_function(
+        env,
+        identifiers,
+        "getField",
+        &["object", "name"],
+        Rc::new(
+            |func: &LoxFunction,
+             interpreter: &Interpreter,
+             _env: &Environment,
+             args: Vec<Value>,
+             call_pos: Position| {
+                let instance = instance_arg(func, interpreter, &args[0], call_pos)?;
+                let prop = public_field(func, interpreter, &instance, &args[1], call_pos)?;
+
+                Ok(instance
+                    .get(prop, interpreter, call_pos)?
+                    .unwrap_or(Value::Nil))
+            },
+        ),
+    );
+
+    // writes a property like obj.name = value does and returns the value
+    define_function(
+        env,
+        identifiers,
+        "setField",
+        &["object", "name", "value"],
+        Rc::new(
+            |func: &LoxFunction,
+             interpreter: &Interpreter,
+             _env: &Environment,
+             args: Vec<Value>,
+             call_pos: Position| {
+                let instance = instance_arg(func, interpreter, &args[0], call_pos)?;
+                let prop = public_field(func, interpreter, &instance, &args[1], call_pos)?;
+
+                instance.set(prop, &args[2], interpreter, call_pos)?;
+                Ok(args[2].clone())
+            },
+        ),
+    );
+
+    define_function(
+        env,
+        identifiers,
+        "superclass",
+        &["class"],
+        Rc::new(
+            |func: &LoxFunction,
+             interpreter: &Interpreter,
+             _env: &Environment,
+             args: Vec<Value>,
+             call_pos: Position| {
+                let class = class_arg(func, interpreter, &args[0], call_pos)?;
+
+                Ok(match &class.mold.superclass {
+                    Some(parent) => Value::Callable(CallableValue::Class(Rc::clone(parent))),
+                    None => Value::Nil,
+                })
+            },
+        ),
+    );
+}
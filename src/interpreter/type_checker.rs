@@ -0,0 +1,1430 @@
+use crate::parser::{
+    expressions::{
+        BinaryOperator, Expr, ExprCtx, FuncExpr, FuncParam, Literal, LogicalOperator, MatchArmBody,
+        Pattern, UnaryOperator,
+    },
+    statements::{Destructuring, Stmt},
+    types::{FunctionType, Type},
+    Identifier, IdentifierHandle, IdentifierNames,
+};
+use crate::resolver::BUILTIN_ENUMS;
+use crate::scanner::scanner_result::ErrorPosition;
+use crate::scanner::token::Position;
+use fnv::FnvHashMap;
+use std::fmt;
+use std::rc::Rc;
+
+// A gradual type checker: unannotated values are 'any' and are never reported,
+// only what can be proven wrong from the annotations is
+
+#[derive(Default)]
+struct ClassInfo {
+    superclass: Option<IdentifierHandle>,
+    fields: FnvHashMap<IdentifierHandle, Type>,
+    methods: FnvHashMap<IdentifierHandle, Type>, // 'any' for overloaded methods
+    static_methods: FnvHashMap<IdentifierHandle, Type>,
+    open: bool, // properties are not known in advance (built-in classes)
+}
+
+pub struct TypeChecker {
+    scopes: Vec<FnvHashMap<IdentifierHandle, Type>>,
+    classes: FnvHashMap<IdentifierHandle, ClassInfo>,
+    return_types: Vec<Type>,
+    this_types: Vec<Type>,
+    names: Rc<IdentifierNames>,
+    diagnostics: Vec<TypeDiagnostic>,
+}
+
+impl TypeChecker {
+    pub fn new(names: &Rc<IdentifierNames>) -> TypeChecker {
+        let mut checker = TypeChecker {
+            scopes: vec![FnvHashMap::default()],
+            classes: FnvHashMap::default(),
+            return_types: Vec::new(),
+            this_types: Vec::new(),
+            names: Rc::clone(names),
+            diagnostics: Vec::new(),
+        };
+
+        checker.declare_natives();
+        checker
+    }
+
+    fn handle(&self, name: &str) -> Option<IdentifierHandle> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    fn declare_natives(&mut self) {
+        let natives = [
+            ("clock", Type::function(vec![], Type::Number)),
+            ("str", Type::function(vec![Type::Any], Type::String)),
+            ("type", Type::function(vec![Type::Any], Type::String)),
+            (
+                "num",
+                Type::function(vec![Type::Any], Type::Nullable(Box::new(Type::Number))),
+            ),
+            (
+                "readLine",
+                Type::function(vec![], Type::Nullable(Box::new(Type::String))),
+            ),
+            ("readAll", Type::function(vec![], Type::String)),
+            ("args", Type::function(vec![], Type::array(Type::String))),
+            (
+                "env",
+                Type::function(vec![Type::String], Type::Nullable(Box::new(Type::String))),
+            ),
+        ];
+
+        for (name, type_) in natives.iter() {
+            if let Some(handle) = self.handle(name) {
+                self.define(handle, type_.clone());
+            }
+        }
+
+        let range = self.handle("Range");
+        let classes = [Some(Identifier::array()), Some(Identifier::string()), range];
+
+        for class in classes.iter().flatten() {
+            self.classes.insert(
+                *class,
+                ClassInfo {
+                    open: true,
+                    ..ClassInfo::default()
+                },
+            );
+            self.define(*class, Type::Class(*class));
+        }
+
+        for (name, variants) in BUILTIN_ENUMS {
+            let variants = variants
+                .iter()
+                .map(|(variant, fields)| {
+                    let fields =
+                        fields.map(|fields| fields.iter().map(|f| self.handle(f)).collect());
+                    (self.handle(variant), fields)
+                })
+                .collect::<Vec<_>>();
+
+            if let Some(name) = self.handle(name) {
+                for (variant, fields) in variants {
+                    if let (Some(variant), Some(fields)) = (variant, fields.unwrap_or(Some(vec![])))
+                    {
+                        self.declare_variant(name, variant, Some(fields));
+                    }
+                }
+
+                self.declare_enum(name);
+            }
+        }
+    }
+
+    fn declare_enum(&mut self, name: IdentifierHandle) {
+        self.classes.entry(name).or_default();
+        self.define(name, Type::Class(name));
+    }
+
+    // variants with fields are constructors, the others are values
+    fn declare_variant(
+        &mut self,
+        enum_name: IdentifierHandle,
+        variant: IdentifierHandle,
+        fields: Option<Vec<IdentifierHandle>>,
+    ) {
+        let mut info = ClassInfo {
+            superclass: Some(enum_name),
+            ..ClassInfo::default()
+        };
+
+        let type_ = match fields {
+            Some(fields) if !fields.is_empty() => {
+                let params = fields.iter().map(|_| Type::Any).collect();
+                info.methods.insert(
+                    Identifier::init(),
+                    Type::Function(Rc::new(FunctionType {
+                        required: fields.len(),
+                        params,
+                        rest: false,
+                        ret: Type::Nil,
+                    })),
+                );
+
+                for field in fields {
+                    info.fields.insert(field, Type::Any);
+                }
+
+                Type::Class(variant)
+            }
+            _ => Type::Named(variant, vec![]),
+        };
+
+        self.classes.insert(variant, info);
+        self.define(variant, type_);
+    }
+
+    pub fn check(mut self, stmts: &[Stmt]) -> Vec<TypeDiagnostic> {
+        // classes can be used before their declaration inside function bodies
+        for stmt in stmts {
+            self.collect_classes(stmt);
+        }
+
+        for stmt in stmts {
+            self.check_stmt(stmt);
+        }
+
+        self.diagnostics
+    }
+
+    fn name(&self, handle: IdentifierHandle) -> String {
+        self.names[handle].clone()
+    }
+
+    fn type_name(&self, type_: &Type) -> String {
+        type_.name(&self.names)
+    }
+
+    fn report(&mut self, diagnostic: TypeDiagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(FnvHashMap::default());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn define(&mut self, name: IdentifierHandle, type_: Type) {
+        self.scopes.last_mut().unwrap().insert(name, type_);
+    }
+
+    fn lookup(&self, name: IdentifierHandle) -> Type {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&name))
+            .cloned()
+            .unwrap_or(Type::Any)
+    }
+
+    fn collect_classes(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::ClassDecl(class_decl) => {
+                let mut info = ClassInfo {
+                    superclass: class_decl.superclass.as_ref().map(|s| s.identifier.name),
+                    ..ClassInfo::default()
+                };
+
+                // a superclass unknown to the checker could define any property
+                if let Some(superclass) = info.superclass {
+                    info.open = !self.classes.contains_key(&superclass);
+                }
+
+                for method in class_decl
+                    .methods
+                    .iter()
+                    .chain(&class_decl.getters)
+                    .chain(&class_decl.setters)
+                {
+                    collect_this_assignments(&method.body, &mut info.fields);
+                }
+
+                for method in &class_decl.methods {
+                    self.collect_method(&mut info.methods, method);
+                }
+
+                for method in &class_decl.static_methods {
+                    self.collect_method(&mut info.static_methods, method);
+                }
+
+                // accessors are typed like fields
+                for getter in &class_decl.getters {
+                    let type_ = self.function_type(getter).ret;
+                    info.fields.insert(getter.name.unwrap().name, type_);
+                }
+
+                for setter in &class_decl.setters {
+                    let type_ = self.function_type(setter).params.remove(0);
+                    info.fields
+                        .entry(setter.name.unwrap().name)
+                        .or_insert(type_);
+                }
+
+                for field in &class_decl.fields {
+                    info.fields.insert(
+                        field.identifier.name,
+                        field.type_.clone().unwrap_or(Type::Any),
+                    );
+                }
+
+                self.classes.insert(class_decl.identifier.name, info);
+
+                for method in &class_decl.methods {
+                    for stmt in &method.body {
+                        self.collect_classes(stmt);
+                    }
+                }
+            }
+            Stmt::EnumDecl(enum_decl) => {
+                self.classes.entry(enum_decl.identifier.name).or_default();
+            }
+            Stmt::Block(block) => {
+                for stmt in &block.stmts {
+                    self.collect_classes(stmt);
+                }
+            }
+            Stmt::If(if_stmt) => {
+                self.collect_classes(&if_stmt.then_branch);
+                if let Some(else_branch) = &if_stmt.else_branch {
+                    self.collect_classes(else_branch);
+                }
+            }
+            Stmt::While(while_stmt) => self.collect_classes(&while_stmt.body),
+            Stmt::Expr(expr_stmt) => {
+                if let Expr::Func(func) = &expr_stmt.expr.expr {
+                    for stmt in &func.body {
+                        self.collect_classes(stmt);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn collect_method(&self, methods: &mut FnvHashMap<IdentifierHandle, Type>, method: &FuncExpr) {
+        if let Some(name) = method.name {
+            // overloads are only resolved at runtime
+            let type_ = if methods.contains_key(&name.name) {
+                Type::Any
+            } else {
+                Type::Function(Rc::new(self.function_type(method)))
+            };
+
+            methods.insert(name.name, type_);
+        }
+    }
+
+    fn function_type(&self, func: &FuncExpr) -> FunctionType {
+        let params = func.params.as_deref().unwrap_or(&[]);
+
+        FunctionType {
+            params: (0..params.len())
+                .map(|i| {
+                    func.param_types
+                        .get(i)
+                        .cloned()
+                        .flatten()
+                        .unwrap_or(Type::Any)
+                })
+                .collect(),
+            required: params
+                .iter()
+                .filter(|p| matches!(p, FuncParam::Required(_)))
+                .count(),
+            rest: matches!(params.last(), Some(FuncParam::Rest(_))),
+            ret: func.return_type.clone().unwrap_or(Type::Any),
+        }
+    }
+
+    // reports names in annotations which are neither classes nor enums
+    fn check_type(&mut self, type_: &Type, pos: Position) {
+        match type_ {
+            Type::Named(name, args) => {
+                if !self.classes.contains_key(name) {
+                    self.report(TypeDiagnostic::UnknownType(pos, self.name(*name)));
+                }
+
+                for arg in args {
+                    self.check_type(arg, pos);
+                }
+            }
+            Type::Function(func) => {
+                for param in &func.params {
+                    self.check_type(param, pos);
+                }
+                self.check_type(&func.ret, pos);
+            }
+            Type::Nullable(type_) => self.check_type(type_, pos),
+            _ => {}
+        }
+    }
+
+    fn is_subclass(&self, class: IdentifierHandle, of: IdentifierHandle) -> bool {
+        let mut current = Some(class);
+
+        while let Some(name) = current {
+            if name == of {
+                return true;
+            }
+
+            current = self.classes.get(&name).and_then(|info| info.superclass);
+        }
+
+        false
+    }
+
+    fn is_assignable(&self, from: &Type, to: &Type) -> bool {
+        match (from, to) {
+            (Type::Any, _) | (_, Type::Any) => true,
+            (Type::Nil, Type::Nullable(_)) => true,
+            (Type::Nullable(from), Type::Nullable(to)) => self.is_assignable(from, to),
+            (_, Type::Nullable(to)) => self.is_assignable(from, to),
+            (Type::Named(from, from_args), Type::Named(to, to_args)) => {
+                self.is_subclass(*from, *to)
+                    && (from_args.is_empty()
+                        || to_args.is_empty()
+                        || from_args
+                            .iter()
+                            .zip(to_args)
+                            .all(|(from, to)| self.is_assignable(from, to)))
+            }
+            (Type::Function(from), Type::Function(to)) => {
+                self.is_assignable(&from.ret, &to.ret)
+                    && from
+                        .params
+                        .iter()
+                        .zip(&to.params)
+                        .all(|(from, to)| self.is_assignable(to, from))
+            }
+            (from, to) => from == to,
+        }
+    }
+
+    fn expect_assignable(&mut self, from: &Type, to: &Type, pos: Position) {
+        if !self.is_assignable(from, to) {
+            self.report(TypeDiagnostic::TypeMismatch(
+                pos,
+                self.type_name(to),
+                self.type_name(from),
+            ));
+        }
+    }
+
+    fn expect_number(&mut self, type_: &Type, op: String, pos: Position) {
+        if !self.is_assignable(type_, &Type::Number) {
+            self.report(TypeDiagnostic::OperatorExpectsNumbers(
+                pos,
+                op,
+                self.type_name(type_),
+            ));
+        }
+    }
+
+    fn check_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Expr(expr_stmt) => {
+                self.check_expr(&expr_stmt.expr);
+            }
+            Stmt::Print(print_stmt) => {
+                self.check_expr(&print_stmt.value);
+            }
+            Stmt::VarDecl(decl) => {
+                let declared = decl.type_.clone().unwrap_or(Type::Any);
+                self.check_type(&declared, decl.identifier.pos);
+
+                if let Some(init) = &decl.initializer {
+                    let type_ = self.check_expr_expecting(init, &declared);
+                    self.expect_assignable(&type_, &declared, init.pos);
+                }
+
+                self.define(decl.identifier.name, declared);
+            }
+            Stmt::DestructuringDecl(decl) => {
+                let type_ = self.check_expr(&decl.initializer);
+
+                match &decl.target {
+                    Destructuring::Array(names, rest) => {
+                        let elements = element_type(&type_);
+
+                        for identifier in names {
+                            self.define(identifier.name, elements.clone());
+                        }
+
+                        if let Some(rest) = rest {
+                            self.define(rest.name, Type::array(elements));
+                        }
+                    }
+                    Destructuring::Object(fields) => {
+                        for field in fields {
+                            let field_type = match &type_ {
+                                Type::Named(class, args) => {
+                                    match self.property_type(*class, args, field.name) {
+                                        Some(field_type) => field_type,
+                                        None => {
+                                            self.report(TypeDiagnostic::UnknownProperty(
+                                                field.pos,
+                                                self.name(field.name),
+                                                self.type_name(&type_),
+                                            ));
+                                            Type::Any
+                                        }
+                                    }
+                                }
+                                _ => Type::Any,
+                            };
+
+                            self.define(field.name, field_type);
+                        }
+                    }
+                }
+            }
+            Stmt::Block(block) => {
+                self.begin_scope();
+                for stmt in &block.stmts {
+                    self.check_stmt(stmt);
+                }
+                self.end_scope();
+            }
+            Stmt::If(if_stmt) => {
+                self.check_expr(&if_stmt.condition);
+                self.check_stmt(&if_stmt.then_branch);
+                if let Some(else_branch) = &if_stmt.else_branch {
+                    self.check_stmt(else_branch);
+                }
+            }
+            Stmt::While(while_stmt) => {
+                self.check_expr(&while_stmt.condition);
+                self.check_stmt(&while_stmt.body);
+            }
+            Stmt::Return(ret_stmt) => {
+                let type_ = match &ret_stmt.value {
+                    Some(value) => self.check_expr(value),
+                    None => Type::Nil,
+                };
+
+                if let Some(expected) = self.return_types.last().cloned() {
+                    self.expect_assignable(&type_, &expected, ret_stmt.pos);
+                }
+            }
+            Stmt::ClassDecl(class_decl) => {
+                let name = class_decl.identifier.name;
+                self.define(name, Type::Class(name));
+
+                self.this_types.push(Type::Named(name, vec![]));
+
+                for field in &class_decl.fields {
+                    let declared = field.type_.clone().unwrap_or(Type::Any);
+                    self.check_type(&declared, field.identifier.pos);
+
+                    if let Some(initializer) = &field.initializer {
+                        let type_ = self.check_expr_expecting(initializer, &declared);
+                        self.expect_assignable(&type_, &declared, initializer.pos);
+                    }
+                }
+
+                for method in &class_decl.methods {
+                    let is_init = method.name.map(|n| n.name) == Some(Identifier::init());
+                    self.check_function(method, is_init);
+                }
+
+                for accessor in class_decl.getters.iter().chain(&class_decl.setters) {
+                    self.check_function(accessor, false);
+                }
+
+                self.this_types.pop();
+                self.this_types.push(Type::Class(name));
+
+                for method in &class_decl.static_methods {
+                    self.check_function(method, false);
+                }
+
+                self.this_types.pop();
+            }
+            Stmt::EnumDecl(enum_decl) => {
+                let name = enum_decl.identifier.name;
+                self.declare_enum(name);
+
+                for variant in &enum_decl.variants {
+                    let fields = variant
+                        .fields
+                        .as_ref()
+                        .map(|fields| fields.iter().map(|f| f.name).collect());
+                    self.declare_variant(name, variant.identifier.name, fields);
+                }
+            }
+        }
+    }
+
+    fn check_function(&mut self, func: &FuncExpr, is_init: bool) -> Type {
+        let type_ = Rc::new(self.function_type(func));
+
+        for param_type in func.param_types.iter().flatten() {
+            self.check_type(param_type, func.pos);
+        }
+
+        if let Some(return_type) = &func.return_type {
+            self.check_type(return_type, func.pos);
+        }
+
+        self.begin_scope();
+
+        if let Some(params) = &func.params {
+            for (param, param_type) in params.iter().zip(&type_.params) {
+                if let FuncParam::DefaultValued(_, default) = param {
+                    let default_type = self.check_expr(default);
+                    self.expect_assignable(&default_type, param_type, default.pos);
+                }
+
+                self.define(param.identifier().name, param_type.clone());
+            }
+        }
+
+        // initializers implicitly return 'this'
+        self.return_types.push(if is_init {
+            Type::Any
+        } else {
+            type_.ret.clone()
+        });
+
+        for stmt in &func.body {
+            self.check_stmt(stmt);
+        }
+
+        self.return_types.pop();
+        self.end_scope();
+
+        Type::Function(type_)
+    }
+
+    fn check_args(&mut self, callee: String, func: &FunctionType, args: &[ExprCtx], pos: Position) {
+        // the number of spread arguments is only known at runtime
+        if args.iter().any(|arg| matches!(arg.expr, Expr::Spread(_))) {
+            for arg in args {
+                self.check_expr(arg);
+            }
+            return;
+        }
+
+        let types = args
+            .iter()
+            .map(|arg| self.check_expr(arg))
+            .collect::<Vec<_>>();
+        let fixed = if func.rest {
+            func.params.len() - 1
+        } else {
+            func.params.len()
+        };
+
+        if func.rest && types.len() < func.required {
+            self.report(TypeDiagnostic::WrongNumberOfArgsBetween(
+                pos,
+                callee,
+                func.required,
+                None,
+                types.len(),
+            ));
+            return;
+        }
+
+        if !func.rest && (types.len() < func.required || types.len() > fixed) {
+            self.report(if func.required == fixed {
+                TypeDiagnostic::WrongNumberOfArgs(pos, callee, fixed, types.len())
+            } else {
+                TypeDiagnostic::WrongNumberOfArgsBetween(
+                    pos,
+                    callee,
+                    func.required,
+                    Some(fixed),
+                    types.len(),
+                )
+            });
+            return;
+        }
+
+        // a rest parameter annotated with Array<T> accepts values of type T
+        let rest_type = match func.params.last() {
+            Some(Type::Named(array, args)) if func.rest && *array == Identifier::array() => {
+                args.first().cloned().unwrap_or(Type::Any)
+            }
+            _ => Type::Any,
+        };
+
+        for (i, (type_, arg)) in types.iter().zip(args).enumerate() {
+            let expected = if i < fixed {
+                func.params[i].clone()
+            } else {
+                rest_type.clone()
+            };
+            self.expect_assignable(type_, &expected, arg.pos);
+        }
+    }
+
+    // arr[a..b] is a slice of the array rather than one of its elements,
+    // an untyped index could be either
+    fn check_index(&mut self, get: &FunctionType, index: &ExprCtx) -> Type {
+        let range = self.handle("Range");
+
+        match self.check_expr(index) {
+            Type::Named(class, _) if Some(class) == range => {
+                Type::Named(Identifier::array(), vec![get.ret.clone()])
+            }
+            Type::Any => Type::Any,
+            type_ => {
+                self.expect_assignable(&type_, &get.params[0], index.pos);
+                get.ret.clone()
+            }
+        }
+    }
+
+    // the type of a property on an instance of class, None if the class has no such property
+    fn property_type(
+        &self,
+        class: IdentifierHandle,
+        args: &[Type],
+        property: IdentifierHandle,
+    ) -> Option<Type> {
+        if class == Identifier::array() {
+            let elements = args.first().cloned().unwrap_or(Type::Any);
+            let array = Type::array(elements.clone());
+
+            // the methods adding or changing elements only accept the element type
+            return Some(if property == Identifier::get() {
+                Type::function(vec![Type::Number], elements)
+            } else if property == Identifier::set() {
+                Type::function(vec![Type::Number, elements], Type::Any)
+            } else if Some(property) == self.handle("push") {
+                Type::Function(Rc::new(FunctionType {
+                    params: vec![array.clone()],
+                    required: 0,
+                    rest: true,
+                    ret: array,
+                }))
+            } else if Some(property) == self.handle("insert") {
+                Type::function(vec![Type::Number, elements], array)
+            } else if Some(property) == self.handle("length") {
+                Type::function(vec![], Type::Number)
+            } else {
+                Type::Any
+            });
+        }
+
+        let mut current = Some(class);
+
+        while let Some(name) = current {
+            let info = match self.classes.get(&name) {
+                Some(info) => info,
+                None => return Some(Type::Any),
+            };
+
+            if let Some(type_) = info.fields.get(&property) {
+                return Some(type_.clone());
+            }
+
+            if let Some(method) = info.methods.get(&property) {
+                return Some(method.clone());
+            }
+
+            if info.open {
+                return Some(Type::Any);
+            }
+
+            current = info.superclass;
+        }
+
+        None
+    }
+
+    fn static_method_type(
+        &self,
+        class: IdentifierHandle,
+        method: IdentifierHandle,
+    ) -> Option<Type> {
+        let mut current = Some(class);
+
+        while let Some(name) = current {
+            let info = match self.classes.get(&name) {
+                Some(info) => info,
+                None => return Some(Type::Any),
+            };
+
+            if let Some(type_) = info.static_methods.get(&method) {
+                return Some(type_.clone());
+            }
+
+            if info.open {
+                return Some(Type::Any);
+            }
+
+            current = info.superclass;
+        }
+
+        None
+    }
+
+    fn constructor_type(&self, class: IdentifierHandle) -> Option<Rc<FunctionType>> {
+        let mut current = Some(class);
+
+        while let Some(name) = current {
+            let info = self.classes.get(&name)?;
+
+            match info.methods.get(&Identifier::init()) {
+                Some(Type::Function(init)) => return Some(Rc::clone(init)),
+                Some(_) => return None,
+                None => {}
+            }
+
+            if info.open {
+                return None;
+            }
+
+            current = info.superclass;
+        }
+
+        Some(Rc::new(FunctionType {
+            params: vec![],
+            required: 0,
+            rest: false,
+            ret: Type::Nil,
+        }))
+    }
+
+    fn join(&self, a: Type, b: Type) -> Type {
+        match (a, b) {
+            (a, b) if a == b => a,
+            (Type::Nil, Type::Nullable(t)) | (Type::Nullable(t), Type::Nil) => Type::Nullable(t),
+            (Type::Nil, t) | (t, Type::Nil) => Type::Nullable(Box::new(t)),
+            _ => Type::Any,
+        }
+    }
+
+    // a mixed array literal joins to Array<any>, which is assignable to any array,
+    // so its elements are checked one by one against the expected element type
+    fn check_expr_expecting(&mut self, expr: &ExprCtx, expected: &Type) -> Type {
+        match (&expr.expr, expected) {
+            (Expr::ArrayDeclExpr(array), Type::Named(name, args))
+                if *name == Identifier::array() && !args.is_empty() =>
+            {
+                self.check_array(&array.values, Some(&args[0]))
+            }
+            _ => self.check_expr(expr),
+        }
+    }
+
+    fn check_array(&mut self, values: &[ExprCtx], expected: Option<&Type>) -> Type {
+        let mut elements: Option<Type> = None;
+        let mut types = Vec::with_capacity(values.len());
+
+        for value in values {
+            let type_ = match (&value.expr, expected) {
+                (Expr::Spread(_), _) => element_type(&self.check_expr(value)),
+                (_, Some(expected)) => self.check_expr_expecting(value, expected),
+                _ => self.check_expr(value),
+            };
+
+            types.push((type_.clone(), value.pos));
+            elements = Some(match elements {
+                Some(elements) => self.join(elements, type_),
+                None => type_,
+            });
+        }
+
+        if let (Some(expected), Some(Type::Any)) = (expected, &elements) {
+            for (type_, pos) in types {
+                self.expect_assignable(&type_, expected, pos);
+            }
+        }
+
+        match elements {
+            Some(elements) => Type::array(elements),
+            None => Type::Named(Identifier::array(), vec![]),
+        }
+    }
+
+    fn check_expr(&mut self, expr: &ExprCtx) -> Type {
+        match &expr.expr {
+            Expr::Literal(literal) => match literal {
+                Literal::Nil => Type::Nil,
+                Literal::Number(_) => Type::Number,
+                Literal::String(_) => Type::String,
+                Literal::Boolean(_) => Type::Boolean,
+            },
+            Expr::Var(var) => self.lookup(var.identifier.name),
+            Expr::Assign(assign) => {
+                let declared = self.lookup(assign.identifier.name);
+                let type_ = self.check_expr_expecting(&assign.expr, &declared);
+                self.expect_assignable(&type_, &declared, assign.expr.pos);
+                type_
+            }
+            Expr::Grouping(group) => self.check_expr(&group.expression),
+            Expr::Unary(unary) => {
+                let type_ = self.check_expr(&unary.right);
+
+                match unary.operator {
+                    UnaryOperator::Bang => Type::Boolean,
+                    UnaryOperator::Minus | UnaryOperator::Tilde => {
+                        self.expect_number(&type_, format!("{}", unary.operator), expr.pos);
+                        Type::Number
+                    }
+                }
+            }
+            Expr::Binary(binary) => {
+                let left = self.check_expr(&binary.left);
+                let right = self.check_expr(&binary.right);
+                let op = &binary.operator;
+
+                match op.op {
+                    BinaryOperator::EqualEqual | BinaryOperator::BangEqual | BinaryOperator::Is => {
+                        Type::Boolean
+                    }
+                    // anything other than two numbers is concatenated
+                    BinaryOperator::Plus => match (left, right) {
+                        (Type::Number, Type::Number) => Type::Number,
+                        (Type::Any, _) | (_, Type::Any) => Type::Any,
+                        _ => Type::String,
+                    },
+                    BinaryOperator::Greater
+                    | BinaryOperator::GreaterEqual
+                    | BinaryOperator::Less
+                    | BinaryOperator::LessEqual => {
+                        self.expect_number(&left, format!("{}", op.op), op.pos);
+                        self.expect_number(&right, format!("{}", op.op), op.pos);
+                        Type::Boolean
+                    }
+                    _ => {
+                        self.expect_number(&left, format!("{}", op.op), op.pos);
+                        self.expect_number(&right, format!("{}", op.op), op.pos);
+                        Type::Number
+                    }
+                }
+            }
+            Expr::Logical(logical) => {
+                let left = self.check_expr(&logical.left);
+                let right = self.check_expr(&logical.right);
+
+                match logical.operator {
+                    LogicalOperator::NilCoalescing => match left {
+                        Type::Nullable(left) => self.join(*left, right),
+                        Type::Nil => right,
+                        left => self.join(left, right),
+                    },
+                    _ => self.join(left, right),
+                }
+            }
+            Expr::Conditional(cond) => {
+                self.check_expr(&cond.condition);
+                let then_type = self.check_expr(&cond.then_branch);
+                let else_type = self.check_expr(&cond.else_branch);
+                self.join(then_type, else_type)
+            }
+            Expr::Call(call) => {
+                // obj?.method() is not called on a nil object
+                let (callee, optional) = match (self.check_expr(&call.callee), &call.callee.expr) {
+                    (Type::Nullable(callee), Expr::Get(get)) if get.optional => (*callee, true),
+                    (callee, _) => (callee, false),
+                };
+
+                let callee_name = match &call.callee.expr {
+                    Expr::Var(var) => self.name(var.identifier.name),
+                    Expr::Get(get) => self.name(get.property.name),
+                    _ => self.name(Identifier::anonymous()),
+                };
+
+                let type_ = match callee {
+                    Type::Function(func) => match (&call.callee.expr, &call.args[..]) {
+                        (Expr::Get(get), [index])
+                            if get.property.name == Identifier::get() && func.params.len() == 1 =>
+                        {
+                            self.check_index(&func, index)
+                        }
+                        _ => {
+                            self.check_args(callee_name, &func, &call.args, expr.pos);
+                            func.ret.clone()
+                        }
+                    },
+                    Type::Class(class) => {
+                        match self.constructor_type(class) {
+                            Some(init) => self.check_args(callee_name, &init, &call.args, expr.pos),
+                            None => {
+                                for arg in &call.args {
+                                    self.check_expr(arg);
+                                }
+                            }
+                        }
+
+                        Type::Named(class, vec![])
+                    }
+                    Type::Any => {
+                        for arg in &call.args {
+                            self.check_expr(arg);
+                        }
+                        Type::Any
+                    }
+                    type_ => {
+                        for arg in &call.args {
+                            self.check_expr(arg);
+                        }
+                        self.report(TypeDiagnostic::NotCallable(
+                            expr.pos,
+                            self.type_name(&type_),
+                        ));
+                        Type::Any
+                    }
+                };
+
+                if optional {
+                    self.join(type_, Type::Nil)
+                } else {
+                    type_
+                }
+            }
+            Expr::Func(func) => {
+                // defined before the body for recursive calls
+                if let Some(name) = func.name {
+                    // overloads are only resolved at runtime
+                    let type_ = if self.scopes.last().unwrap().contains_key(&name.name) {
+                        Type::Any
+                    } else {
+                        Type::Function(Rc::new(self.function_type(func)))
+                    };
+
+                    self.define(name.name, type_);
+                }
+
+                self.check_function(func, false)
+            }
+            Expr::Get(get) => {
+                let object = self.check_expr(&get.object);
+
+                let (object, nullable) = match object {
+                    Type::Nullable(object) if get.optional => (*object, true),
+                    object => (object, false),
+                };
+
+                let type_ = match &object {
+                    Type::Named(class, args) => {
+                        match self.property_type(*class, args, get.property.name) {
+                            Some(type_) => type_,
+                            None => {
+                                self.report(TypeDiagnostic::UnknownProperty(
+                                    get.property.pos,
+                                    self.name(get.property.name),
+                                    self.type_name(&object),
+                                ));
+                                Type::Any
+                            }
+                        }
+                    }
+                    Type::Class(class) => {
+                        match self.static_method_type(*class, get.property.name) {
+                            Some(type_) => type_,
+                            None => {
+                                self.report(TypeDiagnostic::UnknownProperty(
+                                    get.property.pos,
+                                    self.name(get.property.name),
+                                    self.type_name(&object),
+                                ));
+                                Type::Any
+                            }
+                        }
+                    }
+                    _ => Type::Any,
+                };
+
+                if nullable {
+                    self.join(type_, Type::Nil)
+                } else {
+                    type_
+                }
+            }
+            Expr::Set(set) => {
+                let object = self.check_expr(&set.object);
+                let value = self.check_expr(&set.value);
+
+                if let Type::Named(class, args) = &object {
+                    match self.property_type(*class, args, set.property.name) {
+                        Some(type_) => self.expect_assignable(&value, &type_, set.value.pos),
+                        None => self.report(TypeDiagnostic::UnknownProperty(
+                            set.property.pos,
+                            self.name(set.property.name),
+                            self.type_name(&object),
+                        )),
+                    }
+                }
+
+                value
+            }
+            Expr::This(_) => self.this_types.last().cloned().unwrap_or(Type::Any),
+            Expr::Super(_) => Type::Any,
+            Expr::ArrayDeclExpr(array) => self.check_array(&array.values, None),
+            Expr::Spread(spread) => self.check_expr(&spread.value),
+            Expr::Match(match_expr) => {
+                self.check_expr(&match_expr.value);
+                let mut result: Option<Type> = None;
+
+                for arm in &match_expr.arms {
+                    self.begin_scope();
+                    self.define_pattern_bindings(&arm.pattern);
+
+                    if let Some(guard) = &arm.guard {
+                        self.check_expr(guard);
+                    }
+
+                    let type_ = match &arm.body {
+                        MatchArmBody::Expr(expr) => self.check_expr(expr),
+                        MatchArmBody::Block(block) => {
+                            self.begin_scope();
+                            for stmt in &block.stmts {
+                                self.check_stmt(stmt);
+                            }
+                            self.end_scope();
+                            Type::Nil
+                        }
+                    };
+
+                    self.end_scope();
+
+                    result = Some(match result {
+                        Some(result) => self.join(result, type_),
+                        None => type_,
+                    });
+                }
+
+                result.unwrap_or(Type::Nil)
+            }
+        }
+    }
+
+    fn define_pattern_bindings(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Binding(identifier) => self.define(identifier.name, Type::Any),
+            Pattern::Variant(_, patterns) => {
+                for pattern in patterns {
+                    self.define_pattern_bindings(pattern);
+                }
+            }
+            Pattern::Instance(_, fields) => {
+                for (_, pattern) in fields {
+                    self.define_pattern_bindings(pattern);
+                }
+            }
+            Pattern::Array(patterns, rest) => {
+                for pattern in patterns {
+                    self.define_pattern_bindings(pattern);
+                }
+                if let Some(rest) = rest {
+                    self.define_pattern_bindings(rest);
+                }
+            }
+            Pattern::Wildcard | Pattern::Literal(_) | Pattern::Constant(_) => {}
+        }
+    }
+}
+
+// fields created by 'this.field = value' in the methods of a class
+// the type of the elements of an array, any for other types
+fn element_type(type_: &Type) -> Type {
+    match type_ {
+        Type::Named(array, args) if *array == Identifier::array() => {
+            args.first().cloned().unwrap_or(Type::Any)
+        }
+        _ => Type::Any,
+    }
+}
+
+fn collect_this_assignments(stmts: &[Stmt], fields: &mut FnvHashMap<IdentifierHandle, Type>) {
+    fn expr(expr_ctx: &ExprCtx, fields: &mut FnvHashMap<IdentifierHandle, Type>) {
+        match &expr_ctx.expr {
+            Expr::Set(set) => {
+                if let Expr::This(_) = set.object.expr {
+                    fields.entry(set.property.name).or_insert(Type::Any);
+                }
+                expr(&set.object, fields);
+                expr(&set.value, fields);
+            }
+            Expr::Assign(assign) => expr(&assign.expr, fields),
+            Expr::Grouping(group) => expr(&group.expression, fields),
+            Expr::Unary(unary) => expr(&unary.right, fields),
+            Expr::Binary(binary) => {
+                expr(&binary.left, fields);
+                expr(&binary.right, fields);
+            }
+            Expr::Logical(logical) => {
+                expr(&logical.left, fields);
+                expr(&logical.right, fields);
+            }
+            Expr::Conditional(cond) => {
+                expr(&cond.condition, fields);
+                expr(&cond.then_branch, fields);
+                expr(&cond.else_branch, fields);
+            }
+            Expr::Call(call) => {
+                expr(&call.callee, fields);
+                for arg in &call.args {
+                    expr(arg, fields);
+                }
+            }
+            Expr::Func(func) => collect_this_assignments(&func.body, fields),
+            Expr::Get(get) => expr(&get.object, fields),
+            Expr::ArrayDeclExpr(array) => {
+                for value in &array.values {
+                    expr(value, fields);
+                }
+            }
+            Expr::Match(match_expr) => {
+                expr(&match_expr.value, fields);
+                for arm in &match_expr.arms {
+                    match &arm.body {
+                        MatchArmBody::Expr(body) => expr(body, fields),
+                        MatchArmBody::Block(block) => {
+                            collect_this_assignments(&block.stmts, fields)
+                        }
+                    }
+                }
+            }
+            Expr::Spread(spread) => expr(&spread.value, fields),
+            Expr::Literal(_) | Expr::Var(_) | Expr::This(_) | Expr::Super(_) => {}
+        }
+    }
+
+    for stmt in stmts {
+        match stmt {
+            Stmt::Expr(expr_stmt) => expr(&expr_stmt.expr, fields),
+            Stmt::Print(print_stmt) => expr(&print_stmt.value, fields),
+            Stmt::VarDecl(decl) => {
+                if let Some(init) = &decl.initializer {
+                    expr(init, fields);
+                }
+            }
+            Stmt::DestructuringDecl(decl) => expr(&decl.initializer, fields),
+            Stmt::Block(block) => collect_this_assignments(&block.stmts, fields),
+            Stmt::If(if_stmt) => {
+                expr(&if_stmt.condition, fields);
+                collect_this_assignments(std::slice::from_ref(&if_stmt.then_branch), fields);
+                if let Some(else_branch) = &if_stmt.else_branch {
+                    collect_this_assignments(std::slice::from_ref(else_branch), fields);
+                }
+            }
+            Stmt::While(while_stmt) => {
+                expr(&while_stmt.condition, fields);
+                collect_this_assignments(std::slice::from_ref(&while_stmt.body), fields);
+            }
+            Stmt::Return(ret_stmt) => {
+                if let Some(value) = &ret_stmt.value {
+                    expr(value, fields);
+                }
+            }
+            Stmt::ClassDecl(_) | Stmt::EnumDecl(_) => {}
+        }
+    }
+}
+
+pub enum TypeDiagnostic {
+    TypeMismatch(Position, String, String),
+    UnknownType(Position, String),
+    UnknownProperty(Position, String, String),
+    WrongNumberOfArgs(Position, String, usize, usize),
+    WrongNumberOfArgsBetween(Position, String, usize, Option<usize>, usize),
+    OperatorExpectsNumbers(Position, String, String),
+    NotCallable(Position, String),
+}
+
+impl fmt::Display for TypeDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use TypeDiagnostic::*;
+        match self {
+            TypeMismatch(_, expected, found) => write!(
+                f,
+                "Type mismatch: expected '{}', found '{}'",
+                expected, found
+            ),
+            UnknownType(_, name) => write!(f, "Unknown type '{}'", name),
+            UnknownProperty(_, property, type_) => {
+                write!(f, "Unknown property '{}' on type '{}'", property, type_)
+            }
+            WrongNumberOfArgs(_, name, expected, got) => {
+                let s = if *expected != 1 { "s" } else { "" };
+                write!(
+                    f,
+                    "'{}' expected {} argument{}, got {}",
+                    name, expected, s, got
+                )
+            }
+            WrongNumberOfArgsBetween(_, name, min, max, got) => {
+                let max = match max {
+                    Some(max) => format!("{}", max),
+                    None => "infinity".into(),
+                };
+                write!(
+                    f,
+                    "'{}' expected between {} and {} arguments, got {}",
+                    name, min, max, got
+                )
+            }
+            OperatorExpectsNumbers(_, op, found) => {
+                write!(f, "Operator '{}' expects numbers, found '{}'", op, found)
+            }
+            NotCallable(_, type_) => write!(f, "Value of type '{}' is not callable", type_),
+        }
+    }
+}
+
+impl ErrorPosition for TypeDiagnostic {
+    fn position(&self) -> &Position {
+        use TypeDiagnostic::*;
+        match self {
+            TypeMismatch(pos, _, _)
+            | UnknownType(pos, _)
+            | UnknownProperty(pos, _, _)
+            | WrongNumberOfArgs(pos, _, _, _)
+            | WrongNumberOfArgsBetween(pos, _, _, _, _)
+            | OperatorExpectsNumbers(pos, _, _)
+            | NotCallable(pos, _) => pos,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{IdentifierHandlesGenerator, Parser};
+    use crate::scanner::Scanner;
+
+    fn check(source: &str) -> Vec<String> {
+        let mut identifiers = IdentifierHandlesGenerator::new();
+        crate::interpreter::environment::Environment::with_natives(None, &mut identifiers);
+        let scanner = Scanner::new(source.chars().peekable());
+        let mut parser = Parser::new(scanner.peekable(), &mut identifiers);
+        let ast = parser
+            .parse()
+            .unwrap_or_else(|_| panic!("could not parse the source"));
+        let names = Rc::new(parser.names());
+
+        TypeChecker::new(&names)
+            .check(&ast)
+            .iter()
+            .map(|diagnostic| format!("{}", diagnostic))
+            .collect()
+    }
+
+    #[test]
+    fn variables() {
+        assert!(check("var a: number = 1; var b = a + 2; var c: string? = nil;").is_empty());
+        assert_eq!(
+            check("var a: number = \"a\";"),
+            vec!["Type mismatch: expected 'number', found 'string'"]
+        );
+        assert_eq!(check("var a: Foo;"), vec!["Unknown type 'Foo'"]);
+    }
+
+    #[test]
+    fn functions() {
+        let source = "fun add(a: number, b: number = 1): number { return a + b; }";
+        assert!(check(source).is_empty());
+        assert_eq!(
+            check(&format!("{} add(\"a\");", source)),
+            vec!["Type mismatch: expected 'number', found 'string'"]
+        );
+        assert_eq!(
+            check(&format!("{} add();", source)),
+            vec!["'add' expected between 1 and 2 arguments, got 0"]
+        );
+        assert_eq!(
+            check("fun f(): string { return 1; }"),
+            vec!["Type mismatch: expected 'string', found 'number'"]
+        );
+    }
+
+    #[test]
+    fn classes() {
+        let source = "class Point { x: number; init(x) { this.x = x; this.y = 0; } }";
+        assert!(check(&format!("{} var p: Point = Point(1); p.x = p.y;", source)).is_empty());
+        assert_eq!(
+            check(&format!("{} var p: Point = Point(1); p.z;", source)),
+            vec!["Unknown property 'z' on type 'Point'"]
+        );
+        assert_eq!(
+            check(&format!("{} var p: Point = Point(1); p.x = \"a\";", source)),
+            vec!["Type mismatch: expected 'number', found 'string'"]
+        );
+    }
+
+    #[test]
+    fn overloads() {
+        let source = "fun f(a: number) {} fun f(a: string, b: string) {}";
+        assert!(check(&format!("{} f(1); f(\"a\", \"b\");", source)).is_empty());
+    }
+
+    #[test]
+    fn class_members() {
+        let source =
+            "class Counter { count: number = 0; static zero(): Counter { return Counter(); } \
+                      get double(): number { return this.count * 2; } }";
+        assert!(check(&format!(
+            "{} var c: Counter = Counter.zero(); var d: number = c.double;",
+            source
+        ))
+        .is_empty());
+        assert_eq!(
+            check(&format!("{} Counter.one();", source)),
+            vec!["Unknown property 'one' on type 'class Counter'"]
+        );
+    }
+
+    #[test]
+    fn arrays() {
+        assert!(
+            check("var a: Array<Array<number>> = [[1], [2, 3]]; var n: number = a[0][1];")
+                .is_empty()
+        );
+        assert_eq!(
+            check("var a: Array<number> = [\"a\"];"),
+            vec!["Type mismatch: expected 'Array<number>', found 'Array<string>'"]
+        );
+    }
+
+    #[test]
+    fn array_elements() {
+        let source = "var a: Array<number> = [1];";
+        assert!(check(&format!(
+            "{} a.push(2, 3); a.insert(0, 0); a[1] = 4;",
+            source
+        ))
+        .is_empty());
+        assert_eq!(
+            check(&format!(
+                "{} a.push(2, \"b\"); a.insert(0, nil); a[1] = \"c\";",
+                source
+            )),
+            vec![
+                "Type mismatch: expected 'number', found 'string'",
+                "Type mismatch: expected 'number', found 'nil'",
+                "Type mismatch: expected 'number', found 'string'"
+            ]
+        );
+        assert_eq!(
+            check("var b: Array<number> = [1, \"x\", ...[2]]; b = [2, true];"),
+            vec![
+                "Type mismatch: expected 'number', found 'string'",
+                "Type mismatch: expected 'number', found 'bool'"
+            ]
+        );
+    }
+
+    #[test]
+    fn ranges() {
+        assert!(
+            check("var a = [1, 2]; var r: Range = 0..=1; var s: Array<number> = a[0..1];")
+                .is_empty()
+        );
+        assert_eq!(
+            check("var a: Array<number> = [1]; var n: number = a[0..1];"),
+            vec!["Type mismatch: expected 'number', found 'Array<number>'"]
+        );
+    }
+
+    #[test]
+    fn destructuring() {
+        assert!(
+            check("var [a, ...b] = [1, 2]; var n: number = a; var m: Array<number> = [...b];")
+                .is_empty()
+        );
+        assert_eq!(
+            check("class P { x: number; } var {x} = P(); var s: string = x;"),
+            vec!["Type mismatch: expected 'string', found 'number'"]
+        );
+        assert_eq!(
+            check("class P { x: number; } var {y} = P();"),
+            vec!["Unknown property 'y' on type 'P'"]
+        );
+    }
+}
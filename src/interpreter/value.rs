@@ -78,6 +78,22 @@ impl Value {
 }
 
 impl CallableValue {
+    // picks the overload of a function or of a class initializer accepting arg_count arguments
+    pub fn select_overload(self, arg_count: usize) -> CallableValue {
+        match self {
+            CallableValue::Function(func) => {
+                CallableValue::Function(func.select_overload(arg_count))
+            }
+            CallableValue::Class(class) => match class.find_method(Identifier::init()) {
+                Some(init) if init.overload.is_some() => CallableValue::Class(Rc::new(
+                    class.with_initializer(init.select_overload(arg_count)),
+                )),
+                _ => CallableValue::Class(class),
+            },
+            native => native,
+        }
+    }
+
     pub fn into_callable(self) -> Rc<LoxCallable> {
         match self {
             CallableValue::Class(c) => c,
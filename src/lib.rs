@@ -2,14 +2,17 @@ extern crate wasm_bindgen;
 
 pub mod interpreter;
 pub mod parser;
+pub mod resolver;
 pub mod runner;
 pub mod scanner;
 pub mod vm;
 
-use crate::interpreter::host::Host;
+use crate::interpreter::host::{FileSystem, Host};
 use runner::{interp::EloxInterpreter, EloxRunner};
-use crate::runner::EloxResult;
+use crate::interpreter::eval_result::EvalError;
+use crate::runner::{EloxError, EloxResult};
 use std::rc::Rc;
+use vm::EloxVM;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen(raw_module = "../web/index.js")]
@@ -19,9 +22,9 @@ extern "C" {
     pub fn clock() -> f64;
 }
 
-#[wasm_bindgen]
-pub fn run(source: &str) -> EloxResult {
-    let host = Host {
+// routes the output, errors and clock of a script to the playground's JS callbacks
+fn web_host() -> Host {
+    Host {
         print: Rc::new(|_, msg| {
             log(msg);
             Ok(())
@@ -30,12 +33,58 @@ pub fn run(source: &str) -> EloxResult {
             error(err, line, col);
             Ok(())
         }),
+        warn: Rc::new(|_, warning, line, col| {
+            log(format!("Warning: [line {}:{}]: {}", line, col, warning));
+            Ok(())
+        }),
         clock: Rc::new(|_| Ok(clock())),
-    };
+        seed: Rc::new(|| (clock() * 1000f64) as u64),
+        // scripts run in the browser only see their own scratch files
+        fs: FileSystem::in_memory(),
+        read_line: Rc::new(|| None),
+        read_all: Rc::new(String::new),
+        args: Rc::new(Vec::new),
+        env_var: Rc::new(|_| None),
+        exit: Rc::new(|_| {}),
+    }
+}
 
-    let mut elox = EloxInterpreter::new(host);
-    if let Err(err) = elox.run(source) {
-        elox.throw_error(err)?;
+fn run_on<R: EloxRunner>(mut elox: R, source: &str) -> EloxResult {
+    match elox.run(source) {
+        // exit() stops the script, it is not an error
+        Ok(()) | Err(EloxError::Eval(EvalError::Exit(_, _))) => {}
+        Err(err) => elox.throw_error(err)?,
     }
     Ok(())
 }
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backend {
+    TreeWalker,
+    Vm,
+}
+
+#[wasm_bindgen]
+pub fn run(source: &str) -> EloxResult {
+    run_on(EloxInterpreter::new(web_host()), source)
+}
+
+#[wasm_bindgen]
+pub fn run_vm(source: &str) -> EloxResult {
+    run_on(EloxVM::with_host(web_host()), source)
+}
+
+#[wasm_bindgen]
+pub fn run_with_backend(source: &str, backend: Backend) -> EloxResult {
+    match backend {
+        Backend::TreeWalker => run(source),
+        Backend::Vm => run_vm(source),
+    }
+}
+
+impl From<EloxError> for JsValue {
+    fn from(err: EloxError) -> JsValue {
+        JsValue::from_str(&format!("{}", err))
+    }
+}
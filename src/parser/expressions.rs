@@ -1,5 +1,6 @@
-use super::statements::Stmt;
-use super::{IdentifierHandle, IdentifierUse};
+use super::statements::{BlockStmt, Stmt};
+use super::types::Type;
+use super::{IdentifierHandle, IdentifierUse, IdentifierUseHandle};
 use crate::interpreter::lox_function::LoxFunctionParams;
 use crate::interpreter::value::Value;
 use crate::interpreter::{
@@ -32,6 +33,7 @@ pub enum Expr {
     Var(VarExpr),
     Assign(Box<AssignExpr>),
     Logical(Box<LogicalExpr>),
+    Conditional(Box<ConditionalExpr>),
     Call(Box<CallExpr>),
     Func(FuncExpr),
     Get(Box<GetExpr>),
@@ -39,6 +41,8 @@ pub enum Expr {
     This(ThisExpr),
     Super(SuperExpr),
     ArrayDeclExpr(Box<ArrayDeclExpr>),
+    Match(Box<MatchExpr>),
+    Spread(Box<SpreadExpr>),
 }
 
 #[derive(Clone)]
@@ -60,12 +64,21 @@ pub enum BinaryOperator {
     Slash,
     Star,
     Percent,
+    StarStar,
+    SlashSlash,
+    Ampersand,
+    Pipe,
+    Caret,
+    LessLess,
+    GreaterGreater,
+    GreaterGreaterGreater,
     BangEqual,
     EqualEqual,
     Greater,
     GreaterEqual,
     Less,
     LessEqual,
+    Is,
 }
 
 impl fmt::Display for BinaryOperator {
@@ -80,12 +93,21 @@ impl fmt::Display for BinaryOperator {
                 Slash => "/",
                 Star => "*",
                 Percent => "%",
+                StarStar => "**",
+                SlashSlash => "//",
+                Ampersand => "&",
+                Pipe => "|",
+                Caret => "^",
+                LessLess => "<<",
+                GreaterGreater => ">>",
+                GreaterGreaterGreater => ">>>",
                 BangEqual => "!=",
                 EqualEqual => "==",
                 Greater => ">",
                 GreaterEqual => ">=",
                 Less => "<",
                 LessEqual => "<=",
+                Is => "is",
             }
         )
     }
@@ -99,10 +121,19 @@ impl BinaryOperator {
             TokenType::Slash => BinaryOperator::Slash,
             TokenType::Star => BinaryOperator::Star,
             TokenType::Percent => BinaryOperator::Percent,
+            TokenType::StarStar => BinaryOperator::StarStar,
+            TokenType::SlashSlash => BinaryOperator::SlashSlash,
+            TokenType::Ampersand => BinaryOperator::Ampersand,
+            TokenType::Pipe => BinaryOperator::Pipe,
+            TokenType::Caret => BinaryOperator::Caret,
+            TokenType::LessLess => BinaryOperator::LessLess,
+            TokenType::GreaterGreater => BinaryOperator::GreaterGreater,
+            TokenType::GreaterGreaterGreater => BinaryOperator::GreaterGreaterGreater,
             TokenType::BangEqual => BinaryOperator::BangEqual,
             TokenType::Greater => BinaryOperator::Greater,
             TokenType::GreaterEqual => BinaryOperator::GreaterEqual,
             TokenType::Less => BinaryOperator::LessEqual,
+            TokenType::Is => BinaryOperator::Is,
             TokenType::PlusEqual => BinaryOperator::Plus,
             TokenType::PlusPlus => BinaryOperator::Plus,
             TokenType::MinusEqual => BinaryOperator::Minus,
@@ -148,6 +179,7 @@ impl BinaryExpr {
 pub enum UnaryOperator {
     Minus,
     Bang,
+    Tilde,
 }
 
 impl fmt::Display for UnaryOperator {
@@ -158,6 +190,7 @@ impl fmt::Display for UnaryOperator {
             match self {
                 UnaryOperator::Minus => "-",
                 UnaryOperator::Bang => "!",
+                UnaryOperator::Tilde => "~",
             }
         )
     }
@@ -217,6 +250,7 @@ impl AssignExpr {
 pub enum LogicalOperator {
     And,
     Or,
+    NilCoalescing, // short-circuits on nil only
 }
 
 #[derive(Clone)]
@@ -238,6 +272,31 @@ impl LogicalExpr {
     }
 }
 
+// cond ? then_branch : else_branch
+#[derive(Clone)]
+pub struct ConditionalExpr {
+    pub condition: ExprCtx,
+    pub then_branch: ExprCtx,
+    pub else_branch: ExprCtx,
+}
+
+impl ConditionalExpr {
+    pub fn new(
+        pos: Position,
+        condition: ExprCtx,
+        then_branch: ExprCtx,
+        else_branch: ExprCtx,
+    ) -> ExprCtx {
+        let expr = Expr::Conditional(Box::new(ConditionalExpr {
+            condition,
+            then_branch,
+            else_branch,
+        }));
+
+        ExprCtx::new(expr, pos)
+    }
+}
+
 #[derive(Clone)]
 pub struct CallExpr {
     pub callee: ExprCtx,
@@ -269,8 +328,11 @@ pub enum ContextLessFuncParam {
 pub struct FuncExpr {
     pub name: Option<IdentifierUse>,
     pub params: Option<Vec<FuncParam>>,
+    pub param_types: Vec<Option<Type>>, // one annotation per parameter
+    pub return_type: Option<Type>,
     pub body: Vec<Stmt>,
     pub pos: Position,
+    pub handle: IdentifierUseHandle, // unique to this function, unlike its position
 }
 
 impl FuncParam {
@@ -298,6 +360,13 @@ impl FuncParam {
 }
 
 impl ContextLessFuncParam {
+    pub fn identifier(&self) -> IdentifierHandle {
+        use ContextLessFuncParam::*;
+        match self {
+            Required(name) | DefaultValued(name, _) | Rest(name) => *name,
+        }
+    }
+
     pub fn is_required(&self) -> bool {
         if let ContextLessFuncParam::Required(_) = self {
             true
@@ -318,15 +387,21 @@ impl ContextLessFuncParam {
 impl FuncExpr {
     pub fn new(
         pos: Position,
+        handle: IdentifierUseHandle,
         name: Option<IdentifierUse>,
         params: Option<Vec<FuncParam>>,
+        param_types: Vec<Option<Type>>,
+        return_type: Option<Type>,
         body: Vec<Stmt>,
     ) -> ExprCtx {
         let expr = Expr::Func(FuncExpr {
             name,
             params,
+            param_types,
+            return_type,
             body,
             pos,
+            handle,
         });
         ExprCtx::new(expr, pos)
     }
@@ -347,17 +422,45 @@ impl FuncExpr {
             Ok(None)
         }
     }
+
+    // the minimum and maximum number of arguments accepted
+    pub fn arity(&self) -> (usize, usize) {
+        let params = self.params.as_deref().unwrap_or(&[]);
+        let min = params
+            .iter()
+            .filter(|p| matches!(p, FuncParam::Required(_)))
+            .count();
+
+        match params.last() {
+            Some(FuncParam::Rest(_)) => (min, usize::MAX),
+            _ => (min, params.len()),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct GetExpr {
     pub property: IdentifierUse,
     pub object: ExprCtx,
+    pub optional: bool, // obj?.prop yields nil on a nil object
 }
 
 impl GetExpr {
     pub fn new(pos: Position, property: IdentifierUse, object: ExprCtx) -> ExprCtx {
-        let expr = Expr::Get(Box::new(GetExpr { property, object }));
+        let expr = Expr::Get(Box::new(GetExpr {
+            property,
+            object,
+            optional: false,
+        }));
+        ExprCtx::new(expr, pos)
+    }
+
+    pub fn new_optional(pos: Position, property: IdentifierUse, object: ExprCtx) -> ExprCtx {
+        let expr = Expr::Get(Box::new(GetExpr {
+            property,
+            object,
+            optional: true,
+        }));
         ExprCtx::new(expr, pos)
     }
 }
@@ -415,3 +518,59 @@ impl ArrayDeclExpr {
         ExprCtx::new(Expr::ArrayDeclExpr(Box::new(ArrayDeclExpr { values })), pos)
     }
 }
+
+// ...values, only found in array literals and call arguments
+#[derive(Clone)]
+pub struct SpreadExpr {
+    pub value: ExprCtx,
+}
+
+impl SpreadExpr {
+    pub fn new(pos: Position, value: ExprCtx) -> ExprCtx {
+        ExprCtx::new(Expr::Spread(Box::new(SpreadExpr { value })), pos)
+    }
+}
+
+// capitalized names in patterns refer to enum variants or classes, never to new bindings
+#[derive(Clone)]
+pub enum Pattern {
+    Wildcard,                                               // _
+    Literal(Literal),                                       // 1, "a", true, nil
+    Binding(IdentifierUse),                                 // x
+    Constant(IdentifierUse),                                // None, Circle
+    Variant(IdentifierUse, Vec<Pattern>),                   // Circle(r)
+    Instance(IdentifierUse, Vec<(IdentifierUse, Pattern)>), // Point { x, y: 0 }
+    Array(Vec<Pattern>, Option<Box<Pattern>>),              // [a, b, ...rest]
+}
+
+impl Pattern {
+    // a pattern matching any value
+    pub fn is_irrefutable(&self) -> bool {
+        matches!(self, Pattern::Wildcard | Pattern::Binding(_))
+    }
+}
+
+#[derive(Clone)]
+pub enum MatchArmBody {
+    Expr(ExprCtx),
+    Block(BlockStmt), // evaluates to nil
+}
+
+#[derive(Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<ExprCtx>,
+    pub body: MatchArmBody,
+}
+
+#[derive(Clone)]
+pub struct MatchExpr {
+    pub value: ExprCtx,
+    pub arms: Vec<MatchArm>,
+}
+
+impl MatchExpr {
+    pub fn new(pos: Position, value: ExprCtx, arms: Vec<MatchArm>) -> ExprCtx {
+        ExprCtx::new(Expr::Match(Box::new(MatchExpr { value, arms })), pos)
+    }
+}
@@ -2,8 +2,9 @@ pub mod expressions;
 pub mod parser_result;
 pub mod pretty_printer;
 pub mod statements;
+pub mod types;
 use crate::scanner::scanner_result::ScannerResult;
-use crate::scanner::token::token_type::{TokenType, TokenType::*};
+use crate::scanner::token::token_type::{StringPart, TokenType, TokenType::*};
 use crate::scanner::token::{Position, Token};
 use crate::scanner::Scanner;
 use expressions::*;
@@ -11,12 +12,13 @@ use fnv::FnvHashMap;
 use parser_result::{ParserError, ParserResult};
 use statements::*;
 use std::iter::Peekable;
+use types::{FunctionType, Type};
 
 pub struct Identifier {}
 
 impl Identifier {
     pub fn reserved_count() -> usize {
-        9
+        10
     }
 
     pub fn this() -> IdentifierHandle {
@@ -54,6 +56,10 @@ impl Identifier {
     pub fn str_() -> IdentifierHandle {
         8
     }
+
+    pub fn string() -> IdentifierHandle {
+        9
+    }
 }
 
 pub type IdentifierHandle = usize;
@@ -71,10 +77,10 @@ pub struct IdentifierHandlesGenerator {
 impl IdentifierHandlesGenerator {
     pub fn new() -> IdentifierHandlesGenerator {
         let mut gen = IdentifierHandlesGenerator {
-            next_id_handle: 9,
+            next_id_handle: Identifier::reserved_count(),
             handles: FnvHashMap::default(),
             next_use_handle: 0,
-            names: Vec::with_capacity(9),
+            names: Vec::with_capacity(Identifier::reserved_count()),
         };
 
         IdentifierHandlesGenerator::init(&mut gen);
@@ -92,6 +98,7 @@ impl IdentifierHandlesGenerator {
             "#get",
             "#set",
             "#str",
+            "String",
         ];
 
         for (handle, &name) in identifiers.iter().enumerate() {
@@ -182,6 +189,7 @@ pub struct Parser<'a> {
     tokens: Peekable<Scanner<'a>>,
     identifiers: &'a mut IdentifierHandlesGenerator,
     pos: Position,
+    pending_greater: usize, // '>' left over from a '>>' token closing nested type arguments
 }
 
 impl<'a> Parser<'a> {
@@ -193,6 +201,7 @@ impl<'a> Parser<'a> {
             tokens,
             identifiers,
             pos: Position { line: 1, col: 1 },
+            pending_greater: 0,
         }
     }
 
@@ -271,6 +280,10 @@ impl<'a> Parser<'a> {
                 self.next();
                 res = Some(self.class_declaration());
             }
+            Enum => {
+                self.next();
+                res = Some(self.enum_declaration());
+            }
             _ => {}
         }
 
@@ -302,27 +315,17 @@ impl<'a> Parser<'a> {
             }
 
             if self.consume(LeftBrace)? {
-                let mut methods = Vec::new();
+                let mut body = ClassBody::default();
 
                 while !self.match_next(RightBrace)? {
-                    let func = self.function_declaration()?;
-                    match func.expr {
-                        Expr::Func(f) => {
-                            methods.push(f);
-                        }
-                        _ => {
-                            return Err(ParserError::ExpectedMethodDeclarationInClass(
-                                self.pos, name.name,
-                            ))
-                        }
-                    }
+                    self.class_member(name, &mut body)?;
                 }
 
                 if !self.consume(RightBrace)? {
                     return Err(ParserError::ExpectedRightBraceAfterClassBody(self.pos));
                 }
 
-                return Ok(ClassDeclStmt::to_stmt(self.pos, name, superclass, methods));
+                return Ok(ClassDeclStmt::to_stmt(self.pos, name, superclass, body));
             } else {
                 return Err(ParserError::ExpectedLeftBraceBeforeClassBody(self.pos));
             }
@@ -331,12 +334,175 @@ impl<'a> Parser<'a> {
         }
     }
 
+    // member → "private"? memberDecl ;
+    fn class_member(&mut self, class: IdentifierUse, body: &mut ClassBody) -> ParserResult<()> {
+        let mut member = self.consume_identifier()?;
+        let mut is_private = false;
+
+        if let Some(identifier) = member {
+            // like static, get and set, private is only a keyword in front of a member name
+            if self.identifiers.name(identifier.name) == "private" {
+                if let Identifier(_) = self.peek_token_type()? {
+                    is_private = true;
+                    member = self.consume_identifier()?;
+                }
+            }
+        }
+
+        let name = self.member_declaration(class, member, body)?;
+
+        if is_private {
+            if let Some(name) = name {
+                body.private_members.push(name);
+            }
+        }
+
+        Ok(())
+    }
+
+    // memberDecl → "static" function | "get" IDENTIFIER "(" ")" block | "set" function
+    //            | IDENTIFIER ( ":" type )? ( "=" expression )? ";" | function ;
+    fn member_declaration(
+        &mut self,
+        class: IdentifierUse,
+        member: Option<IdentifierUse>,
+        body: &mut ClassBody,
+    ) -> ParserResult<Option<IdentifierHandle>> {
+        if let Some(identifier) = member {
+            if let Identifier(_) = self.peek_token_type()? {
+                // static, get and set are only keywords in front of a method name
+                let modifier = self.identifiers.name(identifier.name);
+
+                if modifier == "static" || modifier == "get" || modifier == "set" {
+                    let name = self.consume_identifier()?;
+                    let method = self.method(class, name)?;
+                    let params = method.params.as_ref().map_or(0, |params| params.len());
+
+                    match modifier.as_str() {
+                        "static" => body.static_methods.push(method),
+                        "get" if params != 0 => {
+                            return Err(ParserError::GetterWithParameters(method.pos))
+                        }
+                        "get" => body.getters.push(method),
+                        _ if params != 1 => {
+                            return Err(ParserError::SetterWithoutOneParameter(method.pos))
+                        }
+                        _ => body.setters.push(method),
+                    }
+
+                    return Ok(name.map(|name| name.name));
+                }
+            }
+
+            if let Colon | Equal | SemiColon = self.peek_token_type()? {
+                let type_ = self.optional_type_annotation()?;
+                let initializer = if self.consume(Equal)? {
+                    Some(self.expression()?)
+                } else {
+                    None
+                };
+
+                if !self.consume(SemiColon)? {
+                    return Err(ParserError::ExpectedSemicolonAfterFieldDecl(self.pos));
+                }
+
+                body.fields.push(FieldDecl {
+                    identifier,
+                    type_,
+                    initializer,
+                });
+
+                return Ok(Some(identifier.name));
+            }
+        }
+
+        let method = self.method(class, member)?;
+        body.methods.push(method);
+
+        Ok(member.map(|name| name.name))
+    }
+
+    fn method(
+        &mut self,
+        class: IdentifierUse,
+        name: Option<IdentifierUse>,
+    ) -> ParserResult<FuncExpr> {
+        match self.function(name)?.expr {
+            Expr::Func(func) => Ok(func),
+            _ => Err(ParserError::ExpectedMethodDeclarationInClass(
+                self.pos, class.name,
+            )),
+        }
+    }
+
+    // enumDecl → "enum" IDENTIFIER "{" ( variant ( "," variant )* ","? )? "}" ;
+    // variant → IDENTIFIER ( "(" ( IDENTIFIER ( "," IDENTIFIER )* )? ")" )? ;
+    fn enum_declaration(&mut self) -> ParserResult<Stmt> {
+        let name = match self.consume_identifier()? {
+            Some(name) => name,
+            None => return Err(ParserError::ExpectedEnumName(self.pos)),
+        };
+
+        if !self.consume(LeftBrace)? {
+            return Err(ParserError::ExpectedLeftBraceBeforeEnumBody(self.pos));
+        }
+
+        let mut variants = Vec::new();
+
+        while !self.match_next(RightBrace)? {
+            let identifier = match self.consume_identifier()? {
+                Some(identifier) => identifier,
+                None => return Err(ParserError::ExpectedVariantName(self.pos)),
+            };
+
+            let mut fields = None;
+
+            if self.consume(LeftParen)? {
+                let mut names = Vec::new();
+
+                while !self.match_next(RightParen)? {
+                    match self.consume_identifier()? {
+                        Some(field) => names.push(field),
+                        None => return Err(ParserError::ExpectedVariantFieldName(self.pos)),
+                    }
+
+                    if !self.consume(Comma)? {
+                        break;
+                    }
+                }
+
+                if !self.consume(RightParen)? {
+                    return Err(ParserError::ExpectedRightParenAfterVariantFields(self.pos));
+                }
+
+                fields = Some(names);
+            }
+
+            variants.push(EnumVariant { identifier, fields });
+
+            if !self.consume(Comma)? {
+                break;
+            }
+        }
+
+        if !self.consume(RightBrace)? {
+            return Err(ParserError::ExpectedRightBraceAfterEnumBody(self.pos));
+        }
+
+        Ok(EnumDeclStmt::to_stmt(self.pos, name, variants))
+    }
+
     fn function_declaration(&mut self) -> ParserResult<ExprCtx> {
         let name = self.consume_identifier()?;
+        self.function(name)
+    }
+
+    fn function(&mut self, name: Option<IdentifierUse>) -> ParserResult<ExprCtx> {
         let pos = self.pos;
 
         if self.consume(LeftParen)? {
             let mut params = Vec::new();
+            let mut param_types = Vec::new();
             let mut has_rest_param = false;
             let mut idx = 0;
             let mut first_optional_idx = usize::max_value();
@@ -345,6 +511,8 @@ impl<'a> Parser<'a> {
             if !self.match_next(RightParen)? {
                 loop {
                     if let Some(param) = self.consume_identifier()? {
+                        param_types.push(self.optional_type_annotation()?);
+
                         if self.consume(Equal)? {
                             let value = self.expression()?;
                             if idx < first_optional_idx {
@@ -359,6 +527,7 @@ impl<'a> Parser<'a> {
                         }
                     } else if self.consume(DotDotDot)? {
                         if let Some(param) = self.consume_identifier()? {
+                            param_types.push(self.optional_type_annotation()?);
                             has_rest_param = true;
                             if idx < first_optional_idx {
                                 first_optional_idx = idx;
@@ -391,6 +560,8 @@ impl<'a> Parser<'a> {
                 return Err(ParserError::ExpectedRightParenAfterCallExpr(self.pos));
             }
 
+            let return_type = self.optional_type_annotation()?;
+
             if !self.consume(LeftBrace)? {
                 return Err(ParserError::ExpectedLeftBraceBeforeFuncBody(self.pos));
             }
@@ -403,12 +574,120 @@ impl<'a> Parser<'a> {
                 None
             };
 
-            return Ok(FuncExpr::new(pos, name, params_opt, body));
+            return Ok(FuncExpr::new(
+                pos,
+                self.identifiers.next_use_handle(),
+                name,
+                params_opt,
+                param_types,
+                return_type,
+                body,
+            ));
         } else {
             Err(ParserError::ExpectedRightParenAfterCallExpr(self.pos))
         }
     }
 
+    // ( ":" type )?
+    fn optional_type_annotation(&mut self) -> ParserResult<Option<Type>> {
+        if self.consume(Colon)? {
+            return Ok(Some(self.type_annotation()?));
+        }
+
+        Ok(None)
+    }
+
+    // type → ( "fun" "(" types? ")" ( ":" type )? | "nil" | IDENTIFIER ( "<" types ">" )? ) "?"? ;
+    fn type_annotation(&mut self) -> ParserResult<Type> {
+        self.peek_token_type()?;
+        let next = self.next().unwrap().unwrap();
+
+        let type_ = match next.token_type {
+            Nil => Type::Nil,
+            Fun => {
+                if !self.consume(LeftParen)? {
+                    return Err(ParserError::ExpectedLeftParenInFunctionType(self.pos));
+                }
+
+                let mut params = Vec::new();
+
+                while !self.match_next(RightParen)? {
+                    params.push(self.type_annotation()?);
+
+                    if !self.consume(Comma)? {
+                        break;
+                    }
+                }
+
+                if !self.consume(RightParen)? {
+                    return Err(ParserError::ExpectedRightParenInFunctionType(self.pos));
+                }
+
+                let ret = self.optional_type_annotation()?.unwrap_or(Type::Any);
+
+                Type::Function(std::rc::Rc::new(FunctionType {
+                    required: params.len(),
+                    params,
+                    rest: false,
+                    ret,
+                }))
+            }
+            Identifier(name) => match name.as_str() {
+                "any" => Type::Any,
+                "bool" => Type::Boolean,
+                "number" => Type::Number,
+                "string" => Type::String,
+                _ => {
+                    let mut args = Vec::new();
+
+                    if self.consume(Less)? {
+                        loop {
+                            args.push(self.type_annotation()?);
+
+                            if !self.consume(Comma)? {
+                                break;
+                            }
+                        }
+
+                        if !self.consume_closing_angle_bracket()? {
+                            return Err(ParserError::ExpectedClosingAngleBracket(self.pos));
+                        }
+                    }
+
+                    Type::Named(self.identifiers.by_name(&name), args)
+                }
+            },
+            _ => return Err(ParserError::ExpectedType(next.pos, next.lexeme)),
+        };
+
+        // the '?' of 'Array<number>>?' belongs to the outer type
+        if self.pending_greater == 0 && self.consume(Question)? {
+            return Ok(Type::Nullable(Box::new(type_)));
+        }
+
+        Ok(type_)
+    }
+
+    // the scanner reads the end of Array<Array<number>> as a single '>>' token
+    fn consume_closing_angle_bracket(&mut self) -> ParserResult<bool> {
+        if self.pending_greater > 0 {
+            self.pending_greater -= 1;
+            return Ok(true);
+        }
+
+        let pending = match self.peek_token_type()? {
+            Greater => 0,
+            GreaterGreater => 1,
+            GreaterGreaterGreater => 2,
+            _ => return Ok(false),
+        };
+
+        self.next();
+        self.pending_greater = pending;
+
+        Ok(true)
+    }
+
     fn match_identifier(&mut self) -> ParserResult<Option<IdentifierUse>> {
         if let Identifier(name) = self.peek_token_type()? {
             return Ok(Some(IdentifierUse::new(
@@ -431,8 +710,13 @@ impl<'a> Parser<'a> {
     }
 
     fn var_declaration(&mut self) -> ParserResult<Stmt> {
+        if let LeftBracket | LeftBrace = self.peek_token_type()? {
+            return self.destructuring_declaration();
+        }
+
         if let Some(identifier) = self.match_identifier()? {
             self.next();
+            let type_ = self.optional_type_annotation()?;
             let mut initializer: Option<ExprCtx> = None;
 
             if self.consume(Equal)? {
@@ -443,7 +727,12 @@ impl<'a> Parser<'a> {
                 return Err(ParserError::ExpectedSemicolonAfterExpr(self.pos));
             }
 
-            return Ok(VarDeclStmt::to_stmt(identifier, initializer, self.pos));
+            return Ok(VarDeclStmt::to_stmt(
+                identifier,
+                type_,
+                initializer,
+                self.pos,
+            ));
         } else {
             if let Some(Ok(tok)) = self.tokens.peek() {
                 return Err(ParserError::ExpectedVarName(self.pos, tok.lexeme.clone()));
@@ -453,6 +742,81 @@ impl<'a> Parser<'a> {
         }
     }
 
+    // destructuringDecl → ( "[" ( IDENTIFIER "," )* ( "..." IDENTIFIER )? "]"
+    //                    | "{" ( IDENTIFIER ( "," IDENTIFIER )* )? "}" ) "=" expression ";" ;
+    fn destructuring_declaration(&mut self) -> ParserResult<Stmt> {
+        let opening = self.peek_token_type()?;
+        self.next(); // [ or {
+        let pattern_pos = self.pos;
+
+        let target = if opening == LeftBracket {
+            let mut names = Vec::new();
+            let mut rest = None;
+
+            while !self.match_next(RightBracket)? {
+                if self.consume(DotDotDot)? {
+                    rest = Some(self.destructured_name()?);
+                    break;
+                }
+
+                names.push(self.destructured_name()?);
+
+                if !self.consume(Comma)? {
+                    break;
+                }
+            }
+
+            if !self.consume(RightBracket)? {
+                return Err(ParserError::UnclosedPattern(self.pos, ']'));
+            }
+
+            Destructuring::Array(names, rest)
+        } else {
+            let mut names = Vec::new();
+
+            while !self.match_next(RightBrace)? {
+                names.push(self.destructured_name()?);
+
+                if !self.consume(Comma)? {
+                    break;
+                }
+            }
+
+            if !self.consume(RightBrace)? {
+                return Err(ParserError::UnclosedPattern(self.pos, '}'));
+            }
+
+            Destructuring::Object(names)
+        };
+
+        if !self.consume(Equal)? {
+            return Err(ParserError::ExpectedInitializerAfterDestructuring(self.pos));
+        }
+
+        let initializer = self.expression()?;
+
+        if !self.consume(SemiColon)? {
+            return Err(ParserError::ExpectedSemicolonAfterExpr(self.pos));
+        }
+
+        Ok(DestructuringDeclStmt::to_stmt(
+            target,
+            initializer,
+            pattern_pos,
+        ))
+    }
+
+    fn destructured_name(&mut self) -> ParserResult<IdentifierUse> {
+        if let Some(identifier) = self.consume_identifier()? {
+            return Ok(identifier);
+        }
+
+        match self.tokens.peek() {
+            Some(Ok(tok)) => Err(ParserError::ExpectedVarName(self.pos, tok.lexeme.clone())),
+            _ => Err(ParserError::ExpectedStatement(self.pos)),
+        }
+    }
+
     fn statement(&mut self) -> ParserResult<Stmt> {
         if let Some(Ok(token)) = self.tokens.peek() {
             match token.token_type {
@@ -477,6 +841,10 @@ impl<'a> Parser<'a> {
                     self.next();
                     return self.while_stmt();
                 }
+                Match => {
+                    self.next();
+                    return self.match_stmt();
+                }
                 LeftBrace => {
                     self.next();
                     return Ok(Stmt::Block(self.block()?));
@@ -575,6 +943,217 @@ impl<'a> Parser<'a> {
         }
     }
 
+    // a match used as a statement doesn't need a trailing ';'
+    fn match_stmt(&mut self) -> ParserResult<Stmt> {
+        let expr_ctx = self.match_expr(self.pos)?;
+        self.consume(SemiColon)?;
+
+        Ok(ExprStmt::to_stmt(expr_ctx))
+    }
+
+    // match → "match" "(" expression ")" "{" ( arm ( "," arm )* ","? )? "}" ;
+    // arm → pattern ( "if" expression )? "=>" ( block | expression ) ;
+    fn match_expr(&mut self, pos: Position) -> ParserResult<ExprCtx> {
+        if !self.consume(LeftParen)? {
+            return Err(ParserError::ExpectedLeftParenAfterMatch(self.pos));
+        }
+
+        let value = self.expression()?;
+
+        if !self.consume(RightParen)? {
+            return Err(ParserError::ExpectedRightParenAfterMatch(self.pos));
+        }
+
+        if !self.consume(LeftBrace)? {
+            return Err(ParserError::ExpectedLeftBraceBeforeMatchArms(self.pos));
+        }
+
+        let mut arms = Vec::new();
+
+        while !self.match_next(RightBrace)? {
+            let pattern = self.pattern()?;
+            let mut guard = None;
+
+            if self.consume(If)? {
+                guard = Some(self.expression()?);
+            }
+
+            if !self.consume(FatArrow)? {
+                return Err(ParserError::ExpectedFatArrowAfterPattern(self.pos));
+            }
+
+            let body = if self.consume(LeftBrace)? {
+                MatchArmBody::Block(self.block()?)
+            } else {
+                MatchArmBody::Expr(self.expression()?)
+            };
+
+            let is_block = matches!(body, MatchArmBody::Block(_));
+
+            arms.push(MatchArm {
+                pattern,
+                guard,
+                body,
+            });
+
+            // the comma is optional after a block
+            if !self.consume(Comma)? && !is_block {
+                break;
+            }
+        }
+
+        if !self.consume(RightBrace)? {
+            return Err(ParserError::ExpectedRightBraceAfterMatchArms(self.pos));
+        }
+
+        Ok(MatchExpr::new(pos, value, arms))
+    }
+
+    // pattern → "_" | literal | "-" NUMBER | IDENTIFIER | IDENTIFIER "(" patterns? ")"
+    //         | IDENTIFIER "{" ( IDENTIFIER ( ":" pattern )? "," )* "}"
+    //         | "[" patterns? ( "..." pattern )? "]" ;
+    // lowercase identifiers are bindings, capitalized ones refer to enum variants and classes
+    fn pattern(&mut self) -> ParserResult<Pattern> {
+        self.peek_token_type()?;
+        let next = self.next().unwrap().unwrap();
+
+        match next.token_type {
+            Nil => Ok(Pattern::Literal(Literal::Nil)),
+            True => Ok(Pattern::Literal(Literal::Boolean(true))),
+            False => Ok(Pattern::Literal(Literal::Boolean(false))),
+            Number(nb) => Ok(Pattern::Literal(Literal::Number(nb))),
+            String(s) => Ok(Pattern::Literal(Literal::String(s))),
+            Minus => {
+                if let Number(nb) = self.peek_token_type()? {
+                    self.next();
+                    return Ok(Pattern::Literal(Literal::Number(-nb)));
+                }
+
+                Err(ParserError::ExpectedPattern(next.pos, next.lexeme))
+            }
+            Identifier(name) => {
+                if name == "_" {
+                    return Ok(Pattern::Wildcard);
+                }
+
+                let identifier = self.identifiers.next_with_name(&name, next.pos);
+
+                if !name.starts_with(char::is_uppercase) {
+                    return Ok(Pattern::Binding(identifier));
+                }
+
+                if self.consume(LeftParen)? {
+                    let mut fields = Vec::new();
+
+                    while !self.match_next(RightParen)? {
+                        fields.push(self.pattern()?);
+
+                        if !self.consume(Comma)? {
+                            break;
+                        }
+                    }
+
+                    if !self.consume(RightParen)? {
+                        return Err(ParserError::UnclosedPattern(self.pos, ')'));
+                    }
+
+                    return Ok(Pattern::Variant(identifier, fields));
+                }
+
+                if self.consume(LeftBrace)? {
+                    let mut fields = Vec::new();
+
+                    while !self.match_next(RightBrace)? {
+                        let field = match self.consume_identifier()? {
+                            Some(field) => field,
+                            None => return Err(ParserError::ExpectedFieldNameInPattern(self.pos)),
+                        };
+
+                        let pattern = if self.consume(Colon)? {
+                            self.pattern()?
+                        } else {
+                            Pattern::Binding(field)
+                        };
+
+                        fields.push((field, pattern));
+
+                        if !self.consume(Comma)? {
+                            break;
+                        }
+                    }
+
+                    if !self.consume(RightBrace)? {
+                        return Err(ParserError::UnclosedPattern(self.pos, '}'));
+                    }
+
+                    return Ok(Pattern::Instance(identifier, fields));
+                }
+
+                Ok(Pattern::Constant(identifier))
+            }
+            LeftBracket => {
+                let mut values = Vec::new();
+                let mut rest = None;
+
+                while !self.match_next(RightBracket)? {
+                    if self.consume(DotDotDot)? {
+                        rest = Some(Box::new(self.pattern()?));
+                        break;
+                    }
+
+                    values.push(self.pattern()?);
+
+                    if !self.consume(Comma)? {
+                        break;
+                    }
+                }
+
+                if !self.consume(RightBracket)? {
+                    return Err(ParserError::UnclosedPattern(self.pos, ']'));
+                }
+
+                Ok(Pattern::Array(values, rest))
+            }
+            _ => Err(ParserError::ExpectedPattern(next.pos, next.lexeme)),
+        }
+    }
+
+    // conditional → nil_coalescing ( "?" expression ":" conditional )? ;
+    fn conditional(&mut self) -> ParserResult<ExprCtx> {
+        let condition = self.nil_coalescing()?;
+
+        if self.consume(Question)? {
+            let then_branch = self.expression()?;
+
+            if !self.consume(Colon)? {
+                return Err(ParserError::ExpectedColonInConditional(self.pos));
+            }
+
+            let else_branch = self.conditional()?;
+
+            return Ok(ConditionalExpr::new(
+                condition.pos,
+                condition,
+                then_branch,
+                else_branch,
+            ));
+        }
+
+        Ok(condition)
+    }
+
+    // nil_coalescing → or_expr ( "??" or_expr )* ;
+    fn nil_coalescing(&mut self) -> ParserResult<ExprCtx> {
+        let mut expr_ctx = self.or_expr()?;
+
+        while self.consume(QuestionQuestion)? {
+            let right = self.or_expr()?;
+            expr_ctx = LogicalExpr::new(self.pos, expr_ctx, LogicalOperator::NilCoalescing, right);
+        }
+
+        Ok(expr_ctx)
+    }
+
     fn or_expr(&mut self) -> ParserResult<ExprCtx> {
         let mut expr_ctx = self.and_expr()?;
 
@@ -666,7 +1245,7 @@ impl<'a> Parser<'a> {
     }
 
     fn assignment(&mut self) -> ParserResult<ExprCtx> {
-        let left_val = self.or_expr()?;
+        let left_val = self.conditional()?;
 
         let next_token = self.tokens.peek();
 
@@ -708,7 +1287,11 @@ impl<'a> Parser<'a> {
                 match left_val.expr {
                     Expr::Var(v) => return Ok(AssignExpr::new(self.pos, v.identifier, right_val)),
                     Expr::Get(g) => {
-                        return Ok(SetExpr::new(self.pos, g.property, g.object, right_val))
+                        if g.optional {
+                            return Err(ParserError::InvalidAssignmentTarget(self.pos));
+                        }
+
+                        return Ok(SetExpr::new(self.pos, g.property, g.object, right_val));
                     }
                     Expr::Call(call_expr_ctx) => {
                         if let Expr::Get(access) = call_expr_ctx.callee.expr {
@@ -770,10 +1353,28 @@ impl<'a> Parser<'a> {
                 self.next();
                 Ok(Some(BinaryOperator::LessEqual))
             }
+            Is => {
+                self.next();
+                Ok(Some(BinaryOperator::Is))
+            }
             _ => Ok(None),
         }
     }
 
+    fn match_binary_operator(
+        &mut self,
+        operators: &[TokenType],
+    ) -> ParserResult<Option<BinaryOperator>> {
+        let token_type = self.peek_token_type()?;
+
+        if operators.contains(&token_type) {
+            self.next();
+            return Ok(BinaryOperator::from_token_type(&token_type));
+        }
+
+        Ok(None)
+    }
+
     fn match_addition(&mut self) -> ParserResult<Option<BinaryOperator>> {
         match self.peek_token_type()? {
             Plus => {
@@ -805,6 +1406,10 @@ impl<'a> Parser<'a> {
                 self.next();
                 Ok(Some(BinaryOperator::Percent))
             }
+            SlashSlash => {
+                self.next();
+                Ok(Some(BinaryOperator::SlashSlash))
+            }
             _ => Ok(None),
         }
     }
@@ -819,6 +1424,10 @@ impl<'a> Parser<'a> {
                 self.next();
                 Ok(Some(UnaryOperator::Bang))
             }
+            Tilde => {
+                self.next();
+                Ok(Some(UnaryOperator::Tilde))
+            }
             _ => Ok(None),
         }
     }
@@ -835,11 +1444,84 @@ impl<'a> Parser<'a> {
         Ok(expr_ctx)
     }
 
-    // comparison → addition ( ( ">" | ">=" | "<" | "<=" ) addition )* ;
+    // comparison → range ( ( ">" | ">=" | "<" | "<=" | "is" ) range )* ;
     fn comparison(&mut self) -> ParserResult<ExprCtx> {
-        let mut expr_ctx = self.addition()?;
+        let mut expr_ctx = self.range()?;
 
         while let Some(op) = self.match_comparison()? {
+            let right = self.range()?;
+            expr_ctx = BinaryExpr::new(self.pos, expr_ctx, op, right);
+        }
+
+        Ok(expr_ctx)
+    }
+
+    // a..b is sugar for Range(a, b, false) and a..=b for Range(a, b, true)
+    // range → bit_or ( ( ".." | "..=" ) bit_or )? ;
+    fn range(&mut self) -> ParserResult<ExprCtx> {
+        let start = self.bit_or()?;
+
+        let inclusive = if self.consume(DotDot)? {
+            false
+        } else if self.consume(DotDotEqual)? {
+            true
+        } else {
+            return Ok(start);
+        };
+
+        let pos = self.pos;
+        let end = self.bit_or()?;
+        let range_class = VarExpr::new(pos, self.identifiers.next_with_name("Range", pos));
+
+        let inclusive = Literal::new(pos, Literal::Boolean(inclusive));
+
+        Ok(CallExpr::new(pos, range_class, vec![start, end, inclusive]))
+    }
+
+    // bitwise operators bind tighter than comparisons: x & 1 == 0 is (x & 1) == 0
+    // bit_or → bit_xor ( "|" bit_xor )* ;
+    fn bit_or(&mut self) -> ParserResult<ExprCtx> {
+        let mut expr_ctx = self.bit_xor()?;
+
+        while let Some(op) = self.match_binary_operator(&[Pipe])? {
+            let right = self.bit_xor()?;
+            expr_ctx = BinaryExpr::new(self.pos, expr_ctx, op, right);
+        }
+
+        Ok(expr_ctx)
+    }
+
+    // bit_xor → bit_and ( "^" bit_and )* ;
+    fn bit_xor(&mut self) -> ParserResult<ExprCtx> {
+        let mut expr_ctx = self.bit_and()?;
+
+        while let Some(op) = self.match_binary_operator(&[Caret])? {
+            let right = self.bit_and()?;
+            expr_ctx = BinaryExpr::new(self.pos, expr_ctx, op, right);
+        }
+
+        Ok(expr_ctx)
+    }
+
+    // bit_and → shift ( "&" shift )* ;
+    fn bit_and(&mut self) -> ParserResult<ExprCtx> {
+        let mut expr_ctx = self.shift()?;
+
+        while let Some(op) = self.match_binary_operator(&[Ampersand])? {
+            let right = self.shift()?;
+            expr_ctx = BinaryExpr::new(self.pos, expr_ctx, op, right);
+        }
+
+        Ok(expr_ctx)
+    }
+
+    // shift → addition ( ( "<<" | ">>" | ">>>" ) addition )* ;
+    fn shift(&mut self) -> ParserResult<ExprCtx> {
+        let mut expr_ctx = self.addition()?;
+
+        while let Some(op) =
+            self.match_binary_operator(&[LessLess, GreaterGreater, GreaterGreaterGreater])?
+        {
             let right = self.addition()?;
             expr_ctx = BinaryExpr::new(self.pos, expr_ctx, op, right);
         }
@@ -875,7 +1557,25 @@ impl<'a> Parser<'a> {
             return Ok(UnaryExpr::new(self.pos, op, right));
         }
 
-        self.call()
+        self.power()
+    }
+
+    // power → call ( "**" unary )? ;
+    // right associative and binds tighter than a unary operator on its left: -2 ** 2 == -4
+    fn power(&mut self) -> ParserResult<ExprCtx> {
+        let expr_ctx = self.call()?;
+
+        if self.consume(StarStar)? {
+            let right = self.unary()?;
+            return Ok(BinaryExpr::new(
+                self.pos,
+                expr_ctx,
+                BinaryOperator::StarStar,
+                right,
+            ));
+        }
+
+        Ok(expr_ctx)
     }
 
     fn call(&mut self) -> ParserResult<ExprCtx> {
@@ -896,6 +1596,12 @@ impl<'a> Parser<'a> {
                 } else {
                     return Err(ParserError::ExpectedPropertyNameAfterDot(self.pos));
                 }
+            } else if self.consume(QuestionDot)? {
+                if let Some(prop) = self.consume_identifier()? {
+                    expr_ctx = GetExpr::new_optional(self.pos, prop, expr_ctx);
+                } else {
+                    return Err(ParserError::ExpectedPropertyNameAfterDot(self.pos));
+                }
             } else {
                 break;
             }
@@ -909,7 +1615,7 @@ impl<'a> Parser<'a> {
 
         if !self.match_next(RightParen)? {
             loop {
-                args.push(self.expression()?);
+                args.push(self.spreadable_expression()?);
                 if !self.consume(Comma)? {
                     break;
                 }
@@ -923,6 +1629,16 @@ impl<'a> Parser<'a> {
         Ok(CallExpr::new(self.pos, expr_ctx, args))
     }
 
+    // spreadable → "..."? expression ;
+    fn spreadable_expression(&mut self) -> ParserResult<ExprCtx> {
+        if self.consume(DotDotDot)? {
+            let pos = self.pos;
+            return Ok(SpreadExpr::new(pos, self.expression()?));
+        }
+
+        self.expression()
+    }
+
     fn finish_array_access(&mut self, expr_ctx: ExprCtx) -> ParserResult<ExprCtx> {
         let mut args = Vec::new();
 
@@ -942,6 +1658,45 @@ impl<'a> Parser<'a> {
         Ok(CallExpr::new(self.pos, expr_ctx, args))
     }
 
+    // "a ${b} c" is desugared into "a " + b + " c"
+    fn interpolated_string(
+        &mut self,
+        pos: Position,
+        parts: Vec<StringPart>,
+    ) -> ParserResult<ExprCtx> {
+        let mut expr_ctx = Literal::new(pos, Literal::String("".into()));
+
+        for part in parts {
+            let right = match part {
+                StringPart::Literal(s) => Literal::new(pos, Literal::String(s)),
+                StringPart::Expr(source, expr_pos) => {
+                    self.interpolated_expression(&source, expr_pos)?
+                }
+            };
+
+            expr_ctx = BinaryExpr::new(pos, expr_ctx, BinaryOperator::Plus, right);
+        }
+
+        Ok(expr_ctx)
+    }
+
+    fn interpolated_expression(&mut self, source: &str, pos: Position) -> ParserResult<ExprCtx> {
+        if source.trim().is_empty() {
+            return Err(ParserError::ExpectedExpressionInInterpolation(pos));
+        }
+
+        let scanner = Scanner::with_position(source.chars().peekable(), pos);
+        let mut parser = Parser::new(scanner.peekable(), &mut *self.identifiers);
+        let expr_ctx = parser.expression()?;
+
+        if parser.match_next(EOF)? {
+            return Ok(expr_ctx);
+        }
+
+        let token = parser.next().unwrap().unwrap();
+        Err(ParserError::UnexpectedToken(token.pos, token.lexeme))
+    }
+
     fn primary(&mut self) -> ParserResult<ExprCtx> {
         let next = self.next().unwrap().unwrap();
 
@@ -961,6 +1716,7 @@ impl<'a> Parser<'a> {
             String(s) => {
                 return Ok(Literal::new(next.pos, Literal::String(s.clone())));
             }
+            InterpolatedString(parts) => self.interpolated_string(next.pos, parts),
             LeftParen => {
                 let expr_ctx = self.expression()?;
                 if self.consume(RightParen)? {
@@ -976,6 +1732,7 @@ impl<'a> Parser<'a> {
                 ))
             }
             Fun => self.function_declaration(),
+            Match => self.match_expr(next.pos),
             This => Ok(ThisExpr::new(
                 self.pos,
                 self.identifiers
@@ -999,7 +1756,7 @@ impl<'a> Parser<'a> {
                 // array inline expression
                 let mut values = vec![];
                 while !self.match_next(RightBracket)? {
-                    values.push(self.expression()?);
+                    values.push(self.spreadable_expression()?);
                     if !self.consume(Comma)? {
                         break;
                     }
@@ -1025,7 +1782,7 @@ impl<'a> Parser<'a> {
             // TODO: return ¨ParseErrors instead of printing in place
             match self.next() {
                 Some(Ok(t)) => match t.token_type {
-                    Class | Fun | Var | For | If | While | Print | Return => return,
+                    Class | Enum | Fun | Var | For | If | While | Print | Return => return,
                     _ => continue,
                 },
                 Some(Err(e)) => println!("scanner error: {}", e),
@@ -34,6 +34,30 @@ pub enum ParserError {
     ExpectedSuperclassMethodName(Position),
     RestParameterMustBeLast(Position),
     OptionalParamCannotPrecedeRequiredParam(Position),
+    ExpectedExpressionInInterpolation(Position),
+    ExpectedColonInConditional(Position),
+    ExpectedEnumName(Position),
+    ExpectedLeftBraceBeforeEnumBody(Position),
+    ExpectedRightBraceAfterEnumBody(Position),
+    ExpectedVariantName(Position),
+    ExpectedVariantFieldName(Position),
+    ExpectedRightParenAfterVariantFields(Position),
+    ExpectedLeftParenAfterMatch(Position),
+    ExpectedRightParenAfterMatch(Position),
+    ExpectedLeftBraceBeforeMatchArms(Position),
+    ExpectedRightBraceAfterMatchArms(Position),
+    ExpectedFatArrowAfterPattern(Position),
+    ExpectedPattern(Position, String),
+    ExpectedFieldNameInPattern(Position),
+    UnclosedPattern(Position, char),
+    ExpectedSemicolonAfterFieldDecl(Position),
+    GetterWithParameters(Position),
+    SetterWithoutOneParameter(Position),
+    ExpectedType(Position, String),
+    ExpectedClosingAngleBracket(Position),
+    ExpectedLeftParenInFunctionType(Position),
+    ExpectedRightParenInFunctionType(Position),
+    ExpectedInitializerAfterDestructuring(Position),
 }
 
 impl fmt::Display for ParserError {
@@ -76,6 +100,47 @@ impl fmt::Display for ParserError {
                 f,
                 "An optional parameter cannot precede a required parameter"
             ),
+            ExpectedExpressionInInterpolation(_) => {
+                write!(f, "Expected an expression inside '${{}}'")
+            }
+            ExpectedColonInConditional(_) => {
+                write!(
+                    f,
+                    "Expected ':' after the then branch of a conditional expression"
+                )
+            }
+            ExpectedEnumName(_) => write!(f, "Expected enum name"),
+            ExpectedLeftBraceBeforeEnumBody(_) => write!(f, "Expected '{{' before enum body"),
+            ExpectedRightBraceAfterEnumBody(_) => write!(f, "Expected '}}' after enum body"),
+            ExpectedVariantName(_) => write!(f, "Expected enum variant name"),
+            ExpectedVariantFieldName(_) => write!(f, "Expected field name for enum variant"),
+            ExpectedRightParenAfterVariantFields(_) => {
+                write!(f, "Expected ')' after enum variant fields")
+            }
+            ExpectedLeftParenAfterMatch(_) => write!(f, "Expected '(' after 'match'"),
+            ExpectedRightParenAfterMatch(_) => write!(f, "Expected ')' after matched value"),
+            ExpectedLeftBraceBeforeMatchArms(_) => write!(f, "Expected '{{' before match arms"),
+            ExpectedRightBraceAfterMatchArms(_) => write!(f, "Expected '}}' after match arms"),
+            ExpectedFatArrowAfterPattern(_) => write!(f, "Expected '=>' after match pattern"),
+            ExpectedPattern(_, t) => write!(f, "Expected a pattern, got: '{}'", t),
+            ExpectedFieldNameInPattern(_) => write!(f, "Expected field name in pattern"),
+            UnclosedPattern(_, c) => write!(f, "Expected '{}' to close the pattern", c),
+            ExpectedSemicolonAfterFieldDecl(_) => {
+                write!(f, "Expected ';' after field declaration")
+            }
+            GetterWithParameters(_) => write!(f, "A getter cannot have parameters"),
+            SetterWithoutOneParameter(_) => {
+                write!(f, "A setter must have exactly one parameter")
+            }
+            ExpectedType(_, t) => write!(f, "Expected a type, got: '{}'", t),
+            ExpectedClosingAngleBracket(_) => write!(f, "Expected '>' after type arguments"),
+            ExpectedInitializerAfterDestructuring(_) => {
+                write!(f, "Expected '=' after a destructuring pattern")
+            }
+            ExpectedLeftParenInFunctionType(_) => write!(f, "Expected '(' after 'fun' in type"),
+            ExpectedRightParenInFunctionType(_) => {
+                write!(f, "Expected ')' after function type parameters")
+            }
         }
     }
 }
@@ -111,6 +176,30 @@ impl ErrorPosition for ParserError {
             | ExpectedSuperclassName(pos)
             | ExpectedSuperclassMethodName(pos)
             | OptionalParamCannotPrecedeRequiredParam(pos)
+            | ExpectedExpressionInInterpolation(pos)
+            | ExpectedColonInConditional(pos)
+            | ExpectedEnumName(pos)
+            | ExpectedLeftBraceBeforeEnumBody(pos)
+            | ExpectedRightBraceAfterEnumBody(pos)
+            | ExpectedVariantName(pos)
+            | ExpectedVariantFieldName(pos)
+            | ExpectedRightParenAfterVariantFields(pos)
+            | ExpectedLeftParenAfterMatch(pos)
+            | ExpectedRightParenAfterMatch(pos)
+            | ExpectedLeftBraceBeforeMatchArms(pos)
+            | ExpectedRightBraceAfterMatchArms(pos)
+            | ExpectedFatArrowAfterPattern(pos)
+            | ExpectedPattern(pos, _)
+            | ExpectedFieldNameInPattern(pos)
+            | UnclosedPattern(pos, _)
+            | ExpectedSemicolonAfterFieldDecl(pos)
+            | GetterWithParameters(pos)
+            | SetterWithoutOneParameter(pos)
+            | ExpectedType(pos, _)
+            | ExpectedClosingAngleBracket(pos)
+            | ExpectedLeftParenInFunctionType(pos)
+            | ExpectedRightParenInFunctionType(pos)
+            | ExpectedInitializerAfterDestructuring(pos)
             | RestParameterMustBeLast(pos) => pos,
         }
     }
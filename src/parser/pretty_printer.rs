@@ -16,6 +16,7 @@ impl PrettyPrinter for Expr {
             Expr::Var(v) => v.pretty_print(names),
             Expr::Assign(a) => a.pretty_print(names),
             Expr::Logical(l) => l.pretty_print(names),
+            Expr::Conditional(c) => c.pretty_print(names),
             Expr::Call(c) => c.pretty_print(names),
             Expr::Func(f) => f.pretty_print(names),
             Expr::Get(g) => g.pretty_print(names),
@@ -23,6 +24,8 @@ impl PrettyPrinter for Expr {
             Expr::This(_) => "this".into(),
             Expr::Super(_) => "super".into(),
             Expr::ArrayDeclExpr(a) => a.pretty_print(names),
+            Expr::Match(m) => m.pretty_print(names),
+            Expr::Spread(s) => format!("(Spread {})", s.value.expr.pretty_print(names)),
         }
     }
 }
@@ -71,6 +74,17 @@ impl PrettyPrinter for LogicalExpr {
     }
 }
 
+impl PrettyPrinter for ConditionalExpr {
+    fn pretty_print(&self, names: &IdentifierNames) -> String {
+        format!(
+            "(ConditionalExpr {} ? {} : {})",
+            self.condition.expr.pretty_print(names),
+            self.then_branch.expr.pretty_print(names),
+            self.else_branch.expr.pretty_print(names)
+        )
+    }
+}
+
 impl PrettyPrinter for Literal {
     fn pretty_print(&self, _names: &IdentifierNames) -> String {
         match self {
@@ -146,8 +160,9 @@ impl PrettyPrinter for FuncExpr {
 impl PrettyPrinter for GetExpr {
     fn pretty_print(&self, names: &IdentifierNames) -> String {
         format!(
-            "(GetExpr {}.{})",
+            "(GetExpr {}{}{})",
             self.object.expr.pretty_print(names),
+            if self.optional { "?." } else { "." },
             self.property.pretty_print(names)
         )
     }
@@ -176,6 +191,65 @@ impl PrettyPrinter for ArrayDeclExpr {
     }
 }
 
+impl PrettyPrinter for Pattern {
+    fn pretty_print(&self, names: &IdentifierNames) -> String {
+        let list = |patterns: &[Pattern]| {
+            patterns
+                .iter()
+                .map(|p| p.pretty_print(names))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        match self {
+            Pattern::Wildcard => "_".into(),
+            Pattern::Literal(l) => l.pretty_print(names),
+            Pattern::Binding(id) | Pattern::Constant(id) => id.pretty_print(names),
+            Pattern::Variant(id, fields) => {
+                format!("{}({})", id.pretty_print(names), list(fields))
+            }
+            Pattern::Instance(id, fields) => format!(
+                "{} {{ {} }}",
+                id.pretty_print(names),
+                fields
+                    .iter()
+                    .map(|(field, p)| format!("{}: {}", names[field.name], p.pretty_print(names)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Pattern::Array(values, rest) => match rest {
+                Some(rest) => format!("[{}, ...{}]", list(values), rest.pretty_print(names)),
+                None => format!("[{}]", list(values)),
+            },
+        }
+    }
+}
+
+impl PrettyPrinter for MatchExpr {
+    fn pretty_print(&self, names: &IdentifierNames) -> String {
+        format!(
+            "(MatchExpr {} {})",
+            self.value.expr.pretty_print(names),
+            self.arms
+                .iter()
+                .map(|arm| format!(
+                    "({}{} => {}), ",
+                    arm.pattern.pretty_print(names),
+                    if let Some(guard) = &arm.guard {
+                        format!(" if {}", guard.expr.pretty_print(names))
+                    } else {
+                        String::new()
+                    },
+                    match &arm.body {
+                        MatchArmBody::Expr(expr) => expr.expr.pretty_print(names),
+                        MatchArmBody::Block(block) => block.pretty_print(names),
+                    }
+                ))
+                .collect::<String>()
+        )
+    }
+}
+
 impl PrettyPrinter for Stmt {
     fn pretty_print(&self, names: &IdentifierNames) -> String {
         match self {
@@ -187,6 +261,8 @@ impl PrettyPrinter for Stmt {
             Stmt::While(stmt) => stmt.pretty_print(names),
             Stmt::Return(stmt) => stmt.pretty_print(names),
             Stmt::ClassDecl(stmt) => stmt.pretty_print(names),
+            Stmt::EnumDecl(stmt) => stmt.pretty_print(names),
+            Stmt::DestructuringDecl(stmt) => stmt.pretty_print(names),
         }
     }
 }
@@ -205,18 +281,56 @@ impl PrettyPrinter for ExprStmt {
 
 impl PrettyPrinter for VarDeclStmt {
     fn pretty_print(&self, names: &IdentifierNames) -> String {
+        let identifier = match &self.type_ {
+            Some(type_) => format!(
+                "{}: {}",
+                self.identifier.pretty_print(names),
+                type_.name(names)
+            ),
+            None => self.identifier.pretty_print(names),
+        };
+
         if let Some(init) = &self.initializer {
             format!(
                 "(VarDeclStmt {} <- {:?})",
-                self.identifier.pretty_print(names),
+                identifier,
                 init.expr.pretty_print(names)
             )
         } else {
-            format!("(VarDeclStmt {} <- nil)", self.identifier)
+            format!("(VarDeclStmt {} <- nil)", identifier)
         }
     }
 }
 
+impl PrettyPrinter for DestructuringDeclStmt {
+    fn pretty_print(&self, names: &IdentifierNames) -> String {
+        let target = match &self.target {
+            Destructuring::Array(identifiers, rest) => format!(
+                "[{}{}]",
+                identifiers
+                    .iter()
+                    .map(|identifier| format!("{}, ", identifier.pretty_print(names)))
+                    .collect::<String>(),
+                rest.map_or("".into(), |rest| format!("...{}", rest.pretty_print(names)))
+            ),
+            Destructuring::Object(identifiers) => format!(
+                "{{{}}}",
+                identifiers
+                    .iter()
+                    .map(|identifier| identifier.pretty_print(names))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+
+        format!(
+            "(DestructuringDeclStmt {} <- {})",
+            target,
+            self.initializer.expr.pretty_print(names)
+        )
+    }
+}
+
 impl PrettyPrinter for BlockStmt {
     fn pretty_print(&self, names: &IdentifierNames) -> String {
         let mut block = "".to_string();
@@ -270,12 +384,68 @@ impl PrettyPrinter for ReturnStmt {
 
 impl PrettyPrinter for ClassDeclStmt {
     fn pretty_print(&self, names: &IdentifierNames) -> String {
+        let private = |name: Option<IdentifierUse>| match name {
+            Some(name) if self.private_members.contains(&name.name) => "private ",
+            _ => "",
+        };
+
+        let methods = |prefix: &str, methods: &[FuncExpr]| {
+            methods
+                .iter()
+                .map(|method| {
+                    format!(
+                        "{}{}{}, ",
+                        private(method.name),
+                        prefix,
+                        method.pretty_print(names)
+                    )
+                })
+                .collect::<String>()
+        };
+
         format!(
-            "(ClassDeclStmt {} {})",
+            "(ClassDeclStmt {} {}{}{}{}{})",
             self.identifier.pretty_print(names),
-            self.methods
+            self.fields
                 .iter()
-                .map(|stmt| format!("{}, ", stmt.pretty_print(names)))
+                .map(|field| format!(
+                    "{}{}: {} <- {}, ",
+                    private(Some(field.identifier)),
+                    field.identifier.pretty_print(names),
+                    field.type_.as_ref().map_or("any".into(), |t| t.name(names)),
+                    field
+                        .initializer
+                        .as_ref()
+                        .map_or("nil".into(), |init| init.expr.pretty_print(names))
+                ))
+                .collect::<String>(),
+            methods("static ", &self.static_methods),
+            methods("get ", &self.getters),
+            methods("set ", &self.setters),
+            methods("", &self.methods)
+        )
+    }
+}
+
+impl PrettyPrinter for EnumDeclStmt {
+    fn pretty_print(&self, names: &IdentifierNames) -> String {
+        format!(
+            "(EnumDeclStmt {} {})",
+            self.identifier.pretty_print(names),
+            self.variants
+                .iter()
+                .map(|variant| match &variant.fields {
+                    Some(fields) => format!(
+                        "{}({}), ",
+                        variant.identifier.pretty_print(names),
+                        fields
+                            .iter()
+                            .map(|field| names[field.name].clone())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    None => format!("{}, ", variant.identifier.pretty_print(names)),
+                })
                 .collect::<String>()
         )
     }
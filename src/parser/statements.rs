@@ -1,6 +1,7 @@
-use super::IdentifierUse;
 use super::Position;
+use super::{IdentifierHandle, IdentifierUse};
 use crate::parser::expressions::{ExprCtx, FuncExpr, VarExpr};
+use crate::parser::types::Type;
 
 #[derive(Clone)]
 pub enum Stmt {
@@ -12,6 +13,8 @@ pub enum Stmt {
     While(WhileStmt),
     Return(ReturnStmt),
     ClassDecl(ClassDeclStmt),
+    EnumDecl(EnumDeclStmt),
+    DestructuringDecl(DestructuringDeclStmt),
 }
 
 // expression statement aka an expression followed by ;
@@ -41,14 +44,53 @@ impl PrintStmt {
 #[derive(Clone)]
 pub struct VarDeclStmt {
     pub identifier: IdentifierUse,
+    pub type_: Option<Type>,
     pub initializer: Option<ExprCtx>,
     pub pos: Position,
 }
 
 impl VarDeclStmt {
-    pub fn to_stmt(identifier: IdentifierUse, initializer: Option<ExprCtx>, pos: Position) -> Stmt {
+    pub fn to_stmt(
+        identifier: IdentifierUse,
+        type_: Option<Type>,
+        initializer: Option<ExprCtx>,
+        pos: Position,
+    ) -> Stmt {
         Stmt::VarDecl(VarDeclStmt {
             identifier,
+            type_,
+            initializer,
+            pos,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub enum Destructuring {
+    Array(Vec<IdentifierUse>, Option<IdentifierUse>), // [a, b, ...rest]
+    Object(Vec<IdentifierUse>),                       // {x, y}
+}
+
+impl Destructuring {
+    pub fn identifiers(&self) -> Vec<IdentifierUse> {
+        match self {
+            Destructuring::Array(names, rest) => names.iter().chain(rest).copied().collect(),
+            Destructuring::Object(names) => names.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DestructuringDeclStmt {
+    pub target: Destructuring,
+    pub initializer: ExprCtx,
+    pub pos: Position,
+}
+
+impl DestructuringDeclStmt {
+    pub fn to_stmt(target: Destructuring, initializer: ExprCtx, pos: Position) -> Stmt {
+        Stmt::DestructuringDecl(DestructuringDeclStmt {
+            target,
             initializer,
             pos,
         })
@@ -64,7 +106,11 @@ pub struct BlockStmt {
 
 impl BlockStmt {
     pub fn to_stmt(stmts: Vec<Stmt>, start_pos: Position, end_pos: Position) -> Stmt {
-        Stmt::Block(BlockStmt { stmts, start_pos, end_pos })
+        Stmt::Block(BlockStmt {
+            stmts,
+            start_pos,
+            end_pos,
+        })
     }
 }
 
@@ -116,11 +162,34 @@ impl ReturnStmt {
     }
 }
 
+// x: number; declares the type of a field, count = 0; initializes it before init is called
+#[derive(Clone)]
+pub struct FieldDecl {
+    pub identifier: IdentifierUse,
+    pub type_: Option<Type>,
+    pub initializer: Option<ExprCtx>,
+}
+
+#[derive(Clone, Default)]
+pub struct ClassBody {
+    pub fields: Vec<FieldDecl>,
+    pub methods: Vec<FuncExpr>,
+    pub static_methods: Vec<FuncExpr>,
+    pub getters: Vec<FuncExpr>,
+    pub setters: Vec<FuncExpr>,
+    pub private_members: Vec<IdentifierHandle>,
+}
+
 #[derive(Clone)]
 pub struct ClassDeclStmt {
     pub identifier: IdentifierUse,
     pub superclass: Option<VarExpr>,
+    pub fields: Vec<FieldDecl>,
     pub methods: Vec<FuncExpr>,
+    pub static_methods: Vec<FuncExpr>,
+    pub getters: Vec<FuncExpr>,
+    pub setters: Vec<FuncExpr>,
+    pub private_members: Vec<IdentifierHandle>,
     pub pos: Position,
 }
 
@@ -129,12 +198,41 @@ impl ClassDeclStmt {
         pos: Position,
         identifier: IdentifierUse,
         superclass: Option<VarExpr>,
-        methods: Vec<FuncExpr>,
+        body: ClassBody,
     ) -> Stmt {
         Stmt::ClassDecl(ClassDeclStmt {
             identifier,
             superclass,
-            methods,
+            fields: body.fields,
+            methods: body.methods,
+            static_methods: body.static_methods,
+            getters: body.getters,
+            setters: body.setters,
+            private_members: body.private_members,
+            pos,
+        })
+    }
+}
+
+// a variant without fields (None) is a value, not a constructor
+#[derive(Clone)]
+pub struct EnumVariant {
+    pub identifier: IdentifierUse,
+    pub fields: Option<Vec<IdentifierUse>>,
+}
+
+#[derive(Clone)]
+pub struct EnumDeclStmt {
+    pub identifier: IdentifierUse,
+    pub variants: Vec<EnumVariant>,
+    pub pos: Position,
+}
+
+impl EnumDeclStmt {
+    pub fn to_stmt(pos: Position, identifier: IdentifierUse, variants: Vec<EnumVariant>) -> Stmt {
+        Stmt::EnumDecl(EnumDeclStmt {
+            identifier,
+            variants,
             pos,
         })
     }
@@ -0,0 +1,66 @@
+use super::{Identifier, IdentifierHandle, IdentifierNames};
+use std::rc::Rc;
+
+// Optional type annotations, they are only used by the type checker
+// and never change how a program runs
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Type {
+    Any,
+    Nil,
+    Boolean,
+    Number,
+    String,
+    Named(IdentifierHandle, Vec<Type>), // instances of classes and enums: Point, Array<number>
+    Class(IdentifierHandle),            // a class itself, not expressible in annotations
+    Function(Rc<FunctionType>),         // fun(number, string): bool
+    Nullable(Box<Type>),                // number?
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionType {
+    pub params: Vec<Type>,
+    pub required: usize, // the other parameters have default values
+    pub rest: bool,      // the last parameter is a rest parameter
+    pub ret: Type,
+}
+
+impl Type {
+    pub fn array(elements: Type) -> Type {
+        Type::Named(Identifier::array(), vec![elements])
+    }
+
+    pub fn function(params: Vec<Type>, ret: Type) -> Type {
+        Type::Function(Rc::new(FunctionType {
+            required: params.len(),
+            params,
+            rest: false,
+            ret,
+        }))
+    }
+
+    pub fn name(&self, names: &IdentifierNames) -> String {
+        let list = |types: &[Type]| {
+            types
+                .iter()
+                .map(|t| t.name(names))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        match self {
+            Type::Any => "any".into(),
+            Type::Nil => "nil".into(),
+            Type::Boolean => "bool".into(),
+            Type::Number => "number".into(),
+            Type::String => "string".into(),
+            Type::Named(name, args) if args.is_empty() => names[*name].clone(),
+            Type::Named(name, args) => format!("{}<{}>", names[*name], list(args)),
+            Type::Class(name) => format!("class {}", names[*name]),
+            Type::Function(func) => {
+                format!("fun({}): {}", list(&func.params), func.ret.name(names))
+            }
+            Type::Nullable(t) => format!("{}?", t.name(names)),
+        }
+    }
+}
@@ -0,0 +1,906 @@
+use super::resolutions::Resolutions;
+use super::BUILTIN_ENUMS;
+use crate::parser::{
+    expressions::{Expr, ExprCtx, FuncExpr, FuncParam, MatchArmBody, MatchExpr, Pattern, VarExpr},
+    statements::{ClassDeclStmt, Destructuring, Stmt},
+    Identifier, IdentifierHandle, IdentifierNames, IdentifierUse, IdentifierUseHandle,
+};
+use crate::scanner::scanner_result::ErrorPosition;
+use crate::scanner::token::Position;
+use fnv::FnvHashMap;
+use std::collections::hash_map::Entry;
+use std::fmt;
+use std::rc::Rc;
+
+pub type LexicalScopeResolutionResult = Result<(), LexicalScopeResolutionError>;
+
+#[derive(Clone, Copy, Debug)]
+pub enum IdentifierStatus {
+    Undeclared,
+    Declared,
+    Defined,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum FunctionType {
+    Outside,
+    Function,
+    Initializer,
+    Method,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum ClassType {
+    NotAClass,
+    Class,
+    Subclass,
+}
+
+// the members a class declares, used to check accesses to its private ones
+struct ClassMembersInfo {
+    superclass: Option<IdentifierHandle>,
+    public: Vec<IdentifierHandle>,
+    private: Vec<IdentifierHandle>,
+}
+
+pub struct Resolver {
+    scopes: Vec<FnvHashMap<IdentifierHandle, IdentifierStatus>>,
+    // arities of the functions declared in each scope, the first one is global
+    functions: Vec<FnvHashMap<IdentifierHandle, Vec<(usize, usize)>>>,
+    depths: FnvHashMap<IdentifierUseHandle, usize>,
+    pub func_type: FunctionType,
+    pub class_type: ClassType,
+    pub class_name: Option<IdentifierHandle>,
+    class_declaration: Option<IdentifierUseHandle>, // two classes can share a name
+    names: Rc<IdentifierNames>,
+    enums: FnvHashMap<IdentifierHandle, Vec<IdentifierHandle>>, // enum -> variants
+    variant_enums: FnvHashMap<IdentifierHandle, IdentifierHandle>, // variant -> enum
+    classes: FnvHashMap<IdentifierHandle, ClassMembersInfo>,
+    // the declaration of the class enclosing each property access,
+    // private members can only be accessed from it
+    access_classes: FnvHashMap<IdentifierUseHandle, IdentifierUseHandle>,
+    warnings: Vec<LexicalScopeResolutionWarning>,
+    resolutions: Resolutions,
+}
+
+impl Resolver {
+    pub fn new(names: &Rc<IdentifierNames>) -> Resolver {
+        let mut resolver = Resolver {
+            scopes: Vec::new(),
+            functions: vec![FnvHashMap::default()],
+            depths: FnvHashMap::default(),
+            func_type: FunctionType::Outside,
+            class_type: ClassType::NotAClass,
+            class_name: None,
+            class_declaration: None,
+            names: Rc::clone(names),
+            enums: FnvHashMap::default(),
+            variant_enums: FnvHashMap::default(),
+            classes: FnvHashMap::default(),
+            access_classes: FnvHashMap::default(),
+            warnings: Vec::new(),
+            resolutions: Resolutions::new(),
+        };
+
+        resolver.declare_builtin_enums();
+        resolver
+    }
+
+    fn declare_builtin_enums(&mut self) {
+        let names = Rc::clone(&self.names);
+        let handle = |name: &str| names.iter().position(|n| n == name);
+
+        for (name, variants) in BUILTIN_ENUMS {
+            let variants = variants.iter().map(|(variant, _)| handle(variant)).collect();
+
+            if let (Some(name), Some(variants)) = (handle(name), variants) {
+                self.declare_enum(name, variants);
+            }
+        }
+    }
+
+    fn declare_enum(&mut self, name: IdentifierHandle, variants: Vec<IdentifierHandle>) {
+        for variant in &variants {
+            self.variant_enums.insert(*variant, name);
+        }
+
+        self.enums.insert(name, variants);
+    }
+
+    pub fn warnings(&self) -> &[LexicalScopeResolutionWarning] {
+        &self.warnings
+    }
+
+    // the slots of the variables, for the VM compiler
+    pub fn resolutions(&self) -> &Resolutions {
+        &self.resolutions
+    }
+
+    pub fn depth(&self, id: IdentifierUseHandle) -> Option<&usize> {
+        self.depths.get(&id)
+    }
+
+    pub fn access_class(&self, id: IdentifierUseHandle) -> Option<IdentifierUseHandle> {
+        self.access_classes.get(&id).copied()
+    }
+
+    fn declare_class_members(&mut self, class_decl: &ClassDeclStmt) {
+        let public = class_decl
+            .methods
+            .iter()
+            .chain(&class_decl.static_methods)
+            .chain(&class_decl.getters)
+            .chain(&class_decl.setters)
+            .filter_map(|method| method.name.map(|name| name.name))
+            .chain(class_decl.fields.iter().map(|field| field.identifier.name))
+            .filter(|name| !class_decl.private_members.contains(name))
+            .collect();
+
+        self.classes.insert(
+            class_decl.identifier.name,
+            ClassMembersInfo {
+                superclass: class_decl
+                    .superclass
+                    .as_ref()
+                    .map(|superclass| superclass.identifier.name),
+                public,
+                private: class_decl.private_members.clone(),
+            },
+        );
+    }
+
+    // the class declaring 'member' as private, looking up from 'class' through its superclasses
+    fn private_owner(
+        &self,
+        mut class: Option<IdentifierHandle>,
+        member: IdentifierHandle,
+    ) -> Option<IdentifierHandle> {
+        while let Some(info) = class.and_then(|name| self.classes.get(&name)) {
+            if info.private.contains(&member) {
+                return class;
+            }
+
+            if info.public.contains(&member) {
+                return None;
+            }
+
+            class = info.superclass;
+        }
+
+        None
+    }
+
+    fn resolve_property_access(
+        &mut self,
+        object: &ExprCtx,
+        property: &IdentifierUse,
+    ) -> LexicalScopeResolutionResult {
+        let (class_name, declaration) = match (self.class_name, self.class_declaration) {
+            (Some(class_name), Some(declaration)) => (class_name, declaration),
+            _ => return Ok(()),
+        };
+
+        self.access_classes.insert(property.use_handle, declaration);
+
+        // the class of 'this' is known statically, other receivers are checked at runtime
+        let receiver_class = match &object.expr {
+            Expr::This(_) => Some(class_name),
+            _ => return Ok(()),
+        };
+
+        self.check_private_access(receiver_class, property)
+    }
+
+    fn check_private_access(
+        &self,
+        receiver_class: Option<IdentifierHandle>,
+        property: &IdentifierUse,
+    ) -> LexicalScopeResolutionResult {
+        match self.private_owner(receiver_class, property.name) {
+            Some(owner) if Some(owner) != self.class_name => {
+                Err(LexicalScopeResolutionError::PrivateMemberAccess(
+                    property.pos,
+                    self.name(property.name),
+                    self.name(owner),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(FnvHashMap::default());
+        self.functions.push(FnvHashMap::default());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+        self.functions.pop();
+        self.resolutions.end_scope(self.scopes.len());
+    }
+
+    // Ok(true) if func overloads a function previously declared in the same scope
+    fn declare_overload(&mut self, func: &FuncExpr) -> Result<bool, LexicalScopeResolutionError> {
+        let identifier = func.name.unwrap();
+        let overloads = self
+            .functions
+            .last_mut()
+            .unwrap()
+            .entry(identifier.name)
+            .or_default();
+
+        let is_overload = !overloads.is_empty();
+        let (min, max) = func.arity();
+
+        // both could be called with the same number of arguments
+        if overloads
+            .iter()
+            .any(|&(other_min, other_max)| min <= other_max && other_min <= max)
+        {
+            return Err(LexicalScopeResolutionError::AmbiguousOverload(
+                func.pos,
+                self.name(identifier.name),
+            ));
+        }
+
+        overloads.push((min, max));
+
+        Ok(is_overload)
+    }
+
+    fn declare(&mut self, identifier: IdentifierUse) -> LexicalScopeResolutionResult {
+        if self.scopes.is_empty() {
+            return Ok(());
+        }
+
+        let len = self.scopes.len();
+
+        match self.scopes[len - 1].entry(identifier.name) {
+            Entry::Vacant(v) => {
+                v.insert(IdentifierStatus::Declared);
+                self.resolutions.declare(&identifier, len);
+                Ok(())
+            }
+            Entry::Occupied(_) => Err(LexicalScopeResolutionError::DuplicateVariableDeclaration(
+                identifier.pos,
+                self.name(identifier.name),
+            )),
+        }
+    }
+
+    fn define(&mut self, identifier: IdentifierHandle) {
+        if self.scopes.is_empty() {
+            return;
+        }
+
+        let len = self.scopes.len();
+
+        self.scopes[len - 1].insert(identifier, IdentifierStatus::Defined);
+    }
+
+    fn get_scoped_identifier_status(&self, identifier: &IdentifierHandle) -> IdentifierStatus {
+        if let Some(scope) = self.scopes.last() {
+            if let Some(status) = scope.get(identifier) {
+                return *status;
+            }
+        }
+
+        IdentifierStatus::Undeclared
+    }
+
+    fn resolve_local(&mut self, identifier: &IdentifierUse) {
+        self.resolutions.resolve(identifier);
+
+        for i in 0..self.scopes.len() {
+            if self.scopes[i].contains_key(&identifier.name) {
+                self.depths
+                    .insert(identifier.use_handle, self.scopes.len() - 1 - i);
+                return;
+            }
+        }
+    }
+
+    fn resolve_function(
+        &mut self,
+        func: &FuncExpr,
+        type_: FunctionType,
+    ) -> LexicalScopeResolutionResult {
+        if let None = func.name {
+            if let FunctionType::Method = type_ {
+                return Err(LexicalScopeResolutionError::AnonymousClassMethod(
+                    func.pos,
+                    self.name(self.class_name.unwrap()),
+                ));
+            }
+        }
+
+        let enclosing_func_type = self.func_type;
+        self.func_type = type_;
+
+        self.resolutions.begin_function();
+        self.begin_scope();
+
+        if let Some(params) = &func.params {
+            for param in params.iter() {
+                let identifier = param.identifier();
+                self.declare(*identifier)?;
+                self.define(identifier.name);
+                if let FuncParam::DefaultValued(_, expr) = param {
+                    expr.resolve(self)?;
+                }
+            }
+        }
+
+        for stmt in &func.body {
+            stmt.resolve(self)?;
+        }
+        self.end_scope();
+        self.resolutions.end_function(func);
+        self.func_type = enclosing_func_type;
+        Ok(())
+    }
+
+    fn resolve_pattern(&mut self, pattern: &Pattern) -> LexicalScopeResolutionResult {
+        match pattern {
+            Pattern::Wildcard | Pattern::Literal(_) => Ok(()),
+            Pattern::Binding(identifier) => {
+                self.declare(*identifier)?;
+                self.define(identifier.name);
+                Ok(())
+            }
+            Pattern::Constant(identifier) => {
+                self.resolve_local(identifier);
+                Ok(())
+            }
+            Pattern::Variant(identifier, patterns) => {
+                self.resolve_local(identifier);
+                for pattern in patterns {
+                    self.resolve_pattern(pattern)?;
+                }
+                Ok(())
+            }
+            Pattern::Instance(identifier, fields) => {
+                self.resolve_local(identifier);
+                for (_, pattern) in fields {
+                    self.resolve_pattern(pattern)?;
+                }
+                Ok(())
+            }
+            Pattern::Array(patterns, rest) => {
+                for pattern in patterns {
+                    self.resolve_pattern(pattern)?;
+                }
+                if let Some(rest) = rest {
+                    self.resolve_pattern(rest)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn resolve_match(&mut self, match_expr: &MatchExpr, pos: Position) -> LexicalScopeResolutionResult {
+        match_expr.value.resolve(self)?;
+
+        for arm in &match_expr.arms {
+            self.begin_scope();
+            self.resolve_pattern(&arm.pattern)?;
+
+            if let Some(guard) = &arm.guard {
+                guard.resolve(self)?;
+            }
+
+            match &arm.body {
+                MatchArmBody::Expr(expr) => expr.resolve(self)?,
+                MatchArmBody::Block(block) => {
+                    self.begin_scope();
+                    for stmt in &block.stmts {
+                        stmt.resolve(self)?;
+                    }
+                    self.end_scope();
+                }
+            }
+
+            self.end_scope();
+        }
+
+        self.check_exhaustiveness(match_expr, pos);
+
+        Ok(())
+    }
+
+    // warns when a match on an enum doesn't handle all of its variants
+    fn check_exhaustiveness(&mut self, match_expr: &MatchExpr, pos: Position) {
+        let mut matched_enum = None;
+        let mut covered = Vec::new();
+
+        for arm in &match_expr.arms {
+            let (name, irrefutable_fields) = match &arm.pattern {
+                Pattern::Wildcard | Pattern::Binding(_) => {
+                    if arm.guard.is_none() {
+                        return;
+                    }
+                    continue;
+                }
+                Pattern::Constant(identifier) => (identifier.name, true),
+                Pattern::Variant(identifier, patterns) => {
+                    (identifier.name, patterns.iter().all(Pattern::is_irrefutable))
+                }
+                Pattern::Instance(identifier, fields) => (
+                    identifier.name,
+                    fields.iter().all(|(_, pattern)| pattern.is_irrefutable()),
+                ),
+                _ => continue,
+            };
+
+            let covers_all = irrefutable_fields && arm.guard.is_none();
+
+            // matching the enum class itself covers every variant
+            if self.enums.contains_key(&name) && covers_all {
+                return;
+            }
+
+            if let Some(&enum_name) = self.variant_enums.get(&name) {
+                matched_enum.get_or_insert(enum_name);
+
+                if covers_all {
+                    covered.push(name);
+                }
+            }
+        }
+
+        if let Some(enum_name) = matched_enum {
+            let missing = self.enums[&enum_name]
+                .iter()
+                .filter(|variant| !covered.contains(variant))
+                .map(|variant| self.name(*variant))
+                .collect::<Vec<_>>();
+
+            if !missing.is_empty() {
+                self.warnings.push(LexicalScopeResolutionWarning::NonExhaustiveMatch(
+                    pos,
+                    self.name(enum_name),
+                    missing,
+                ));
+            }
+        }
+    }
+
+    pub fn resolve(&mut self, statements: &Vec<Stmt>) -> LexicalScopeResolutionResult {
+        for stmt in statements {
+            stmt.resolve(self)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn name(&self, handle: IdentifierHandle) -> std::string::String {
+        self.names[handle].clone()
+    }
+}
+
+pub trait LexicallyScoped {
+    fn resolve(&self, resolver: &mut Resolver) -> LexicalScopeResolutionResult;
+}
+
+impl LexicallyScoped for Stmt {
+    fn resolve(&self, resolver: &mut Resolver) -> LexicalScopeResolutionResult {
+        match self {
+            Stmt::Block(block) => {
+                resolver.begin_scope();
+                for stmt in &block.stmts {
+                    stmt.resolve(resolver)?;
+                }
+                resolver.end_scope();
+                Ok(())
+            }
+            Stmt::VarDecl(decl) => {
+                resolver.declare(decl.identifier)?;
+                if let Some(init) = &decl.initializer {
+                    init.resolve(resolver)?;
+                }
+                resolver.define(decl.identifier.name);
+                Ok(())
+            }
+            Stmt::DestructuringDecl(decl) => {
+                let identifiers = decl.target.identifiers();
+
+                for identifier in &identifiers {
+                    resolver.declare(*identifier)?;
+                }
+
+                decl.initializer.resolve(resolver)?;
+
+                // {x, y} reads properties of the initializer
+                if let (Destructuring::Object(fields), Some(declaration)) =
+                    (&decl.target, resolver.class_declaration)
+                {
+                    for field in fields {
+                        resolver
+                            .access_classes
+                            .insert(field.use_handle, declaration);
+                    }
+                }
+
+                for identifier in &identifiers {
+                    resolver.define(identifier.name);
+                }
+
+                Ok(())
+            }
+            Stmt::Expr(stmt) => stmt.expr.resolve(resolver),
+            Stmt::If(if_stmt) => {
+                if_stmt.condition.resolve(resolver)?;
+                if_stmt.then_branch.resolve(resolver)?;
+                if let Some(else_branch) = &if_stmt.else_branch {
+                    else_branch.resolve(resolver)?;
+                }
+                Ok(())
+            }
+            Stmt::Print(print_stmt) => print_stmt.value.resolve(resolver),
+            Stmt::Return(ret_stmt) => {
+                match resolver.func_type {
+                    FunctionType::Outside => {
+                        return Err(LexicalScopeResolutionError::ReturnKeywordOutsideFunction(
+                            ret_stmt.pos,
+                        ));
+                    }
+                    FunctionType::Initializer => {
+                        return Err(LexicalScopeResolutionError::CannotReturnInsideInitializer(
+                            ret_stmt.pos,
+                            resolver.name(resolver.class_name.unwrap()),
+                        ));
+                    }
+                    _ => {}
+                }
+
+                if let Some(expr) = &ret_stmt.value {
+                    expr.resolve(resolver)?;
+                }
+                Ok(())
+            }
+            Stmt::While(while_stmt) => {
+                while_stmt.condition.resolve(resolver)?;
+                while_stmt.body.resolve(resolver)?;
+                Ok(())
+            }
+            Stmt::ClassDecl(class_decl) => {
+                let enclosing_class = resolver.class_type;
+                let enclosing_class_name = resolver.class_name;
+                let enclosing_class_declaration = resolver.class_declaration;
+                resolver.class_type = ClassType::Class;
+                resolver.class_name = Some(class_decl.identifier.name);
+                resolver.class_declaration = Some(class_decl.identifier.use_handle);
+                resolver.declare_class_members(class_decl);
+
+                resolver.declare(class_decl.identifier)?;
+                resolver.define(class_decl.identifier.name);
+
+                if let Some(superclass) = &class_decl.superclass {
+                    resolver.class_type = ClassType::Subclass;
+                    if superclass.identifier.name != class_decl.identifier.name {
+                        superclass.resolve(resolver)?;
+                    } else {
+                        return Err(LexicalScopeResolutionError::ClassCannotInheritFromItself(
+                            class_decl.identifier.pos,
+                            resolver.name(class_decl.identifier.name),
+                        ));
+                    }
+
+                    resolver.begin_scope();
+                    resolver.define(Identifier::super_());
+                }
+
+                resolver.begin_scope();
+                resolver.define(Identifier::this());
+
+                // methods are declared in the class scope to detect ambiguous overloads
+                for method in &class_decl.methods {
+                    if method.name.is_some() {
+                        resolver.declare_overload(method)?;
+                    }
+                }
+
+                for field in &class_decl.fields {
+                    if let Some(initializer) = &field.initializer {
+                        initializer.resolve(resolver)?;
+                    }
+                }
+
+                // 'this' is the class itself in static methods
+                for accessor in class_decl
+                    .static_methods
+                    .iter()
+                    .chain(&class_decl.getters)
+                    .chain(&class_decl.setters)
+                {
+                    resolver.resolve_function(accessor, FunctionType::Method)?;
+                }
+
+                for method in &class_decl.methods {
+                    if let Some(method_handle) = method.name {
+                        let func_type = if method_handle.name == Identifier::init() {
+                            FunctionType::Initializer
+                        } else {
+                            FunctionType::Method
+                        };
+                        resolver.resolve_function(&method, func_type)?;
+                    } else {
+                        return Err(LexicalScopeResolutionError::AnonymousClassMethod(
+                            method.pos,
+                            resolver.name(resolver.class_name.unwrap()),
+                        ));
+                    }
+                }
+
+                resolver.end_scope();
+                if let Some(_) = class_decl.superclass {
+                    resolver.end_scope();
+                }
+
+                resolver.class_type = enclosing_class;
+                resolver.class_name = enclosing_class_name;
+                resolver.class_declaration = enclosing_class_declaration;
+
+                Ok(())
+            }
+            Stmt::EnumDecl(enum_decl) => {
+                resolver.declare(enum_decl.identifier)?;
+                resolver.define(enum_decl.identifier.name);
+
+                for variant in &enum_decl.variants {
+                    resolver.declare(variant.identifier)?;
+                    resolver.define(variant.identifier.name);
+                }
+
+                resolver.declare_enum(
+                    enum_decl.identifier.name,
+                    enum_decl
+                        .variants
+                        .iter()
+                        .map(|variant| variant.identifier.name)
+                        .collect(),
+                );
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl LexicallyScoped for VarExpr {
+    fn resolve(&self, resolver: &mut Resolver) -> LexicalScopeResolutionResult {
+        match resolver.get_scoped_identifier_status(&self.identifier.name) {
+            IdentifierStatus::Declared => {
+                Err(LexicalScopeResolutionError::VariableUsedInItsInitializer(
+                    self.identifier.pos,
+                    resolver.name(self.identifier.name),
+                ))
+            }
+            _ => {
+                resolver.resolve_local(&self.identifier);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl LexicallyScoped for ExprCtx {
+    fn resolve(&self, resolver: &mut Resolver) -> LexicalScopeResolutionResult {
+        match &self.expr {
+            Expr::Var(expr) => expr.resolve(resolver),
+            Expr::Assign(assignment) => {
+                assignment.expr.resolve(resolver)?;
+                resolver.resolve_local(&assignment.identifier);
+                Ok(())
+            }
+            Expr::Func(func) => {
+                if let Some(identifier) = func.name {
+                    if resolver.declare_overload(func)? {
+                        resolver.resolutions.overload(&identifier);
+                    } else {
+                        resolver.declare(identifier)?;
+                    }
+                    resolver.define(identifier.name);
+                }
+
+                resolver.resolve_function(&func, FunctionType::Function)?;
+
+                Ok(())
+            }
+            Expr::Binary(bin) => {
+                bin.left.resolve(resolver)?;
+                bin.right.resolve(resolver)?;
+                Ok(())
+            }
+            Expr::Call(call) => {
+                call.callee.resolve(resolver)?;
+                for arg in &call.args {
+                    arg.resolve(resolver)?;
+                }
+                Ok(())
+            }
+            Expr::Grouping(group) => group.expression.resolve(resolver),
+            Expr::Literal(_) => Ok(()),
+            Expr::Logical(op) => {
+                op.left.resolve(resolver)?;
+                op.right.resolve(resolver)?;
+                Ok(())
+            }
+            Expr::Conditional(cond) => {
+                cond.condition.resolve(resolver)?;
+                cond.then_branch.resolve(resolver)?;
+                cond.else_branch.resolve(resolver)?;
+                Ok(())
+            }
+            Expr::Unary(unary) => unary.right.resolve(resolver),
+            Expr::Get(get) => {
+                get.object.resolve(resolver)?;
+                resolver.resolve_property_access(&get.object, &get.property)
+            }
+            Expr::Set(set) => {
+                set.object.resolve(resolver)?;
+                set.value.resolve(resolver)?;
+                resolver.resolve_property_access(&set.object, &set.property)
+            }
+            Expr::This(this_expr) => {
+                if let ClassType::NotAClass = resolver.class_type {
+                    return Err(LexicalScopeResolutionError::CannotUseThisOutsideOfAClass(
+                        this_expr.identifier.pos,
+                    ));
+                }
+
+                resolver.resolve_local(&this_expr.identifier);
+                Ok(())
+            }
+            Expr::Super(super_expr) => match resolver.class_type {
+                ClassType::NotAClass => {
+                    return Err(LexicalScopeResolutionError::CannotUseSuperOutsideAclass(
+                        super_expr.identifier.pos,
+                    ))
+                }
+                ClassType::Class => {
+                    return Err(
+                        LexicalScopeResolutionError::CannotUseSuperInAClassWithNoSuperClass(
+                            super_expr.identifier.pos,
+                            resolver.name(resolver.class_name.unwrap()),
+                        ),
+                    )
+                }
+                ClassType::Subclass => {
+                    resolver.resolve_local(&super_expr.identifier);
+
+                    let class_name = resolver.class_name.unwrap();
+                    let superclass = resolver
+                        .classes
+                        .get(&class_name)
+                        .and_then(|info| info.superclass);
+
+                    if let Some(declaration) = resolver.class_declaration {
+                        resolver
+                            .access_classes
+                            .insert(super_expr.method.use_handle, declaration);
+                    }
+                    resolver.check_private_access(superclass, &super_expr.method)
+                }
+            },
+            Expr::ArrayDeclExpr(array_decl) => {
+                for val in &array_decl.values {
+                    val.resolve(resolver)?;
+                }
+
+                Ok(())
+            }
+            Expr::Match(match_expr) => resolver.resolve_match(match_expr, self.pos),
+            Expr::Spread(spread) => spread.value.resolve(resolver),
+        }
+    }
+}
+
+pub enum LexicalScopeResolutionError {
+    VariableUsedInItsInitializer(Position, String),
+    DuplicateVariableDeclaration(Position, String),
+    ReturnKeywordOutsideFunction(Position),
+    AnonymousClassMethod(Position, String),
+    CannotUseThisOutsideOfAClass(Position),
+    CannotReturnInsideInitializer(Position, String),
+    ClassCannotInheritFromItself(Position, String),
+    CannotUseSuperOutsideAclass(Position),
+    CannotUseSuperInAClassWithNoSuperClass(Position, String),
+    AmbiguousOverload(Position, String),
+    PrivateMemberAccess(Position, String, String),
+}
+
+impl fmt::Display for LexicalScopeResolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use LexicalScopeResolutionError::*;
+        match self {
+            VariableUsedInItsInitializer(_, name) => write!(
+                f,
+                "Cannot read local variable '{}' in its own initializer",
+                name
+            ),
+            DuplicateVariableDeclaration(_, name) => {
+                write!(f, "Duplicate variable declaration for '{}'", name)
+            }
+            ReturnKeywordOutsideFunction(_) => {
+                write!(f, "'return' keyword found outside of a function body")
+            }
+            AnonymousClassMethod(_, name) => {
+                write!(f, "A class method cannot be anonymous: in class '{}'", name)
+            }
+            CannotUseThisOutsideOfAClass(_) => {
+                write!(f, "Cannot use the 'this' keyword outside of a class")
+            }
+            CannotReturnInsideInitializer(_, name) => write!(
+                f,
+                "Cannot use the 'return' keyword inside an initializer, in class '{}'",
+                name
+            ),
+            ClassCannotInheritFromItself(_, name) => {
+                write!(f, "Class '{}' cannot inherit from itself", name)
+            }
+            CannotUseSuperOutsideAclass(_) => write!(f, "Cannot use 'super' outside of a class"),
+            CannotUseSuperInAClassWithNoSuperClass(_, name) => write!(
+                f,
+                "Cannot use 'super' in class '{}' which has no superclass",
+                name
+            ),
+            AmbiguousOverload(_, name) => write!(
+                f,
+                "Ambiguous overload of '{}': another declaration accepts the same number of arguments",
+                name
+            ),
+            PrivateMemberAccess(_, name, class) => write!(
+                f,
+                "Cannot access private member '{}' outside of class '{}'",
+                name, class
+            ),
+        }
+    }
+}
+
+impl ErrorPosition for LexicalScopeResolutionError {
+    fn position(&self) -> &Position {
+        use LexicalScopeResolutionError::*;
+        match self {
+            VariableUsedInItsInitializer(pos, _)
+            | DuplicateVariableDeclaration(pos, _)
+            | ReturnKeywordOutsideFunction(pos)
+            | AnonymousClassMethod(pos, _)
+            | CannotUseThisOutsideOfAClass(pos)
+            | CannotReturnInsideInitializer(pos, _)
+            | ClassCannotInheritFromItself(pos, _)
+            | CannotUseSuperOutsideAclass(pos)
+            | CannotUseSuperInAClassWithNoSuperClass(pos, _)
+            | AmbiguousOverload(pos, _)
+            | PrivateMemberAccess(pos, _, _) => pos,
+        }
+    }
+}
+
+pub enum LexicalScopeResolutionWarning {
+    NonExhaustiveMatch(Position, String, Vec<String>),
+}
+
+impl fmt::Display for LexicalScopeResolutionWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use LexicalScopeResolutionWarning::*;
+        match self {
+            NonExhaustiveMatch(_, name, missing) => write!(
+                f,
+                "Non-exhaustive match on enum '{}', missing variants: {}",
+                name,
+                missing.join(", ")
+            ),
+        }
+    }
+}
+
+impl ErrorPosition for LexicalScopeResolutionWarning {
+    fn position(&self) -> &Position {
+        use LexicalScopeResolutionWarning::*;
+        match self {
+            NonExhaustiveMatch(pos, _, _) => pos,
+        }
+    }
+}
@@ -0,0 +1,19 @@
+pub mod lexical_scope;
+pub mod resolutions;
+
+// The semantic analysis shared by the tree-walker, the VM and the WASM target:
+// scoping errors are reported before a program runs and every variable use is
+// resolved to the scope, stack slot or upvalue it refers to.
+
+pub type BuiltinEnum = (
+    &'static str,
+    &'static [(&'static str, Option<&'static [&'static str]>)],
+);
+
+pub const BUILTIN_ENUMS: &[BuiltinEnum] = &[
+    ("Option", &[("Some", Some(&["value"])), ("None", None)]),
+    (
+        "Result",
+        &[("Ok", Some(&["value"])), ("Err", Some(&["error"]))],
+    ),
+];
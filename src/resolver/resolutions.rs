@@ -0,0 +1,167 @@
+use crate::parser::{expressions::FuncExpr, IdentifierHandle, IdentifierUse, IdentifierUseHandle};
+use fnv::{FnvHashMap, FnvHashSet};
+
+// Where each variable lives for the backends keeping locals on a stack.
+// Slot 0 of a call frame holds the called function, its locals start at 1.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Variable {
+    Global,
+    Local(usize), // slot
+    Cell(usize),  // slot of a local captured by a closure, it holds a cell shared with it
+    Upvalue(usize),
+}
+
+// what a closure captures when it is created, from the frame of the enclosing function
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Capture {
+    Local(usize), // the cell in a slot
+    Upvalue(usize),
+}
+
+struct Local {
+    name: IdentifierHandle,
+    depth: usize,
+    declaration: IdentifierUseHandle,
+}
+
+// the locals in scope and the upvalues of a function being resolved
+#[derive(Default)]
+struct Frame {
+    locals: Vec<Local>,
+    upvalues: Vec<Capture>,
+}
+
+pub struct Resolutions {
+    locals: FnvHashMap<IdentifierUseHandle, (usize, IdentifierUseHandle)>, // slot, declaration
+    upvalues: FnvHashMap<IdentifierUseHandle, usize>,
+    captured: FnvHashSet<IdentifierUseHandle>, // declarations
+    overloads: FnvHashSet<IdentifierUseHandle>, // declarations adding to an overload set
+    captures: FnvHashMap<IdentifierUseHandle, Vec<Capture>>, // function handle -> captures
+    frames: Vec<Frame>,                        // the first one is the script
+}
+
+impl Resolutions {
+    pub(super) fn new() -> Resolutions {
+        Resolutions {
+            locals: FnvHashMap::default(),
+            upvalues: FnvHashMap::default(),
+            captured: FnvHashSet::default(),
+            overloads: FnvHashSet::default(),
+            captures: FnvHashMap::default(),
+            frames: vec![Frame::default()],
+        }
+    }
+
+    // declarations resolve to the variable they declare
+    pub fn variable(&self, identifier: &IdentifierUse) -> Variable {
+        if let Some(&(slot, declaration)) = self.locals.get(&identifier.use_handle) {
+            if self.captured.contains(&declaration) {
+                Variable::Cell(slot)
+            } else {
+                Variable::Local(slot)
+            }
+        } else if let Some(&idx) = self.upvalues.get(&identifier.use_handle) {
+            Variable::Upvalue(idx)
+        } else {
+            Variable::Global
+        }
+    }
+
+    // a function overloading one declared in the same scope shares its variable
+    pub fn is_overload(&self, identifier: &IdentifierUse) -> bool {
+        self.overloads.contains(&identifier.use_handle)
+    }
+
+    // indexed like the upvalues of the function
+    pub fn captures(&self, func: &FuncExpr) -> &[Capture] {
+        self.captures
+            .get(&func.handle)
+            .map_or(&[], |captures| captures.as_slice())
+    }
+
+    pub(super) fn begin_function(&mut self) {
+        self.frames.push(Frame::default());
+    }
+
+    pub(super) fn end_function(&mut self, func: &FuncExpr) {
+        let frame = self.frames.pop().expect("no function to end");
+
+        if !frame.upvalues.is_empty() {
+            self.captures.insert(func.handle, frame.upvalues);
+        }
+    }
+
+    pub(super) fn declare(&mut self, identifier: &IdentifierUse, depth: usize) {
+        let frame = self.frames.last_mut().unwrap();
+        let slot = frame.locals.len() + 1;
+
+        frame.locals.push(Local {
+            name: identifier.name,
+            depth,
+            declaration: identifier.use_handle,
+        });
+
+        self.locals
+            .insert(identifier.use_handle, (slot, identifier.use_handle));
+    }
+
+    pub(super) fn overload(&mut self, identifier: &IdentifierUse) {
+        self.overloads.insert(identifier.use_handle);
+        self.resolve(identifier);
+    }
+
+    // drops the locals declared deeper than 'depth'
+    pub(super) fn end_scope(&mut self, depth: usize) {
+        let locals = &mut self.frames.last_mut().unwrap().locals;
+
+        while locals.last().is_some_and(|local| local.depth > depth) {
+            locals.pop();
+        }
+    }
+
+    pub(super) fn resolve(&mut self, identifier: &IdentifierUse) {
+        let frame = self.frames.len() - 1;
+
+        if let Some(local) = self.local(frame, identifier.name) {
+            self.locals.insert(identifier.use_handle, local);
+        } else if let Some(idx) = self.upvalue(frame, identifier.name) {
+            self.upvalues.insert(identifier.use_handle, idx);
+        }
+    }
+
+    fn local(&self, frame: usize, name: IdentifierHandle) -> Option<(usize, IdentifierUseHandle)> {
+        self.frames[frame]
+            .locals
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, local)| local.name == name)
+            .map(|(idx, local)| (idx + 1, local.declaration))
+    }
+
+    // captures the variable from the enclosing functions, the script has no upvalues
+    fn upvalue(&mut self, frame: usize, name: IdentifierHandle) -> Option<usize> {
+        if frame == 0 {
+            return None;
+        }
+
+        let capture = match self.local(frame - 1, name) {
+            Some((slot, declaration)) => {
+                self.captured.insert(declaration);
+                Capture::Local(slot)
+            }
+            None => Capture::Upvalue(self.upvalue(frame - 1, name)?),
+        };
+
+        let upvalues = &mut self.frames[frame].upvalues;
+
+        Some(match upvalues.iter().position(|other| *other == capture) {
+            Some(idx) => idx,
+            None => {
+                upvalues.push(capture);
+                upvalues.len() - 1
+            }
+        })
+    }
+}
@@ -0,0 +1,174 @@
+use super::interp::EloxInterpreter;
+use super::{EloxError, EloxRunner};
+use crate::interpreter::eval_result::EvalError;
+use crate::interpreter::host::Host;
+use crate::scanner::scanner_result::ErrorPosition;
+use crate::vm::instructions::Value;
+use crate::vm::target::{EloxTranslator, WasmTarget};
+use crate::vm::wasm_runtime::{run_module, WasmHost};
+use crate::vm::EloxVM;
+use std::cell::RefCell;
+use std::env;
+use std::fmt;
+use std::rc::Rc;
+
+// Runs programs in-process and records what they print instead of writing to stdout
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backend {
+    TreeWalker,
+    Vm,
+    Wasm,
+}
+
+impl Backend {
+    // the name used in the // #[target] test headers
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::TreeWalker => "tw",
+            Backend::Vm => "vm",
+            Backend::Wasm => "wasm",
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorKind {
+    Syntax, // scanner, parser and resolution errors
+    Runtime,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::Syntax => write!(f, "syntax error"),
+            ErrorKind::Runtime => write!(f, "runtime error"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CapturedError {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl CapturedError {
+    fn new(err: &EloxError) -> CapturedError {
+        let pos = err.position();
+
+        CapturedError {
+            kind: match err {
+                EloxError::Eval(_) => ErrorKind::Runtime,
+                _ => ErrorKind::Syntax,
+            },
+            message: format!("{}", err),
+            line: pos.line,
+            col: pos.col,
+        }
+    }
+}
+
+impl fmt::Display for CapturedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} [line {}:{}]: {}",
+            self.kind, self.line, self.col, self.message
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Captured {
+    pub output: Vec<String>,
+    pub error: Option<CapturedError>,
+}
+
+// a host without side effects on the terminal: the input is closed,
+// there are no arguments and exit() only stops the script
+pub fn capturing_host(output: &Rc<RefCell<Vec<String>>>) -> Host {
+    let output = Rc::clone(output);
+
+    Host {
+        print: Rc::new(move |_, msg| {
+            output.borrow_mut().push(msg);
+            Ok(())
+        }),
+        error: Rc::new(|_, _, _, _| Ok(())),
+        warn: Rc::new(|_, _, _, _| Ok(())),
+        read_line: Rc::new(|| None),
+        read_all: Rc::new(String::new),
+        args: Rc::new(Vec::new),
+        env_var: Rc::new(|name| env::var(name).ok()),
+        exit: Rc::new(|_| {}),
+        ..Host::default()
+    }
+}
+
+struct CapturingWasmHost {
+    output: Vec<String>,
+}
+
+impl WasmHost for CapturingWasmHost {
+    fn print(&mut self, value: f64) {
+        self.output.push(format!("{}", Value::Number(value)));
+    }
+}
+
+fn run_wasm(source: &str) -> Captured {
+    let mut vm = EloxVM::new();
+
+    if let Err(err) = vm.compile(source) {
+        return Captured {
+            output: vec![],
+            error: Some(CapturedError::new(&err)),
+        };
+    }
+
+    let bytes = WasmTarget::new().translate(vm.chunk());
+    let mut host = CapturingWasmHost { output: vec![] };
+    let res = run_module(&bytes, &mut host);
+
+    Captured {
+        output: host.output,
+        // traps have no source position
+        error: res.err().map(|trap| CapturedError {
+            kind: ErrorKind::Runtime,
+            message: trap,
+            line: 0,
+            col: 0,
+        }),
+    }
+}
+
+pub fn run_captured(backend: Backend, source: &str) -> Captured {
+    if backend == Backend::Wasm {
+        return run_wasm(source);
+    }
+
+    let output = Rc::new(RefCell::new(Vec::new()));
+    let host = capturing_host(&output);
+
+    let res = match backend {
+        Backend::TreeWalker => EloxInterpreter::new(host).run(source),
+        _ => EloxVM::with_host(host).run(source),
+    };
+
+    let error = match res {
+        // exit() stops the script, it is not an error
+        Ok(()) | Err(EloxError::Eval(EvalError::Exit(_, _))) => None,
+        Err(err) => Some(CapturedError::new(&err)),
+    };
+
+    let output = output.borrow().clone();
+    Captured { output, error }
+}
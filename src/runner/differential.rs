@@ -0,0 +1,142 @@
+use super::capture::{run_captured, Backend, Captured};
+use super::program_generator::ProgramGenerator;
+use std::fmt;
+
+// Runs the same program on several backends and reports where they disagree.
+// The first backend is the reference, the tree-walker usually.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    pub reference: Backend,
+    pub backend: Backend,
+    pub description: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} vs {}: {}",
+            self.reference, self.backend, self.description
+        )
+    }
+}
+
+fn describe_error(captured: &Captured) -> String {
+    match &captured.error {
+        Some(err) => format!("{}", err),
+        None => "no error".into(),
+    }
+}
+
+fn output_lines(captured: &Captured) -> Vec<&str> {
+    captured
+        .output
+        .iter()
+        .flat_map(|printed| printed.split('\n'))
+        .collect()
+}
+
+// error messages are worded differently across backends, only their kind and position count
+fn compare(reference: &Captured, other: &Captured, backend: Backend) -> Vec<String> {
+    let mut differences = vec![];
+
+    let (expected, actual) = (output_lines(reference), output_lines(other));
+
+    if let Some(idx) =
+        (0..expected.len().max(actual.len())).find(|&idx| expected.get(idx) != actual.get(idx))
+    {
+        differences.push(format!(
+            "output line {} differs: '{}' vs '{}'",
+            idx + 1,
+            expected.get(idx).unwrap_or(&"<none>"),
+            actual.get(idx).unwrap_or(&"<none>")
+        ));
+    }
+
+    let same_error = match (&reference.error, &other.error) {
+        (None, None) => true,
+        // WASM traps have no source position
+        (Some(a), Some(b)) if backend == Backend::Wasm => a.kind == b.kind,
+        (Some(a), Some(b)) => a.kind == b.kind && (a.line, a.col) == (b.line, b.col),
+        _ => false,
+    };
+
+    if !same_error {
+        differences.push(format!(
+            "{} vs {}",
+            describe_error(reference),
+            describe_error(other)
+        ));
+    }
+
+    differences
+}
+
+pub fn run_differential(source: &str, backends: &[Backend]) -> Vec<Divergence> {
+    let (reference, others) = match backends.split_first() {
+        Some(split) => split,
+        None => return vec![],
+    };
+
+    let expected = run_captured(*reference, source);
+
+    others
+        .iter()
+        .flat_map(|&backend| {
+            compare(&expected, &run_captured(backend, source), backend)
+                .into_iter()
+                .map(move |description| Divergence {
+                    reference: *reference,
+                    backend,
+                    description,
+                })
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct FuzzFailure {
+    pub seed: u64,
+    pub source: String,
+    pub divergences: Vec<Divergence>,
+}
+
+impl fmt::Display for FuzzFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "seed {}:", self.seed)?;
+
+        for divergence in &self.divergences {
+            writeln!(f, "  {}", divergence)?;
+        }
+
+        write!(f, "{}", self.source)
+    }
+}
+
+// generates `count` programs from consecutive seeds, the numeric ones also run on the WASM target
+pub fn fuzz(first_seed: u64, count: u64) -> Vec<FuzzFailure> {
+    let mut failures = vec![];
+
+    for seed in first_seed..first_seed + count {
+        let (source, backends) = if seed % 4 == 0 {
+            let backends = vec![Backend::TreeWalker, Backend::Vm, Backend::Wasm];
+            (ProgramGenerator::numeric(seed).source(), backends)
+        } else {
+            let backends = vec![Backend::TreeWalker, Backend::Vm];
+            (ProgramGenerator::new(seed).source(), backends)
+        };
+
+        let divergences = run_differential(&source, &backends);
+
+        if !divergences.is_empty() {
+            failures.push(FuzzFailure {
+                seed,
+                source,
+                divergences,
+            });
+        }
+    }
+
+    failures
+}
@@ -1,23 +1,76 @@
-use super::{EloxError, EloxResult, EloxRunner};
+use super::{EloxError, EloxResult, EloxRunner, EloxTestRunner};
 use crate::interpreter::environment::Environment;
 use crate::interpreter::host::Host;
-use crate::interpreter::lexical_scope::Resolver;
+use crate::resolver::lexical_scope::Resolver;
+use crate::interpreter::type_checker::{TypeChecker, TypeDiagnostic};
 use crate::interpreter::Interpreter;
 use crate::parser::{IdentifierHandlesGenerator, Parser};
 use crate::scanner::scanner_result::ErrorPosition;
+use crate::scanner::token::Position;
 use crate::scanner::Scanner;
+use std::fs;
+use std::path::Path;
+use std::process;
 use std::rc::Rc;
 
 pub struct EloxInterpreter {
     host: Rc<Host>,
+    interpreter: Option<Interpreter>, // kept after a run to execute the registered tests
 }
 
 impl EloxInterpreter {
     pub fn new(host: Host) -> EloxInterpreter {
         EloxInterpreter {
             host: Rc::new(host),
+            interpreter: None,
         }
     }
+
+    // type checks the annotated parts of a program without running it
+    pub fn check(&mut self, source: &str) -> Result<Vec<TypeDiagnostic>, EloxError> {
+        let scanner = Scanner::new(source.chars().peekable());
+        let mut identifiers = IdentifierHandlesGenerator::new();
+        Environment::with_natives(None, &mut identifiers);
+        let mut parser = Parser::new(scanner.peekable(), &mut identifiers);
+
+        match parser.parse() {
+            Ok(ast) => {
+                let names = Rc::new(parser.names());
+                let mut resolver = Resolver::new(&names);
+
+                if let Err(err) = resolver.resolve(&ast) {
+                    return Err(EloxError::Resolution(err));
+                }
+
+                Ok(TypeChecker::new(&names).check(&ast))
+            }
+            Err(err) => Err(EloxError::Parser(err)),
+        }
+    }
+
+    pub fn check_file(&mut self, path: &Path) -> EloxResult {
+        let contents = fs::read_to_string(path).expect("incorrect file path");
+
+        match self.check(&contents) {
+            Ok(diagnostics) => {
+                let failed = !diagnostics.is_empty();
+
+                for diagnostic in diagnostics {
+                    self.throw_error(diagnostic)?;
+                }
+
+                if failed {
+                    process::exit(65);
+                }
+            }
+            Err(err) => {
+                self.throw_error(err)?;
+                process::exit(65);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl EloxRunner for EloxInterpreter {
@@ -41,9 +94,15 @@ impl EloxRunner for EloxInterpreter {
 
                 match resolver.resolve(&ast) {
                     Ok(()) => {
+                        for warning in resolver.warnings() {
+                            let pos = *warning.position();
+                            (self.host.warn)(pos, format!("{}", warning), pos.line, pos.col)?;
+                        }
+
                         let mut interpreter =
                             Interpreter::new(global, &self.host, &names, resolver);
                         let res = interpreter.interpret(&ast);
+                        self.interpreter = Some(interpreter);
                         match res {
                             Ok(()) => return Ok(()),
                             Err(err) => return Err(EloxError::Eval(err)),
@@ -62,3 +121,20 @@ impl EloxRunner for EloxInterpreter {
         Ok(())
     }
 }
+
+impl EloxTestRunner for EloxInterpreter {
+    fn registered_tests(&self) -> Vec<(String, Position)> {
+        self.interpreter
+            .as_ref()
+            .map_or_else(Vec::new, |interpreter| interpreter.registered_tests())
+    }
+
+    fn run_test(&mut self, idx: usize) -> EloxResult {
+        let interpreter = self
+            .interpreter
+            .as_ref()
+            .expect("tests can only run after their script");
+
+        interpreter.run_test(idx).map_err(EloxError::Eval)
+    }
+}
@@ -1,7 +1,11 @@
+pub mod capture;
+pub mod differential;
 pub mod interp;
+pub mod program_generator;
+pub mod test_runner;
 
 use crate::interpreter::eval_result::EvalError;
-use crate::interpreter::lexical_scope::LexicalScopeResolutionError;
+use crate::resolver::lexical_scope::LexicalScopeResolutionError;
 use crate::parser::parser_result::ParserError;
 use crate::scanner::scanner_result::{ErrorPosition, ScannerError};
 use crate::scanner::token::Position;
@@ -49,6 +53,12 @@ pub trait EloxRunner {
     fn throw_error(&mut self, err: impl ErrorPosition) -> EloxResult;
 }
 
+// a runner able to run the tests a script registered with test(name, fn)
+pub trait EloxTestRunner: EloxRunner {
+    fn registered_tests(&self) -> Vec<(String, Position)>;
+    fn run_test(&mut self, idx: usize) -> EloxResult;
+}
+
 pub trait EloxFileAndPromptRunner {
     fn run_file(&mut self, path: &Path) -> EloxResult;
     fn run_prompt(&mut self) -> EloxResult;
@@ -85,13 +95,10 @@ impl<R: EloxRunner> EloxFileAndPromptRunner for R {
     fn run_from_std_args(&mut self) -> EloxResult {
         let args: Vec<String> = env::args().collect();
 
+        // the arguments following the script path are forwarded to the script
         match args.len() {
             1 => self.run_prompt()?,
-            2 => self.run_file(Path::new(&args[1]))?,
-            _ => {
-                println!("Usage: elox [script]");
-                process::exit(64);
-            }
+            _ => self.run_file(Path::new(&args[1]))?,
         }
 
         Ok(())
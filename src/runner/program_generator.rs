@@ -0,0 +1,612 @@
+use crate::interpreter::lox_random::Pcg32;
+use crate::parser::expressions::*;
+use crate::parser::statements::*;
+use crate::parser::{IdentifierHandle, IdentifierHandlesGenerator, IdentifierNames, IdentifierUse};
+use crate::scanner::token::Position;
+
+// Builds random well-formed programs out of the parser's AST types and renders them as source.
+// Only the constructs every backend compiles are generated: globals, blocks, if, bounded while
+// loops and top-level functions calling the functions declared before them, so programs terminate.
+
+const MAX_EXPR_DEPTH: usize = 3;
+const MAX_BLOCK_DEPTH: usize = 2;
+const MAX_STMTS: usize = 5;
+const MAX_TOP_LEVEL_STMTS: usize = 8;
+const MAX_PARAMS: usize = 3;
+const MAX_LOOP_ITERATIONS: usize = 4;
+
+const STRINGS: [&str; 4] = ["", "a", "elox", "b c"];
+
+const OPERATORS: [BinaryOperator; 19] = [
+    BinaryOperator::Minus,
+    BinaryOperator::Plus,
+    BinaryOperator::Slash,
+    BinaryOperator::Star,
+    BinaryOperator::Percent,
+    BinaryOperator::StarStar,
+    BinaryOperator::SlashSlash,
+    BinaryOperator::Ampersand,
+    BinaryOperator::Pipe,
+    BinaryOperator::Caret,
+    BinaryOperator::LessLess,
+    BinaryOperator::GreaterGreater,
+    BinaryOperator::GreaterGreaterGreater,
+    BinaryOperator::BangEqual,
+    BinaryOperator::EqualEqual,
+    BinaryOperator::Greater,
+    BinaryOperator::GreaterEqual,
+    BinaryOperator::Less,
+    BinaryOperator::LessEqual,
+];
+
+// the operators the WASM target translates, they only produce numbers
+const NUMERIC_OPERATORS: [BinaryOperator; 13] = [
+    BinaryOperator::Minus,
+    BinaryOperator::Plus,
+    BinaryOperator::Slash,
+    BinaryOperator::Star,
+    BinaryOperator::Percent,
+    BinaryOperator::StarStar,
+    BinaryOperator::SlashSlash,
+    BinaryOperator::Ampersand,
+    BinaryOperator::Pipe,
+    BinaryOperator::Caret,
+    BinaryOperator::LessLess,
+    BinaryOperator::GreaterGreater,
+    BinaryOperator::GreaterGreaterGreater,
+];
+
+struct Variable {
+    name: IdentifierHandle,
+    assignable: bool, // loop counters are read only so that loops terminate
+}
+
+struct Function {
+    name: IdentifierHandle,
+    arity: usize,
+}
+
+pub struct ProgramGenerator {
+    rng: Pcg32,
+    identifiers: IdentifierHandlesGenerator,
+    scopes: Vec<Vec<Variable>>,
+    functions: Vec<Function>,
+    numeric_only: bool,
+    in_function: bool,
+    block_depth: usize,
+    names_count: usize,
+}
+
+impl ProgramGenerator {
+    pub fn new(seed: u64) -> ProgramGenerator {
+        ProgramGenerator {
+            rng: Pcg32::new(seed),
+            identifiers: IdentifierHandlesGenerator::new(),
+            scopes: vec![vec![]],
+            functions: vec![],
+            numeric_only: false,
+            in_function: false,
+            block_depth: 0,
+            names_count: 0,
+        }
+    }
+
+    // programs printing numeric expressions only, the subset the WASM target supports
+    pub fn numeric(seed: u64) -> ProgramGenerator {
+        ProgramGenerator {
+            numeric_only: true,
+            ..ProgramGenerator::new(seed)
+        }
+    }
+
+    pub fn program(&mut self) -> Vec<Stmt> {
+        let count = 1 + self.rng.below(MAX_TOP_LEVEL_STMTS);
+
+        (0..count)
+            .map(|_| {
+                if self.numeric_only {
+                    let value = self.numeric_expr(0);
+                    PrintStmt::to_stmt(pos(), value)
+                } else if self.chance(4) {
+                    self.function_declaration()
+                } else {
+                    self.statement()
+                }
+            })
+            .collect()
+    }
+
+    pub fn names(&self) -> IdentifierNames {
+        self.identifiers.names()
+    }
+
+    // a new program, as source code
+    pub fn source(&mut self) -> String {
+        let stmts = self.program();
+        let names = self.names();
+
+        let mut source = String::new();
+        for stmt in &stmts {
+            render_stmt(stmt, &names, 0, &mut source);
+        }
+
+        source
+    }
+
+    fn chance(&mut self, one_in: usize) -> bool {
+        self.rng.below(one_in) == 0
+    }
+
+    fn fresh_name(&mut self, prefix: &str) -> IdentifierHandle {
+        // names are never reused: no shadowing, no redeclaration
+        let name = format!("{}{}", prefix, self.names_count);
+        self.names_count += 1;
+
+        self.identifiers.by_name(&name)
+    }
+
+    fn use_of(&mut self, name: IdentifierHandle) -> IdentifierUse {
+        IdentifierUse::new(name, self.identifiers.next_use_handle(), pos())
+    }
+
+    fn declare(&mut self, name: IdentifierHandle, assignable: bool) {
+        self.scopes
+            .last_mut()
+            .expect("the global scope is never popped")
+            .push(Variable { name, assignable });
+    }
+
+    fn pick_variable(&mut self, assignable_only: bool) -> Option<IdentifierHandle> {
+        let candidates = self
+            .scopes
+            .iter()
+            .flatten()
+            .filter(|var| var.assignable || !assignable_only)
+            .map(|var| var.name)
+            .collect::<Vec<_>>();
+
+        if candidates.is_empty() {
+            return None;
+        }
+
+        Some(candidates[self.rng.below(candidates.len())])
+    }
+
+    fn function_declaration(&mut self) -> Stmt {
+        let name = self.fresh_name("f");
+        let arity = self.rng.below(MAX_PARAMS + 1);
+
+        let params = (0..arity).map(|_| self.fresh_name("p")).collect::<Vec<_>>();
+
+        // the body sees the globals declared so far and its parameters
+        self.scopes.push(
+            params
+                .iter()
+                .map(|&name| Variable {
+                    name,
+                    assignable: true,
+                })
+                .collect(),
+        );
+        self.in_function = true;
+
+        let mut body = self.statements();
+        let value = self.expr(0);
+        body.push(ReturnStmt::to_stmt(Some(value), pos()));
+
+        self.in_function = false;
+        self.scopes.pop();
+
+        let params = params
+            .into_iter()
+            .map(|param| FuncParam::Required(self.use_of(param)))
+            .collect::<Vec<_>>();
+
+        let param_types = vec![None; arity];
+        let identifier = self.use_of(name);
+        let func = FuncExpr::new(
+            pos(),
+            self.identifiers.next_use_handle(),
+            Some(identifier),
+            Some(params),
+            param_types,
+            None,
+            body,
+        );
+
+        // declared after its body is generated, so functions cannot recurse
+        self.functions.push(Function { name, arity });
+        ExprStmt::to_stmt(func)
+    }
+
+    fn statements(&mut self) -> Vec<Stmt> {
+        let count = self.rng.below(MAX_STMTS + 1);
+        (0..count).map(|_| self.statement()).collect()
+    }
+
+    fn statement(&mut self) -> Stmt {
+        let nested = self.block_depth < MAX_BLOCK_DEPTH;
+
+        match self.rng.below(if nested { 8 } else { 4 }) {
+            0..=1 => {
+                let value = self.expr(0);
+                PrintStmt::to_stmt(pos(), value)
+            }
+            2 => self.var_declaration(),
+            3 => match self.pick_variable(true) {
+                Some(name) => {
+                    let value = self.expr(0);
+                    let identifier = self.use_of(name);
+                    ExprStmt::to_stmt(AssignExpr::new(pos(), identifier, value))
+                }
+                None => self.var_declaration(),
+            },
+            4 => self.block(),
+            5 => self.if_statement(),
+            6 => self.while_loop(),
+            _ if self.in_function => {
+                // an early return
+                let condition = self.expr(0);
+                let value = self.expr(0);
+                let ret = ReturnStmt::to_stmt(Some(value), pos());
+                IfStmt::to_stmt(condition, ret, None)
+            }
+            _ => self.block(),
+        }
+    }
+
+    fn var_declaration(&mut self) -> Stmt {
+        // the initializer is generated before the variable is in scope
+        let initializer = if self.chance(5) {
+            None
+        } else {
+            Some(self.expr(0))
+        };
+
+        let name = self.fresh_name("v");
+        self.declare(name, true);
+
+        let identifier = self.use_of(name);
+        VarDeclStmt::to_stmt(identifier, None, initializer, pos())
+    }
+
+    fn with_block<F: FnOnce(&mut ProgramGenerator) -> Vec<Stmt>>(&mut self, build: F) -> Stmt {
+        self.block_depth += 1;
+        self.scopes.push(vec![]);
+
+        let stmts = build(self);
+
+        self.scopes.pop();
+        self.block_depth -= 1;
+
+        BlockStmt::to_stmt(stmts, pos(), pos())
+    }
+
+    fn block(&mut self) -> Stmt {
+        self.with_block(|gen| gen.statements())
+    }
+
+    fn if_statement(&mut self) -> Stmt {
+        let condition = self.expr(0);
+        let then_branch = self.block();
+
+        let else_branch = if self.chance(2) {
+            Some(self.block())
+        } else {
+            None
+        };
+
+        IfStmt::to_stmt(condition, then_branch, else_branch)
+    }
+
+    // { var i = 0; while (i < n) { ...; i = i + 1; } }
+    fn while_loop(&mut self) -> Stmt {
+        let iterations = self.rng.below(MAX_LOOP_ITERATIONS + 1);
+
+        self.with_block(|gen| {
+            let counter = gen.fresh_name("i");
+            let init = VarDeclStmt::to_stmt(gen.use_of(counter), None, Some(number(0.0)), pos());
+            gen.declare(counter, false);
+
+            let read = VarExpr::new(pos(), gen.use_of(counter));
+            let condition =
+                BinaryExpr::new(pos(), read, BinaryOperator::Less, number(iterations as f64));
+
+            let body = gen.with_block(|gen| {
+                let mut stmts = gen.statements();
+
+                let read = VarExpr::new(pos(), gen.use_of(counter));
+                let incr = BinaryExpr::new(pos(), read, BinaryOperator::Plus, number(1.0));
+                let identifier = gen.use_of(counter);
+                stmts.push(ExprStmt::to_stmt(AssignExpr::new(pos(), identifier, incr)));
+
+                stmts
+            });
+
+            vec![init, WhileStmt::to_stmt(condition, body)]
+        })
+    }
+
+    fn expr(&mut self, depth: usize) -> ExprCtx {
+        if depth >= MAX_EXPR_DEPTH || self.chance(3) {
+            return self.atom();
+        }
+
+        match self.rng.below(10) {
+            0..=2 => {
+                let op = OPERATORS[self.rng.below(OPERATORS.len())].clone();
+                let left = self.operand(depth);
+                let right = self.operand(depth);
+                BinaryExpr::new(pos(), left, op, right)
+            }
+            3 => {
+                let op = match self.rng.below(3) {
+                    0 => UnaryOperator::Minus,
+                    1 => UnaryOperator::Bang,
+                    _ => UnaryOperator::Tilde,
+                };
+                let right = self.operand(depth);
+                UnaryExpr::new(pos(), op, right)
+            }
+            4..=5 => {
+                let op = match self.rng.below(3) {
+                    0 => LogicalOperator::And,
+                    1 => LogicalOperator::Or,
+                    _ => LogicalOperator::NilCoalescing,
+                };
+                let left = self.operand(depth);
+                let right = self.operand(depth);
+                LogicalExpr::new(pos(), left, op, right)
+            }
+            6 => {
+                let condition = self.operand(depth);
+                let then_branch = self.operand(depth);
+                let else_branch = self.operand(depth);
+                ConditionalExpr::new(pos(), condition, then_branch, else_branch)
+            }
+            7..=8 if !self.functions.is_empty() => self.call(depth),
+            _ => self.atom(),
+        }
+    }
+
+    fn call(&mut self, depth: usize) -> ExprCtx {
+        let idx = self.rng.below(self.functions.len());
+        let (name, arity) = (self.functions[idx].name, self.functions[idx].arity);
+
+        let args = (0..arity).map(|_| self.expr(depth + 1)).collect();
+        let callee = VarExpr::new(pos(), self.use_of(name));
+
+        CallExpr::new(pos(), callee, args)
+    }
+
+    // compound operands are grouped, the rendered source does not depend on precedence
+    fn operand(&mut self, depth: usize) -> ExprCtx {
+        let expr = self.expr(depth + 1);
+        group(expr)
+    }
+
+    fn atom(&mut self) -> ExprCtx {
+        if self.chance(2) {
+            if let Some(name) = self.pick_variable(false) {
+                return VarExpr::new(pos(), self.use_of(name));
+            }
+        }
+
+        match self.rng.below(6) {
+            0..=2 => self.number(),
+            3 => {
+                let value = STRINGS[self.rng.below(STRINGS.len())];
+                Literal::new(pos(), Literal::String(value.into()))
+            }
+            4 => Literal::new(pos(), Literal::Boolean(self.chance(2))),
+            _ => Literal::new(pos(), Literal::Nil),
+        }
+    }
+
+    fn number(&mut self) -> ExprCtx {
+        let value = self.rng.below(10) as f64;
+
+        if self.chance(4) {
+            number(value + 0.5)
+        } else {
+            number(value)
+        }
+    }
+
+    fn numeric_expr(&mut self, depth: usize) -> ExprCtx {
+        if depth >= MAX_EXPR_DEPTH || self.chance(3) {
+            return self.number();
+        }
+
+        if self.chance(5) {
+            let op = if self.chance(2) {
+                UnaryOperator::Minus
+            } else {
+                UnaryOperator::Tilde
+            };
+            let right = group(self.numeric_expr(depth + 1));
+            return UnaryExpr::new(pos(), op, right);
+        }
+
+        let op = NUMERIC_OPERATORS[self.rng.below(NUMERIC_OPERATORS.len())].clone();
+        let left = group(self.numeric_expr(depth + 1));
+        let right = group(self.numeric_expr(depth + 1));
+
+        BinaryExpr::new(pos(), left, op, right)
+    }
+}
+
+// the generated nodes are rendered and parsed again, their positions are not used
+fn pos() -> Position {
+    Position { line: 1, col: 1 }
+}
+
+fn number(value: f64) -> ExprCtx {
+    Literal::new(pos(), Literal::Number(value))
+}
+
+fn group(expr: ExprCtx) -> ExprCtx {
+    match expr.expr {
+        Expr::Literal(_) | Expr::Var(_) | Expr::Call(_) | Expr::Grouping(_) => expr,
+        _ => GroupingExpr::new(pos(), expr),
+    }
+}
+
+fn indent(level: usize, out: &mut String) {
+    for _ in 0..level {
+        out.push_str("    ");
+    }
+}
+
+fn render_stmt(stmt: &Stmt, names: &IdentifierNames, level: usize, out: &mut String) {
+    indent(level, out);
+
+    match stmt {
+        Stmt::Print(print) => {
+            out.push_str(&format!("print {};\n", render_expr(&print.value, names)));
+        }
+        Stmt::Expr(ExprStmt {
+            expr: ExprCtx {
+                expr: Expr::Func(func),
+                ..
+            },
+        }) => render_function(func, names, level, out),
+        Stmt::Expr(expr_stmt) => {
+            out.push_str(&format!("{};\n", render_expr(&expr_stmt.expr, names)));
+        }
+        Stmt::VarDecl(decl) => {
+            let name = &names[decl.identifier.name];
+
+            match &decl.initializer {
+                Some(init) => {
+                    out.push_str(&format!("var {} = {};\n", name, render_expr(init, names)))
+                }
+                None => out.push_str(&format!("var {};\n", name)),
+            }
+        }
+        Stmt::Block(block) => {
+            out.push_str("{\n");
+            render_stmts(&block.stmts, names, level + 1, out);
+            indent(level, out);
+            out.push_str("}\n");
+        }
+        Stmt::If(if_stmt) => {
+            out.push_str(&format!("if ({})", render_expr(&if_stmt.condition, names)));
+            render_branch(&if_stmt.then_branch, names, level, out);
+
+            if let Some(else_branch) = &if_stmt.else_branch {
+                indent(level, out);
+                out.push_str("else");
+                render_branch(else_branch, names, level, out);
+            }
+        }
+        Stmt::While(while_stmt) => {
+            out.push_str(&format!(
+                "while ({})",
+                render_expr(&while_stmt.condition, names)
+            ));
+            render_branch(&while_stmt.body, names, level, out);
+        }
+        Stmt::Return(ret) => match &ret.value {
+            Some(value) => out.push_str(&format!("return {};\n", render_expr(value, names))),
+            None => out.push_str("return;\n"),
+        },
+        _ => unreachable!("the generator does not build this statement"),
+    }
+}
+
+// the body of an if or a while, a block opens on the same line
+fn render_branch(stmt: &Stmt, names: &IdentifierNames, level: usize, out: &mut String) {
+    match stmt {
+        Stmt::Block(block) => {
+            out.push_str(" {\n");
+            render_stmts(&block.stmts, names, level + 1, out);
+            indent(level, out);
+            out.push_str("}\n");
+        }
+        _ => {
+            out.push('\n');
+            render_stmt(stmt, names, level + 1, out);
+        }
+    }
+}
+
+fn render_stmts(stmts: &[Stmt], names: &IdentifierNames, level: usize, out: &mut String) {
+    for stmt in stmts {
+        render_stmt(stmt, names, level, out);
+    }
+}
+
+fn render_function(func: &FuncExpr, names: &IdentifierNames, level: usize, out: &mut String) {
+    let params = func
+        .params
+        .iter()
+        .flatten()
+        .map(|param| match param {
+            FuncParam::Required(identifier) => names[identifier.name].clone(),
+            _ => unreachable!("the generator only builds required parameters"),
+        })
+        .collect::<Vec<_>>();
+
+    let name = func.name.as_ref().map_or("", |name| &names[name.name]);
+
+    out.push_str(&format!("fun {}({}) {{\n", name, params.join(", ")));
+    render_stmts(&func.body, names, level + 1, out);
+    indent(level, out);
+    out.push_str("}\n");
+}
+
+fn render_literal(literal: &Literal) -> String {
+    match literal {
+        Literal::Nil => "nil".into(),
+        Literal::Number(n) => format!("{}", n),
+        Literal::String(s) => format!("\"{}\"", s),
+        Literal::Boolean(b) => format!("{}", b),
+    }
+}
+
+fn render_expr(expr: &ExprCtx, names: &IdentifierNames) -> String {
+    match &expr.expr {
+        Expr::Literal(literal) => render_literal(literal),
+        Expr::Var(var) => names[var.identifier.name].clone(),
+        Expr::Grouping(grouping) => format!("({})", render_expr(&grouping.expression, names)),
+        Expr::Unary(unary) => format!("{}{}", unary.operator, render_expr(&unary.right, names)),
+        Expr::Binary(binary) => format!(
+            "{} {} {}",
+            render_expr(&binary.left, names),
+            binary.operator.op,
+            render_expr(&binary.right, names)
+        ),
+        Expr::Logical(logical) => format!(
+            "{} {} {}",
+            render_expr(&logical.left, names),
+            match logical.operator {
+                LogicalOperator::And => "and",
+                LogicalOperator::Or => "or",
+                LogicalOperator::NilCoalescing => "??",
+            },
+            render_expr(&logical.right, names)
+        ),
+        Expr::Conditional(cond) => format!(
+            "{} ? {} : {}",
+            render_expr(&cond.condition, names),
+            render_expr(&cond.then_branch, names),
+            render_expr(&cond.else_branch, names)
+        ),
+        Expr::Assign(assign) => format!(
+            "{} = {}",
+            names[assign.identifier.name],
+            render_expr(&assign.expr, names)
+        ),
+        Expr::Call(call) => format!(
+            "{}({})",
+            render_expr(&call.callee, names),
+            call.args
+                .iter()
+                .map(|arg| render_expr(arg, names))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        _ => unreachable!("the generator does not build this expression"),
+    }
+}
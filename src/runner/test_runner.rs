@@ -0,0 +1,122 @@
+use super::{EloxError, EloxTestRunner};
+use crate::scanner::scanner_result::ErrorPosition;
+use crate::scanner::token::Position;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const TEST_FILE_SUFFIX: &str = "_test.elox";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TestSummary {
+    pub passed: usize,
+    pub failed: usize,
+}
+
+impl TestSummary {
+    pub fn succeeded(&self) -> bool {
+        self.failed == 0
+    }
+}
+
+// a file is used as is, directories are searched recursively for *_test.elox files
+pub fn discover_test_files(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    if path.is_dir() {
+        collect_test_files(path, &mut files)?;
+        files.sort();
+    } else {
+        fs::metadata(path)?;
+        files.push(path.to_path_buf());
+    }
+
+    Ok(files)
+}
+
+fn collect_test_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            collect_test_files(&path, files)?;
+        } else if path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(TEST_FILE_SUFFIX))
+        {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn location(pos: &Position) -> String {
+    format!("[line {}:{}]", pos.line, pos.col)
+}
+
+fn report_failure(err: &EloxError) {
+    println!("       {} {}", location(err.position()), err);
+}
+
+// each test runs on a fresh runner so that tests cannot observe each other's side effects
+pub fn run_test_file<R, F>(path: &Path, new_runner: &F) -> io::Result<TestSummary>
+where
+    R: EloxTestRunner,
+    F: Fn() -> R,
+{
+    let source = fs::read_to_string(path)?;
+    let mut summary = TestSummary::default();
+
+    println!("{}", path.display());
+
+    let mut runner = new_runner();
+
+    if let Err(err) = runner.run(&source) {
+        println!(
+            "  FAIL {} could not load the file",
+            location(err.position())
+        );
+        report_failure(&err);
+        summary.failed += 1;
+        return Ok(summary);
+    }
+
+    for (idx, (name, pos)) in runner.registered_tests().into_iter().enumerate() {
+        let mut runner = new_runner();
+        let res = runner.run(&source).and_then(|_| runner.run_test(idx));
+
+        match res {
+            Ok(()) => {
+                println!("  PASS {} {}", location(&pos), name);
+                summary.passed += 1;
+            }
+            Err(err) => {
+                println!("  FAIL {} {}", location(&pos), name);
+                report_failure(&err);
+                summary.failed += 1;
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+pub fn run_tests<R, F>(path: &Path, new_runner: F) -> io::Result<TestSummary>
+where
+    R: EloxTestRunner,
+    F: Fn() -> R,
+{
+    let mut summary = TestSummary::default();
+
+    for file in discover_test_files(path)? {
+        let file_summary = run_test_file(&file, &new_runner)?;
+        summary.passed += file_summary.passed;
+        summary.failed += file_summary.failed;
+    }
+
+    println!("{} passed, {} failed", summary.passed, summary.failed);
+
+    Ok(summary)
+}
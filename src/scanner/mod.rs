@@ -3,20 +3,31 @@ pub mod token;
 use scanner_result::{ScannerError, ScannerResult};
 use std::iter::Peekable;
 use std::str::Chars;
-use token::{token_type::TokenType, Position, Token};
+use token::{
+    token_type::{StringPart, TokenType},
+    Position, Token,
+};
 
 pub struct Scanner<'a> {
     source: Peekable<Chars<'a>>,
     current_lexeme: String,
     pos: Position,
+    // used to tell integer divisions from comments: `a // b`
+    last_token: Option<(TokenType, usize)>,
 }
 
 impl<'a> Scanner<'a> {
     pub fn new(source: Peekable<Chars<'a>>) -> Self {
+        Scanner::with_position(source, Position { line: 1, col: 1 })
+    }
+
+    // used to scan code embedded in a larger source, like interpolated expressions
+    pub fn with_position(source: Peekable<Chars<'a>>, pos: Position) -> Self {
         Scanner {
             source,
             current_lexeme: "".into(),
-            pos: Position { line: 1, col: 1 },
+            pos,
+            last_token: None,
         }
     }
 
@@ -147,7 +158,7 @@ impl<'a> Scanner<'a> {
                     if self.consume(&'.') {
                         Ok(self.token(DotDotDot))
                     } else {
-                        Err(ScannerError::UnexpectedCharacter(self.pos, 'c'))
+                        Ok(self.match_next(&'=', DotDotEqual, DotDot))
                     }
                 } else {
                     Ok(self.token(Dot))
@@ -156,14 +167,56 @@ impl<'a> Scanner<'a> {
             Some('-') => Ok(self.match_minus_assignment_shorthand()),
             Some('+') => Ok(self.match_plus_assignment_shorthand()),
             Some(';') => Ok(self.token(SemiColon)),
-            Some('*') => Ok(self.match_next(&'=', StarEqual, Star)),
+            Some('*') => {
+                if self.consume(&'*') {
+                    Ok(self.token(StarStar))
+                } else {
+                    Ok(self.match_next(&'=', StarEqual, Star))
+                }
+            }
             Some('%') => Ok(self.match_next(&'=', PercentEqual, Percent)),
             Some('!') => Ok(self.match_op(BangEqual, Bang)),
-            Some('=') => Ok(self.match_op(EqualEqual, Equal)),
-            Some('<') => Ok(self.match_op(LessEqual, Less)),
-            Some('>') => Ok(self.match_op(GreaterEqual, Greater)),
+            Some('=') => {
+                if self.consume(&'>') {
+                    Ok(self.token(FatArrow))
+                } else {
+                    Ok(self.match_op(EqualEqual, Equal))
+                }
+            }
+            Some('<') => {
+                if self.consume(&'<') {
+                    Ok(self.token(LessLess))
+                } else {
+                    Ok(self.match_op(LessEqual, Less))
+                }
+            }
+            Some('>') => {
+                if self.consume(&'>') {
+                    Ok(self.match_next(&'>', GreaterGreaterGreater, GreaterGreater))
+                } else {
+                    Ok(self.match_op(GreaterEqual, Greater))
+                }
+            }
+            Some('&') => Ok(self.token(Ampersand)),
+            Some('|') => Ok(self.token(Pipe)),
+            Some('^') => Ok(self.token(Caret)),
+            Some('~') => Ok(self.token(Tilde)),
+            Some(':') => Ok(self.token(Colon)),
+            Some('?') => {
+                if self.consume(&'?') {
+                    Ok(self.token(QuestionQuestion))
+                } else {
+                    Ok(self.match_next(&'.', QuestionDot, Question))
+                }
+            }
             Some('/') => {
+                let follows_operand = self.follows_operand();
+
                 Ok(match self.source.peek() {
+                    Some(&'/') if follows_operand => {
+                        self.advance();
+                        self.token(SlashSlash)
+                    }
                     Some(&'/') => {
                         // it's a comment
                         self.skip_line();
@@ -176,7 +229,17 @@ impl<'a> Scanner<'a> {
                     _ => self.token(Slash),
                 })
             }
-            Some('"') => self.scan_string(),
+            Some('"') => {
+                if self.consume(&'"') {
+                    if self.consume(&'"') {
+                        return self.scan_raw_string();
+                    }
+
+                    return Ok(self.token(String(std::string::String::new())));
+                }
+
+                self.scan_string()
+            }
             Some(c) => {
                 if c == '\n' {
                     self.scan_token()
@@ -193,40 +256,276 @@ impl<'a> Scanner<'a> {
     }
 
     fn scan_string(&mut self) -> ScannerResult<Token> {
+        let mut value = String::new();
+        let mut parts = Vec::new();
+
+        while let Some(c) = self.advance() {
+            match c {
+                '"' => {
+                    if parts.is_empty() {
+                        return Ok(self.token(TokenType::String(value)));
+                    }
+
+                    if !value.is_empty() {
+                        parts.push(StringPart::Literal(value));
+                    }
+
+                    return Ok(self.token(TokenType::InterpolatedString(parts)));
+                }
+                '\\' => value.push(self.scan_escape_sequence()?),
+                '$' if self.consume(&'{') => {
+                    if !value.is_empty() {
+                        parts.push(StringPart::Literal(std::mem::take(&mut value)));
+                    }
+
+                    parts.push(self.scan_interpolation()?);
+                }
+                _ => value.push(c),
+            }
+        }
+
+        Err(ScannerError::UnterminatedString(self.pos))
+    }
+
+    fn scan_escape_sequence(&mut self) -> ScannerResult<char> {
+        let pos = self.pos;
+
+        match self.advance() {
+            Some('n') => Ok('\n'),
+            Some('t') => Ok('\t'),
+            Some('r') => Ok('\r'),
+            Some('0') => Ok('\0'),
+            Some('\\') => Ok('\\'),
+            Some('"') => Ok('"'),
+            Some('$') => Ok('$'),
+            Some('u') => {
+                // unicode code point: \u{1F600}
+                let mut seq = String::from("\\u");
+
+                if !self.consume(&'{') {
+                    return Err(ScannerError::InvalidEscapeSequence(pos, seq));
+                }
+
+                seq.push('{');
+                let mut hex = String::new();
+
+                while let Some(c) = self.advance() {
+                    seq.push(c);
+
+                    if c == '}' {
+                        break;
+                    }
+
+                    hex.push(c);
+                }
+
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(std::char::from_u32)
+                    .ok_or(ScannerError::InvalidEscapeSequence(pos, seq))
+            }
+            Some(c) => Err(ScannerError::InvalidEscapeSequence(pos, format!("\\{}", c))),
+            None => Err(ScannerError::UnterminatedString(pos)),
+        }
+    }
+
+    // collects the source of an interpolated expression up to its closing brace
+    fn scan_interpolation(&mut self) -> ScannerResult<StringPart> {
+        let pos = self.pos;
+        let mut source = String::new();
+        let mut depth = 0;
+
+        while let Some(c) = self.advance() {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => return Ok(StringPart::Expr(source, pos)),
+                '}' => depth -= 1,
+                '"' => {
+                    // nested string literal, copied verbatim
+                    source.push(c);
+
+                    while let Some(c) = self.advance() {
+                        source.push(c);
+
+                        match c {
+                            '"' => break,
+                            '\\' => {
+                                if let Some(escaped) = self.advance() {
+                                    source.push(escaped);
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+
+                    continue;
+                }
+                _ => {}
+            }
+
+            source.push(c);
+        }
+
+        Err(ScannerError::UnterminatedInterpolation(pos))
+    }
+
+    // """raw strings""" can span multiple lines and are kept verbatim
+    fn scan_raw_string(&mut self) -> ScannerResult<Token> {
+        let mut value = String::new();
+
+        while let Some(c) = self.advance() {
+            value.push(c);
+
+            if value.ends_with("\"\"\"") {
+                value.truncate(value.len() - 3);
+                return Ok(self.token(TokenType::String(value)));
+            }
+        }
+
+        Err(ScannerError::UnterminatedString(self.pos))
+    }
+
+    // '//' is an integer division when it directly follows an operand on the same line,
+    // otherwise it starts a comment
+    fn follows_operand(&self) -> bool {
+        use TokenType::*;
+
+        matches!(
+            &self.last_token,
+            Some((
+                Number(_)
+                    | String(_)
+                    | InterpolatedString(_)
+                    | Identifier(_)
+                    | RightParen
+                    | RightBracket
+                    | True
+                    | False
+                    | Nil
+                    | This,
+                line,
+            )) if *line == self.pos.line
+        )
+    }
+
+    fn scan_digits(&mut self, radix: u32) {
         while let Some(&c) = self.source.peek() {
-            if c == '"' {
-                self.advance(); // get rid of the terminating '"'
-                return Ok(self.token(TokenType::String(
-                    self.current_lexeme[1..self.current_lexeme.len() - 1].to_owned(),
-                )));
-            } else {
+            if c.is_digit(radix) || c == '_' {
                 self.advance();
+            } else {
+                break;
             }
         }
+    }
+
+    fn invalid_number(&self) -> ScannerError {
+        ScannerError::InvalidNumberLiteral(self.pos, self.current_lexeme.clone())
+    }
+
+    // digits separated by single underscores: 1_000_000
+    // 'start' is the offset of the digits in the lexeme, errors point at the misplaced underscore
+    fn strip_separators(&self, digits: &str, start: usize) -> ScannerResult<String> {
+        if digits.is_empty() {
+            return Err(self.invalid_number());
+        }
+
+        let misplaced = if digits.starts_with('_') {
+            Some(0)
+        } else if let Some(idx) = digits.find("__") {
+            Some(idx + 1)
+        } else if digits.ends_with('_') {
+            Some(digits.len() - 1)
+        } else {
+            None
+        };
+
+        if let Some(idx) = misplaced {
+            let lexeme_col = self.pos.col - self.current_lexeme.len();
+            let pos = Position {
+                line: self.pos.line,
+                col: lexeme_col + start + idx,
+            };
 
-        Err(ScannerError::UnterminatedString(self.pos.clone()))
+            return Err(ScannerError::InvalidNumberLiteral(
+                pos,
+                self.current_lexeme.clone(),
+            ));
+        }
+
+        Ok(digits.replace('_', ""))
     }
 
     fn scan_number(&mut self) -> ScannerResult<Token> {
-        while let Some(&c) = self.source.peek() {
-            if c.is_digit(10) {
-                self.advance();
-            } else if c == '.' {
+        // hexadecimal, binary and octal literals: 0xFF, 0b1010, 0o17
+        if self.current_lexeme == "0" {
+            let radix = match self.source.peek() {
+                Some('x') | Some('X') => Some((16, "hexadecimal")),
+                Some('b') | Some('B') => Some((2, "binary")),
+                Some('o') | Some('O') => Some((8, "octal")),
+                _ => None,
+            };
+
+            if let Some((radix, kind)) = radix {
                 self.advance();
+                self.scan_digits(radix);
 
-                while let Some(&c) = self.source.peek() {
-                    if c.is_digit(10) {
-                        self.advance();
-                    } else {
-                        break;
+                // 0b102 is a mistyped literal, not the number 0b10 followed by 2
+                if let Some(&c) = self.source.peek() {
+                    if c.is_ascii_alphanumeric() {
+                        return Err(ScannerError::InvalidDigit(self.pos, c, kind));
                     }
                 }
-            } else {
-                break;
+
+                let digits = self.strip_separators(&self.current_lexeme[2..], 2)?;
+
+                // accumulated in a float: literals wider than 64 bits are valid like decimal ones
+                let value = digits.chars().fold(0f64, |value, c| {
+                    let digit = c.to_digit(radix).expect("scanned in this radix");
+                    value * radix as f64 + digit as f64
+                });
+
+                return Ok(self.token(TokenType::Number(value)));
+            }
+        }
+
+        self.scan_digits(10);
+
+        // a fractional part needs at least one digit: `1.` is the number 1 followed by a dot
+        if self.source.peek() == Some(&'.') {
+            let mut lookahead = self.source.clone();
+            lookahead.next();
+
+            if lookahead.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.advance();
+                self.scan_digits(10);
+            }
+        }
+
+        // scientific notation: 1e9, 2.5E-3
+        if let Some('e') | Some('E') = self.source.peek() {
+            self.advance();
+
+            if let Some('+') | Some('-') = self.source.peek() {
+                self.advance();
             }
+
+            self.scan_digits(10);
         }
 
-        let value: f64 = self.current_lexeme.parse().unwrap();
+        // separators are only allowed between digits
+        let mut start = 0;
+
+        for part in self.current_lexeme.split(['.', 'e', 'E']) {
+            let sign = part.len() - part.trim_start_matches(['+', '-']).len();
+            self.strip_separators(&part[sign..], start + sign)?;
+            start += part.len() + 1;
+        }
+
+        let value: f64 = self
+            .current_lexeme
+            .replace('_', "")
+            .parse()
+            .map_err(|_| self.invalid_number())?;
 
         Ok(self.token(TokenType::Number(value)))
     }
@@ -244,6 +543,8 @@ impl<'a> Scanner<'a> {
     }
 
     fn token(&mut self, token_type: TokenType) -> Token {
+        self.last_token = Some((token_type.clone(), self.pos.line));
+
         Token::new(
             token_type,
             self.current_lexeme.clone(),
@@ -259,10 +560,13 @@ impl<'a> Scanner<'a> {
             "and" => And,
             "class" => Class,
             "else" => Else,
+            "enum" => Enum,
             "false" => False,
             "for" => For,
             "fun" => Fun,
             "if" => If,
+            "is" => Is,
+            "match" => Match,
             "nil" => Nil,
             "or" => Or,
             "print" => Print,
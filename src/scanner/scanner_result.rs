@@ -7,6 +7,10 @@ pub type ScannerResult<T> = Result<T, ScannerError>;
 pub enum ScannerError {
     UnexpectedCharacter(Position, char),
     UnterminatedString(Position),
+    InvalidEscapeSequence(Position, String),
+    UnterminatedInterpolation(Position),
+    InvalidNumberLiteral(Position, String),
+    InvalidDigit(Position, char, &'static str), // digit, literal kind
 }
 
 impl fmt::Display for ScannerError {
@@ -14,6 +18,18 @@ impl fmt::Display for ScannerError {
         match self {
             ScannerError::UnexpectedCharacter(_, c) => write!(f, "Unexpected character '{}'", c),
             ScannerError::UnterminatedString(_) => write!(f, "Unterminated string"),
+            ScannerError::InvalidEscapeSequence(_, seq) => {
+                write!(f, "Invalid escape sequence '{}'", seq)
+            }
+            ScannerError::UnterminatedInterpolation(_) => {
+                write!(f, "Unterminated string interpolation, expected '}}'")
+            }
+            ScannerError::InvalidNumberLiteral(_, lexeme) => {
+                write!(f, "Invalid number literal '{}'", lexeme)
+            }
+            ScannerError::InvalidDigit(_, digit, kind) => {
+                write!(f, "Invalid digit '{}' in {} literal", digit, kind)
+            }
         }
     }
 }
@@ -28,6 +44,10 @@ impl ErrorPosition for ScannerError {
         match self {
             UnexpectedCharacter(pos, _) => pos,
             UnterminatedString(pos) => pos,
+            InvalidEscapeSequence(pos, _) => pos,
+            UnterminatedInterpolation(pos) => pos,
+            InvalidNumberLiteral(pos, _) => pos,
+            InvalidDigit(pos, _, _) => pos,
         }
     }
 }
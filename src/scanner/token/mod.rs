@@ -2,7 +2,7 @@ pub mod token_type;
 use std::fmt;
 use token_type::TokenType;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Position {
     pub line: usize,
     pub col: usize,
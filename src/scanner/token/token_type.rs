@@ -1,3 +1,12 @@
+use super::Position;
+
+// a piece of an interpolated string: "x = ${x}"
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+    Literal(String),
+    Expr(String, Position), // source of the interpolated expression
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
     // Single-character tokens
@@ -15,6 +24,12 @@ pub enum TokenType {
     Slash,
     Star,
     Percent,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
+    Question,
+    Colon,
 
     // One or two character tokens
     Bang,
@@ -32,23 +47,37 @@ pub enum TokenType {
     StarEqual,
     SlashEqual,
     PercentEqual,
+    StarStar,
+    SlashSlash,
+    LessLess,
+    GreaterGreater,
+    QuestionQuestion,
+    QuestionDot,
+    FatArrow,
+    DotDot,
 
     // 3 chars
     DotDotDot,
+    DotDotEqual,
+    GreaterGreaterGreater,
 
     // Literals
     Identifier(String),
     String(String),
+    InterpolatedString(Vec<StringPart>),
     Number(f64),
 
     // Keywords
     And,
     Class,
     Else,
+    Enum,
     False,
     Fun,
     For,
     If,
+    Is,
+    Match,
     Nil,
     Or,
     Print,
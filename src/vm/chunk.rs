@@ -60,6 +60,8 @@ impl Chunk {
             Inst::Jmp(_) => Inst::Jmp(offset),
             Inst::JmpIfTrue(_) => Inst::JmpIfTrue(offset),
             Inst::JmpIfFalse(_) => Inst::JmpIfFalse(offset),
+            Inst::JmpIfNil(_) => Inst::JmpIfNil(offset),
+            Inst::JmpIfNotNil(_) => Inst::JmpIfNotNil(offset),
             _ => panic!(
                 "Chunk.patch_jmp expected a Jmp instruction, got: {:?}",
                 self.inst_at(start_idx)
@@ -86,6 +88,15 @@ impl Chunk {
             Mult => format!("mult"),
             Div => format!("div"),
             Mod => format!("mod"),
+            Pow => format!("pow"),
+            IntDiv => format!("int div"),
+            BitAnd => format!("bit and"),
+            BitOr => format!("bit or"),
+            BitXor => format!("bit xor"),
+            BitNot => format!("bit not"),
+            Shl => format!("shl"),
+            Shr => format!("shr"),
+            UShr => format!("ushr"),
             Not => format!("not"),
             True => format!("true"),
             False => format!("false"),
@@ -96,19 +107,36 @@ impl Chunk {
             Lss => format!("lss"),
             Gtq => format!("gtq"),
             Leq => format!("leq"),
+            Is => format!("is"),
             Print => format!("print"),
             Pop => format!("pop"),
             PopN(n) => format!("pop {}", n),
-            DefGlobal(id) => format!("def global {}", id),
-            GetGlobal(id) => format!("get global {}", id),
-            SetGlobal(id) => format!("set global {}", id),
+            DefGlobal(slot) => format!("def global {}", slot),
+            GetGlobal(slot) => format!("get global {}", slot),
+            SetGlobal(slot) => format!("set global {}", slot),
             GetLocal(idx) => format!("get local {}", idx),
             SetLocal(idx) => format!("set local {}", idx),
+            MakeCell(idx) => format!("make cell {}", idx),
+            GetCell(idx) => format!("get cell {}", idx),
+            SetCell(idx) => format!("set cell {}", idx),
+            GetUpvalue(idx) => format!("get upvalue {}", idx),
+            SetUpvalue(idx) => format!("set upvalue {}", idx),
+            Closure(captures) => format!("closure {:?}", captures),
+            Overload => format!("overload"),
             Jmp(offset) => format!("jmp {}", offset),
             JmpIfTrue(offset) => format!("jmp if true {}", offset),
             JmpIfFalse(offset) => format!("jmp if false {}", offset),
+            JmpIfNil(offset) => format!("jmp if nil {}", offset),
+            JmpIfNotNil(offset) => format!("jmp if not nil {}", offset),
             Loop(offset) => format!("jmp -{}", offset),
             Call(args_count) => format!("call {}", args_count),
+            GetProp(id) => format!("get prop {}", id),
+            Array(count) => format!("array {}", count),
+            ArrayPush => format!("array push"),
+            ArrayExtend => format!("array extend"),
+            CallSpread => format!("call spread"),
+            Unpack(count, rest) => format!("unpack {}{}", count, if *rest { " rest" } else { "" }),
+            UnpackProps(props) => format!("unpack props {:?}", props),
         };
 
         if offset > 0 && self.positions[offset - 1].line == self.positions[offset].line {
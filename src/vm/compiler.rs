@@ -1,25 +1,24 @@
+use super::globals::Globals;
 use super::{instructions::FuncObj, Inst, Obj, Value};
-use crate::interpreter::lexical_scope::LexicalScopeResolutionError;
+use crate::interpreter::eval_result::EvalError;
 use crate::parser::expressions::{
     BinaryOperator, Expr, ExprCtx, FuncExpr, Literal, LogicalOperator, UnaryOperator,
 };
-use crate::parser::statements::Stmt;
-use crate::parser::{IdentifierHandle, IdentifierHandlesGenerator, IdentifierUse};
+use crate::parser::statements::{Destructuring, Stmt};
+use crate::parser::{IdentifierHandlesGenerator, IdentifierUse};
+use crate::resolver::resolutions::{Resolutions, Variable};
 use crate::runner::{EloxError, EloxResult};
 use crate::scanner::token::Position;
 use fnv::FnvHashMap;
 use std::ops::Deref;
 use std::rc::Rc;
 
-pub struct Local {
-    handle: IdentifierHandle,
-    depth: usize,
-}
-
 enum JumpKind {
     Unconditional,
     OnTrue,
     OnFalse,
+    OnNil,
+    OnNotNil,
 }
 
 use JumpKind::*;
@@ -34,22 +33,24 @@ pub struct Compiler<'a> {
     func_type: FuncType,
     identifiers: &'a mut IdentifierHandlesGenerator,
     strings: &'a mut FnvHashMap<String, Rc<Obj>>,
-    scope_depth: usize,
-    locals: &'a mut Vec<Local>,
+    resolutions: &'a Resolutions,
+    globals: &'a mut Globals,
+    scopes: Vec<usize>, // the number of locals declared in each scope
 }
 
 impl<'a> Compiler<'a> {
     pub fn new(
-        locals: &'a mut Vec<Local>,
-        scope_depth: usize,
+        resolutions: &'a Resolutions,
+        globals: &'a mut Globals,
         func: &'a mut FuncObj,
         func_type: FuncType,
         identifiers: &'a mut IdentifierHandlesGenerator,
         strings: &'a mut FnvHashMap<String, Rc<Obj>>,
     ) -> Compiler<'a> {
         Compiler {
-            locals,
-            scope_depth,
+            resolutions,
+            globals,
+            scopes: vec![],
             func,
             func_type,
             identifiers,
@@ -67,113 +68,68 @@ impl<'a> Compiler<'a> {
         self.func.chunk.write_constant(val, pos);
     }
 
-    fn get_named_variable(&mut self, handle: IdentifierHandle, pos: Position) -> EloxResult {
-        if let Some(idx) = self.resolve_local(handle, pos)? {
-            self.emit(Inst::GetLocal(idx + 1), pos); // + 1 since idx 0 is used by the surrounding func
-        } else {
-            self.emit(Inst::GetGlobal(handle), pos);
-        }
-
-        Ok(())
-    }
-
-    fn set_named_variable(&mut self, handle: IdentifierHandle, pos: Position) -> EloxResult {
-        if let Some(idx) = self.resolve_local(handle, pos)? {
-            self.emit(Inst::SetLocal(idx + 1), pos); // + 1 since idx 0 is used by the surrounding func
-        } else {
-            self.emit(Inst::SetGlobal(handle), pos);
-        }
+    fn get_named_variable(&mut self, identifier: &IdentifierUse) {
+        let inst = match self.resolutions.variable(identifier) {
+            Variable::Global => Inst::GetGlobal(self.globals.slot(identifier.name)),
+            Variable::Local(slot) => Inst::GetLocal(slot),
+            Variable::Cell(slot) => Inst::GetCell(slot),
+            Variable::Upvalue(idx) => Inst::GetUpvalue(idx),
+        };
 
-        Ok(())
+        self.emit(inst, identifier.pos);
     }
 
-    fn resolve_local(
-        &self,
-        handle: IdentifierHandle,
-        pos: Position,
-    ) -> Result<Option<usize>, EloxError> {
-        for (idx, local) in self.locals.iter().rev().enumerate() {
-            if local.handle == handle {
-                return if local.depth == usize::max_value() {
-                    Err(EloxError::Resolution(
-                        LexicalScopeResolutionError::VariableUsedInItsInitializer(
-                            pos,
-                            self.identifiers.name(handle),
-                        ),
-                    ))
-                } else {
-                    Ok(Some(self.locals.len() - 1 - idx))
-                };
-            }
-        }
+    fn set_named_variable(&mut self, identifier: &IdentifierUse) {
+        let inst = match self.resolutions.variable(identifier) {
+            Variable::Global => Inst::SetGlobal(self.globals.slot(identifier.name)),
+            Variable::Local(slot) => Inst::SetLocal(slot),
+            Variable::Cell(slot) => Inst::SetCell(slot),
+            Variable::Upvalue(idx) => Inst::SetUpvalue(idx),
+        };
 
-        Ok(None)
+        self.emit(inst, identifier.pos);
     }
 
-    fn declare_variable(&mut self, handle: IdentifierHandle, pos: Position) -> EloxResult {
-        // Global variables are implicitly declared.
-        if self.scope_depth == 0 {
-            return Ok(());
-        }
-
-        for local in self.locals.iter().rev() {
-            if local.depth < self.scope_depth {
-                break;
+    // the value of the variable is on top of the stack, locals keep it in their slot
+    fn define_variable(&mut self, identifier: &IdentifierUse, pos: Position) {
+        match self.resolutions.variable(identifier) {
+            Variable::Global => {
+                let slot = self.globals.slot(identifier.name);
+                self.emit(Inst::DefGlobal(slot), pos);
             }
-
-            if local.handle == handle {
-                return Err(EloxError::Resolution(
-                    LexicalScopeResolutionError::DuplicateVariableDeclaration(
-                        pos,
-                        self.identifiers.name(handle),
-                    ),
-                ));
+            Variable::Local(_) => self.add_local(),
+            Variable::Cell(slot) => {
+                self.add_local();
+                self.emit(Inst::MakeCell(slot), pos);
             }
+            Variable::Upvalue(_) => unreachable!("a declaration cannot be an upvalue"),
         }
-
-        self.add_local(handle);
-
-        return Ok(());
     }
 
-    fn add_local(&mut self, handle: IdentifierHandle) {
-        self.locals.push(Local {
-            handle,
-            depth: usize::max_value(), // mark as uninitialized
-        });
+    // the values of the variables are on the stack, in declaration order
+    fn define_variables(&mut self, identifiers: &[IdentifierUse], pos: Position) {
+        for identifier in identifiers.iter().rev() {
+            self.define_variable(identifier, pos);
+        }
     }
 
-    fn mark_initialized(&mut self) {
-        if self.scope_depth != 0 {
-            let mut local = self
-                .locals
-                .last_mut()
-                .expect("Could not mark as initialized");
-            local.depth = self.scope_depth;
+    fn add_local(&mut self) {
+        if let Some(count) = self.scopes.last_mut() {
+            *count += 1;
         }
     }
 
     #[inline]
     fn begin_scope(&mut self) {
-        self.scope_depth += 1;
+        self.scopes.push(0);
     }
 
     #[inline]
     fn end_scope(&mut self, pos: Position) {
-        self.scope_depth -= 1;
-        let mut pops = 0;
-
-        while self.locals.len() > 0 && self.locals[self.locals.len() - 1].depth > self.scope_depth {
-            self.locals.pop();
-            pops += 1;
-        }
-
-        // println!("POPPPY");
-
-        match pops {
-            0 => {}
-            1 => self.emit(Inst::Pop, pos),
-            _ => self.emit(Inst::PopN(pops), pos),
+        match self.scopes.pop() {
+            Some(0) | None => {}
+            Some(1) => self.emit(Inst::Pop, pos),
+            Some(pops) => self.emit(Inst::PopN(pops), pos),
         };
     }
 
@@ -182,6 +138,8 @@ impl<'a> Compiler<'a> {
             Unconditional => Inst::Jmp(0),
             OnTrue => Inst::JmpIfTrue(0),
             OnFalse => Inst::JmpIfFalse(0),
+            OnNil => Inst::JmpIfNil(0),
+            OnNotNil => Inst::JmpIfNotNil(0),
         };
 
         self.emit(inst, pos);
@@ -240,9 +198,11 @@ impl<'a> Compiler<'a> {
             Expr::Unary(unary_expr) => {
                 let expr = unary_expr.deref();
                 self.compile_expr(&expr.right)?;
+                // operand type errors are reported on the operand, like the tree-walker
                 match expr.operator {
-                    UnaryOperator::Minus => self.emit(Inst::Neg, expr_ctx.pos),
-                    UnaryOperator::Bang => self.emit(Inst::Not, expr_ctx.pos),
+                    UnaryOperator::Minus => self.emit(Inst::Neg, expr.right.pos),
+                    UnaryOperator::Bang => self.emit(Inst::Not, expr.right.pos),
+                    UnaryOperator::Tilde => self.emit(Inst::BitNot, expr.right.pos),
                 }
             }
             Expr::Binary(bin_expr) => {
@@ -257,23 +217,29 @@ impl<'a> Compiler<'a> {
                     BinaryOperator::Star => self.emit(Inst::Mult, op_ctx.pos),
                     BinaryOperator::Slash => self.emit(Inst::Div, op_ctx.pos),
                     BinaryOperator::Percent => self.emit(Inst::Mod, op_ctx.pos),
+                    BinaryOperator::StarStar => self.emit(Inst::Pow, op_ctx.pos),
+                    BinaryOperator::SlashSlash => self.emit(Inst::IntDiv, op_ctx.pos),
+                    BinaryOperator::Ampersand => self.emit(Inst::BitAnd, op_ctx.pos),
+                    BinaryOperator::Pipe => self.emit(Inst::BitOr, op_ctx.pos),
+                    BinaryOperator::Caret => self.emit(Inst::BitXor, op_ctx.pos),
+                    BinaryOperator::LessLess => self.emit(Inst::Shl, op_ctx.pos),
+                    BinaryOperator::GreaterGreater => self.emit(Inst::Shr, op_ctx.pos),
+                    BinaryOperator::GreaterGreaterGreater => self.emit(Inst::UShr, op_ctx.pos),
                     BinaryOperator::EqualEqual => self.emit(Inst::Equ, op_ctx.pos),
                     BinaryOperator::BangEqual => self.emit(Inst::Neq, op_ctx.pos),
                     BinaryOperator::Greater => self.emit(Inst::Gtr, op_ctx.pos),
                     BinaryOperator::Less => self.emit(Inst::Lss, op_ctx.pos),
                     BinaryOperator::GreaterEqual => self.emit(Inst::Gtq, op_ctx.pos),
                     BinaryOperator::LessEqual => self.emit(Inst::Leq, op_ctx.pos),
+                    BinaryOperator::Is => self.emit(Inst::Is, op_ctx.pos),
                 }
             }
             Expr::Var(var_expr) => {
-                self.get_named_variable(var_expr.identifier.name, var_expr.identifier.pos)?;
+                self.get_named_variable(&var_expr.identifier);
             }
             Expr::Assign(assignment_expr) => {
                 self.compile_expr(&assignment_expr.expr)?;
-                self.set_named_variable(
-                    assignment_expr.identifier.name,
-                    assignment_expr.identifier.pos,
-                )?;
+                self.set_named_variable(&assignment_expr.identifier);
             }
             Expr::Logical(logical_expr) => {
                 let logical_expr = logical_expr.deref();
@@ -296,36 +262,110 @@ impl<'a> Compiler<'a> {
                         self.emit(Inst::Pop, logical_expr.right.pos);
                         self.compile_expr(&logical_expr.right)?;
 
+                        self.patch_jmp(short_circuit);
+                    }
+                    LogicalOperator::NilCoalescing => {
+                        let short_circuit = self.emit_jmp(OnNotNil, logical_expr.left.pos);
+
+                        self.emit(Inst::Pop, logical_expr.right.pos);
+                        self.compile_expr(&logical_expr.right)?;
+
                         self.patch_jmp(short_circuit);
                     }
                 }
             }
-            Expr::Func(func_expr) => {
-                self.compile_func(func_expr, FuncType::FUNC)?;
+            Expr::Conditional(cond_expr) => {
+                self.compile_expr(&cond_expr.condition)?;
+
+                let else_jmp = self.emit_jmp(OnFalse, cond_expr.condition.pos);
+                // pop the condition
+                self.emit(Inst::Pop, cond_expr.condition.pos);
+                self.compile_expr(&cond_expr.then_branch)?;
+
+                let end_jmp = self.emit_jmp(Unconditional, cond_expr.condition.pos);
+                self.patch_jmp(else_jmp);
+                self.emit(Inst::Pop, cond_expr.condition.pos);
+                self.compile_expr(&cond_expr.else_branch)?;
+
+                self.patch_jmp(end_jmp);
+            }
+            Expr::Func(func_expr) => match &func_expr.name {
+                Some(id) => self.compile_func_decl(func_expr, id)?,
+                None => self.compile_func(func_expr, FuncType::FUNC)?,
+            },
+            Expr::Call(_) | Expr::Get(_) => {
+                // an optional member on a nil object jumps over the rest of the chain,
+                // leaving the nil object on the stack as the result
+                let mut nil_jmps = Vec::new();
+                self.compile_chain(expr_ctx, &mut nil_jmps)?;
+
+                for nil_jmp in nil_jmps {
+                    self.patch_jmp(nil_jmp);
+                }
+            }
+            Expr::ArrayDeclExpr(array_decl) => {
+                self.compile_array(&array_decl.values, expr_ctx.pos)?;
+            }
+            Expr::Set(_) => return Err(unsupported("property assignment", expr_ctx.pos)),
+            Expr::This(_) => return Err(unsupported("this", expr_ctx.pos)),
+            Expr::Super(_) => return Err(unsupported("super", expr_ctx.pos)),
+            Expr::Match(_) => return Err(unsupported("match", expr_ctx.pos)),
+            Expr::Spread(_) => return Err(unsupported("spread", expr_ctx.pos)),
+        }
+
+        Ok(())
+    }
+
+    // compiles a chain of member accesses and calls, collecting the jumps of its optional members
+    fn compile_chain(&mut self, expr_ctx: &ExprCtx, nil_jmps: &mut Vec<usize>) -> EloxResult {
+        match &expr_ctx.expr {
+            Expr::Get(get_expr) => {
+                self.compile_chain(&get_expr.object, nil_jmps)?;
+
+                if get_expr.optional {
+                    nil_jmps.push(self.emit_jmp(OnNil, expr_ctx.pos));
+                }
+
+                self.emit(Inst::GetProp(get_expr.property.name), expr_ctx.pos);
             }
             Expr::Call(call_expr) => {
-                self.compile_expr(&call_expr.callee)?;
-                for arg in &call_expr.args {
-                    self.compile_expr(&arg)?;
+                self.compile_chain(&call_expr.callee, nil_jmps)?;
+
+                if call_expr.args.iter().any(is_spread) {
+                    self.compile_array(&call_expr.args, call_expr.callee.pos)?;
+                    self.emit(Inst::CallSpread, call_expr.callee.pos);
+                } else {
+                    for arg in &call_expr.args {
+                        self.compile_expr(&arg)?;
+                    }
+                    self.emit(Inst::Call(call_expr.args.len()), call_expr.callee.pos);
                 }
-                self.emit(Inst::Call(call_expr.args.len()), call_expr.callee.pos);
             }
-            _ => panic!("Unimplemented expr"),
+            _ => self.compile_expr(expr_ctx)?,
         }
 
         Ok(())
     }
 
-    fn emit_identifier(&mut self, id: &IdentifierUse) -> EloxResult {
-        // self.emit_constant(obj, id.pos);
-        // self.get_named_variable(id.name, id.pos)?;
-        self.declare_variable(id.name, id.pos)?;
-        self.define_variable(id.name, id.pos);
-        // self.mark_initialized();
+    // leaves an array of the values on the stack, ...values are expanded in place
+    fn compile_array(&mut self, values: &[ExprCtx], pos: Position) -> EloxResult {
+        let count = values.iter().take_while(|val| !is_spread(val)).count();
+
+        for val in &values[..count] {
+            self.compile_expr(val)?;
+        }
 
-        // if self.scope_depth > 0 {
-        //     self.mark_initialized();
-        // }
+        self.emit(Inst::Array(count), pos);
+
+        for val in &values[count..] {
+            if let Expr::Spread(spread) = &val.expr {
+                self.compile_expr(&spread.value)?;
+                self.emit(Inst::ArrayExtend, val.pos);
+            } else {
+                self.compile_expr(val)?;
+                self.emit(Inst::ArrayPush, val.pos);
+            }
+        }
 
         Ok(())
     }
@@ -336,16 +376,12 @@ impl<'a> Compiler<'a> {
         } else {
             0
         };
-        let name = if let Some(id) = func_expr.name {
-            self.declare_variable(id.name, id.pos)?;
-            Some(id.name)
-        } else {
-            None
-        };
+
+        let name = func_expr.name.map(|id| id.name);
         let mut func = FuncObj::new(name, arity);
         let mut compiler = Compiler::new(
-            &mut self.locals,
-            self.scope_depth,
+            self.resolutions,
+            &mut self.globals,
             &mut func,
             type_,
             &mut self.identifiers,
@@ -354,9 +390,10 @@ impl<'a> Compiler<'a> {
 
         compiler.begin_scope();
 
+        // the arguments are already in their slots
         if let Some(params) = &func_expr.params {
             for param in params {
-                compiler.emit_identifier(param.identifier())?;
+                compiler.define_variable(param.identifier(), func_expr.pos);
             }
         }
 
@@ -364,7 +401,6 @@ impl<'a> Compiler<'a> {
             compiler.compile_stmt(stmt)?;
         }
         compiler.end();
-        compiler.end_scope(func_expr.pos);
 
         func.chunk.disassemble(&format!(
             "<fn {:?}>",
@@ -377,29 +413,49 @@ impl<'a> Compiler<'a> {
         let func_val = Value::Object(Rc::new(Obj::Func(Rc::new(func))));
         self.emit_constant(func_val, func_expr.pos);
 
-        if let Some(id) = func_expr.name {
-            self.define_variable(id.name, id.pos);
+        let captures = self.resolutions.captures(func_expr);
+
+        if !captures.is_empty() {
+            self.emit(Inst::Closure(captures.to_vec()), func_expr.pos);
         }
 
         Ok(())
     }
 
-    fn define_variable(&mut self, global: IdentifierHandle, pos: Position) {
-        if self.scope_depth > 0 {
-            self.mark_initialized();
-            return;
+    fn compile_func_decl(&mut self, func_expr: &FuncExpr, id: &IdentifierUse) -> EloxResult {
+        // an overload replaces the value of the variable declared by the first declaration
+        if self.resolutions.is_overload(id) {
+            self.get_named_variable(id);
+            self.compile_func(func_expr, FuncType::FUNC)?;
+            self.emit(Inst::Overload, id.pos);
+            self.set_named_variable(id);
+            self.emit(Inst::Pop, id.pos);
+            return Ok(());
         }
 
-        self.emit(Inst::DefGlobal(global), pos);
+        match self.resolutions.variable(id) {
+            // the cell exists before the closure so that the function can capture itself
+            Variable::Cell(slot) => {
+                self.emit(Inst::Nil, id.pos);
+                self.define_variable(id, id.pos);
+                self.compile_func(func_expr, FuncType::FUNC)?;
+                self.emit(Inst::SetCell(slot), id.pos);
+                self.emit(Inst::Pop, id.pos);
+            }
+            _ => {
+                self.compile_func(func_expr, FuncType::FUNC)?;
+                self.define_variable(id, id.pos);
+            }
+        }
+
+        Ok(())
     }
 
     fn compile_stmt(&mut self, stmt: &Stmt) -> EloxResult {
         match stmt {
             Stmt::Expr(expr_stmt) => {
-                let pop = match expr_stmt.expr.expr {
-                    Expr::Func(_) => false,
-                    _ => true,
-                };
+                // a function declaration leaves its value in the variable
+                let pop = !matches!(&expr_stmt.expr.expr, Expr::Func(func) if func.name.is_some());
 
                 self.compile_expr(&expr_stmt.expr)?;
                 if pop {
@@ -411,14 +467,30 @@ impl<'a> Compiler<'a> {
                 self.emit(Inst::Print, print_stmt.pos);
             }
             Stmt::VarDecl(var_decl) => {
-                self.declare_variable(var_decl.identifier.name, var_decl.identifier.pos)?;
                 if let Some(init) = &var_decl.initializer {
                     self.compile_expr(init)?;
                 } else {
                     self.emit(Inst::Nil, var_decl.identifier.pos);
                 }
 
-                self.define_variable(var_decl.identifier.name, var_decl.pos);
+                self.define_variable(&var_decl.identifier, var_decl.pos);
+            }
+            Stmt::DestructuringDecl(decl) => {
+                let identifiers = decl.target.identifiers();
+
+                self.compile_expr(&decl.initializer)?;
+
+                match &decl.target {
+                    Destructuring::Array(names, rest) => {
+                        self.emit(Inst::Unpack(names.len(), rest.is_some()), decl.pos);
+                    }
+                    Destructuring::Object(fields) => {
+                        let props = fields.iter().map(|field| field.name).collect();
+                        self.emit(Inst::UnpackProps(props), decl.pos);
+                    }
+                }
+
+                self.define_variables(&identifiers, decl.pos);
             }
             Stmt::Block(block) => {
                 self.begin_scope();
@@ -466,14 +538,29 @@ impl<'a> Compiler<'a> {
             Stmt::Return(ret_stmt) => {
                 if let Some(ret) = &ret_stmt.value {
                     self.compile_expr(ret)?;
-                    self.emit(Inst::Ret, ret_stmt.pos);
                 } else {
-                    self.emit(Inst::Nil, Position { line: 0, col: 0 });
+                    self.emit(Inst::Nil, ret_stmt.pos);
                 }
+
+                self.emit(Inst::Ret, ret_stmt.pos);
+            }
+            Stmt::ClassDecl(class_decl) => {
+                return Err(unsupported("class", class_decl.identifier.pos));
+            }
+            Stmt::EnumDecl(enum_decl) => {
+                return Err(unsupported("enum", enum_decl.identifier.pos));
             }
-            _ => panic!("Unimplemented stmt"),
         }
 
         Ok(())
     }
 }
+
+// the constructs the VM doesn't compile yet
+fn unsupported(construct: &str, pos: Position) -> EloxError {
+    EloxError::Eval(EvalError::NotSupportedOnTheVm(pos, construct.into()))
+}
+
+fn is_spread(expr_ctx: &ExprCtx) -> bool {
+    matches!(expr_ctx.expr, Expr::Spread(_))
+}
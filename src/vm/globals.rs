@@ -0,0 +1,61 @@
+use super::Value;
+use crate::parser::IdentifierHandle;
+use fnv::FnvHashMap;
+
+// Globals are resolved to dense slots at compile time. A name gets its slot the
+// first time it is compiled, a use or a definition, so forward references and
+// redefinitions in the REPL late-bind to the same slot. An unset slot is an
+// undefined variable, its name is kept for the error.
+
+pub struct Globals {
+    slots: FnvHashMap<IdentifierHandle, usize>,
+    names: Vec<IdentifierHandle>, // slot -> name
+    values: Vec<Option<Value>>,
+}
+
+impl Globals {
+    pub fn new() -> Globals {
+        Globals {
+            slots: FnvHashMap::default(),
+            names: vec![],
+            values: vec![],
+        }
+    }
+
+    pub fn slot(&mut self, name: IdentifierHandle) -> usize {
+        if let Some(&slot) = self.slots.get(&name) {
+            return slot;
+        }
+
+        let slot = self.names.len();
+        self.slots.insert(name, slot);
+        self.names.push(name);
+        self.values.push(None);
+
+        slot
+    }
+
+    pub fn name(&self, slot: usize) -> IdentifierHandle {
+        self.names[slot]
+    }
+
+    #[inline]
+    pub fn get(&self, slot: usize) -> Option<&Value> {
+        self.values[slot].as_ref()
+    }
+
+    #[inline]
+    pub fn is_defined(&self, slot: usize) -> bool {
+        self.values[slot].is_some()
+    }
+
+    #[inline]
+    pub fn set(&mut self, slot: usize, val: Value) {
+        self.values[slot] = Some(val);
+    }
+
+    pub fn define(&mut self, name: IdentifierHandle, val: Value) {
+        let slot = self.slot(name);
+        self.set(slot, val);
+    }
+}
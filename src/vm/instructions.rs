@@ -1,6 +1,8 @@
 use crate::parser::IdentifierHandle;
 use super::{EloxError, Position, Chunk};
+use crate::resolver::resolutions::Capture;
 use fnv::FnvHashMap;
+use std::cell::RefCell;
 use std::fmt;
 use std::rc::Rc;
 
@@ -14,6 +16,15 @@ pub enum Inst {
     Mult,
     Div,
     Mod,
+    Pow,
+    IntDiv,
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    Shl,
+    Shr,
+    UShr,
     Not,
     True,
     False,
@@ -24,19 +35,37 @@ pub enum Inst {
     Lss,
     Gtq,
     Leq,
+    Is,
     Print,
     Pop,
-    PopN(usize), // n
-    DefGlobal(IdentifierHandle),
-    GetGlobal(IdentifierHandle),
-    SetGlobal(IdentifierHandle),
+    PopN(usize),       // n
+    DefGlobal(usize),  // global slot
+    GetGlobal(usize),  // global slot
+    SetGlobal(usize),  // global slot
     GetLocal(usize),   // stack offset
     SetLocal(usize),   // stack offset
+    MakeCell(usize),   // stack offset of a captured local
+    GetCell(usize),    // stack offset
+    SetCell(usize),    // stack offset
+    GetUpvalue(usize), // upvalue index
+    SetUpvalue(usize), // upvalue index
+    Closure(Vec<Capture>),
+    // adds the function on top of the stack to the overload set below it
+    Overload,
     Jmp(usize),        // addr
     JmpIfFalse(usize), // addr
     JmpIfTrue(usize),  // addr
+    JmpIfNil(usize),   // addr
+    JmpIfNotNil(usize), // addr
     Loop(usize),       // Jumps backwards to addr
     Call(usize),       // args count
+    GetProp(IdentifierHandle),
+    Array(usize),      // values count
+    ArrayPush,
+    ArrayExtend,
+    CallSpread,          // args array
+    Unpack(usize, bool), // values count, has rest
+    UnpackProps(Vec<IdentifierHandle>),
 }
 
 #[derive(Clone, Debug)]
@@ -68,11 +97,13 @@ impl FuncObj {
     }
 }
 
-pub type NativeFn = (Fn(Position, Vec<Value>) -> Result<Value, EloxError>);
+pub type NativeFn =
+    (Fn(Position, Vec<Value>, &mut FnvHashMap<String, Rc<Obj>>) -> Result<Value, EloxError>);
 
 pub struct NativeFunc {
     pub name: IdentifierHandle,
-    pub arity: usize,
+    pub min_arity: usize,
+    pub max_arity: usize, // missing optional args are not pushed
     pub func: Box<NativeFn>,
 }
 
@@ -82,18 +113,73 @@ impl fmt::Debug for NativeFunc {
     }
 }
 
+// receiver, args, interned strings
+pub type NativeMethodFn =
+    dyn Fn(Position, &Value, Vec<Value>, &mut FnvHashMap<String, Rc<Obj>>) -> Result<Value, EloxError>;
+
+pub struct NativeMethod {
+    pub name: IdentifierHandle,
+    pub min_arity: usize,
+    pub max_arity: usize,
+    pub func: Box<NativeMethodFn>,
+}
+
+impl fmt::Debug for NativeMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<native method>")
+    }
+}
+
+// a global grouping native functions and constants, like Math
+#[derive(Debug, Clone)]
+pub struct Namespace {
+    pub name: String,
+    pub constants: FnvHashMap<IdentifierHandle, Value>,
+    pub methods: FnvHashMap<IdentifierHandle, Rc<NativeMethod>>,
+}
+
+// the builtin classes, the VM doesn't compile classes yet so they are only used by 'is'
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NativeClass {
+    Array,
+    String,
+}
+
+impl NativeClass {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NativeClass::Array => "Array",
+            NativeClass::String => "String",
+        }
+    }
+
+    pub fn is_class_of(&self, value: &Value) -> bool {
+        match self {
+            NativeClass::Array => value.as_array().is_some(),
+            NativeClass::String => value.as_str().is_some(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Obj {
     Str(String),
     Func(Rc<FuncObj>),
+    Closure(Rc<FuncObj>, Vec<Value>), // upvalue cells
+    Cell(RefCell<Value>),
+    Overloads(Vec<Value>), // functions with different arities
     Native(Rc<NativeFunc>),
+    BoundNative(Value, Rc<NativeMethod>),
+    Array(RefCell<Vec<Value>>),
+    Namespace(Namespace),
+    NativeClass(NativeClass),
 }
 
 impl fmt::Display for Obj {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Obj::Str(string) => write!(f, "{}", string),
-            Obj::Func(func) => write!(
+            Obj::Func(func) | Obj::Closure(func, _) => write!(
                 f,
                 "<function {}>",
                 if let Some(name) = func.name {
@@ -102,7 +188,22 @@ impl fmt::Display for Obj {
                     "anonymous".into()
                 }
             ),
+            Obj::Cell(val) => val.borrow().fmt(f),
+            Obj::Overloads(overloads) => overloads[0].fmt(f),
             Obj::Native(_) => write!(f, "<native function>"),
+            Obj::BoundNative(_, _) => write!(f, "<native method>"),
+            Obj::Namespace(namespace) => write!(f, "<namespace {}>", namespace.name),
+            Obj::NativeClass(class) => write!(f, "<class {}>", class.name()),
+            Obj::Array(values) => write!(
+                f,
+                "[{}]",
+                values
+                    .borrow()
+                    .iter()
+                    .map(|val| format!("{}", val))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
         }
     }
 }
@@ -111,8 +212,13 @@ impl Obj {
     pub fn type_(&self) -> String {
         match self {
             Obj::Str(_) => "string",
-            Obj::Func(_) => "function",
+            Obj::Func(_) | Obj::Closure(_, _) | Obj::Overloads(_) => "function",
+            Obj::Cell(_) => "cell",
             Obj::Native(_) => "native function",
+            Obj::BoundNative(_, _) => "native method",
+            Obj::Namespace(_) => "namespace",
+            Obj::NativeClass(_) => "class",
+            Obj::Array(_) => "array",
         }
         .into()
     }
@@ -148,6 +254,74 @@ impl Value {
         .into()
     }
 
+    pub fn as_str(&self) -> Option<&str> {
+        if let Value::Object(obj) = self {
+            if let Obj::Str(s) = &**obj {
+                return Some(s);
+            }
+        }
+
+        None
+    }
+
+    pub fn as_array(&self) -> Option<&RefCell<Vec<Value>>> {
+        if let Value::Object(obj) = self {
+            if let Obj::Array(values) = &**obj {
+                return Some(values);
+            }
+        }
+
+        None
+    }
+
+    pub fn as_func(&self) -> Option<&Rc<FuncObj>> {
+        if let Value::Object(obj) = self {
+            if let Obj::Func(func) | Obj::Closure(func, _) = &**obj {
+                return Some(func);
+            }
+        }
+
+        None
+    }
+
+    pub fn as_cell(&self) -> Option<&RefCell<Value>> {
+        if let Value::Object(obj) = self {
+            if let Obj::Cell(val) = &**obj {
+                return Some(val);
+            }
+        }
+
+        None
+    }
+
+    pub fn new_cell(val: Value) -> Value {
+        Value::Object(Rc::new(Obj::Cell(RefCell::new(val))))
+    }
+
+    pub fn as_namespace(&self) -> Option<&Namespace> {
+        if let Value::Object(obj) = self {
+            if let Obj::Namespace(namespace) = &**obj {
+                return Some(namespace);
+            }
+        }
+
+        None
+    }
+
+    pub fn as_native_class(&self) -> Option<NativeClass> {
+        if let Value::Object(obj) = self {
+            if let Obj::NativeClass(class) = &**obj {
+                return Some(*class);
+            }
+        }
+
+        None
+    }
+
+    pub fn new_array(values: Vec<Value>) -> Value {
+        Value::Object(Rc::new(Obj::Array(RefCell::new(values))))
+    }
+
     pub fn new_str(s: &str, strings: &mut FnvHashMap<String, Rc<Obj>>) -> Value {
         let s = String::from(s);
 
@@ -1,21 +1,33 @@
 pub mod chunk;
 mod compiler;
+mod globals;
 pub mod instructions;
+mod natives;
 pub mod target;
 pub mod wasm_module;
+pub mod wasm_runtime;
 
+use crate::interpreter::bitwise;
 use crate::interpreter::eval_result::EvalError;
 use crate::interpreter::host::Host;
+use crate::interpreter::lox_string::parse_number;
 use crate::parser::expressions::{BinaryOperator, UnaryOperator};
-use crate::parser::{IdentifierHandle, IdentifierHandlesGenerator, Parser};
-use crate::runner::{EloxError, EloxResult, EloxRunner};
+use crate::parser::{Identifier, IdentifierHandle, IdentifierHandlesGenerator, Parser};
+use crate::resolver::lexical_scope::Resolver;
+use crate::resolver::resolutions::Capture;
+use crate::runner::{EloxError, EloxResult, EloxRunner, EloxTestRunner};
 use crate::scanner::scanner_result::ErrorPosition;
 use crate::scanner::token::Position;
 use crate::scanner::Scanner;
 use chunk::Chunk;
-use compiler::{Compiler, FuncType, Local};
+use compiler::{Compiler, FuncType};
 use fnv::FnvHashMap;
-use instructions::{FuncObj, Inst, NativeFunc, Obj, Value};
+use globals::Globals;
+use instructions::{FuncObj, Inst, NativeClass, NativeFunc, Obj, Value};
+use natives::{
+    array_methods, assertion_natives, math_namespace, reflection_natives, string_methods,
+    NativeMethods, TestRegistry,
+};
 use std::fmt;
 use std::rc::Rc;
 
@@ -44,6 +56,16 @@ macro_rules! binary_op {
         };
     });
 
+    // operators without a Rust infix equivalent
+    (fn $f: expr, $self: tt, $bin_op: expr) => ({
+        let b = $self.stack.pop();
+        let a = $self.stack.pop();
+        match (&a, &b) {
+            (&Value::Number(a), &Value::Number(b)) => $self.stack.push(Value::Number($f(a, b))),
+            _ => return Err(EloxError::Eval(EvalError::UnexpectedBinaryOperatorOperands($self.pos(), $bin_op, a.type_(), b.type_()))),
+        };
+    });
+
     ($op: tt, $self: tt, $bin_op: expr, $ValType: ident) => ({
         let b = $self.stack.pop();
         let a = $self.stack.pop();
@@ -57,14 +79,16 @@ macro_rules! binary_op {
 #[derive(Clone)]
 struct CallFrame {
     pub func: Rc<FuncObj>,
+    pub upvalues: Vec<Value>, // cells captured by a closure
     pub stack_top: usize,
     pub ip: usize,
 }
 
 impl CallFrame {
-    pub fn new(func: &Rc<FuncObj>, stack_top: usize, ip: usize) -> CallFrame {
+    pub fn new(func: &Rc<FuncObj>, upvalues: &[Value], stack_top: usize, ip: usize) -> CallFrame {
         CallFrame {
             func: Rc::clone(func),
+            upvalues: upvalues.to_vec(),
             stack_top,
             ip,
         }
@@ -80,20 +104,30 @@ pub struct EloxVM {
     frames_count: usize,
     identifiers: IdentifierHandlesGenerator,
     strings: FnvHashMap<String, Rc<Obj>>,
-    globals: FnvHashMap<IdentifierHandle, Value>,
+    globals: Globals,
+    string_methods: NativeMethods,
+    array_methods: NativeMethods,
+    tests: TestRegistry,
 }
 
 impl EloxVM {
     pub fn new() -> EloxVM {
+        EloxVM::with_host(Host::default())
+    }
+
+    pub fn with_host(host: Host) -> EloxVM {
         let mut vm = EloxVM {
-            host: Rc::new(Host::default()),
+            host: Rc::new(host),
             stack: EloxVMStack::with_capacity(256),
             // TODO: Use MaybeUninit
             call_frames: Vec::with_capacity(64),
             frames_count: 0,
             identifiers: IdentifierHandlesGenerator::new(),
             strings: FnvHashMap::default(),
-            globals: FnvHashMap::default(),
+            globals: Globals::new(),
+            string_methods: FnvHashMap::default(),
+            array_methods: FnvHashMap::default(),
+            tests: TestRegistry::default(),
         };
 
         vm.define_natives();
@@ -105,8 +139,9 @@ impl EloxVM {
         let host_clock = Rc::clone(&self.host.clock);
         let clock = NativeFunc {
             name: self.identifiers.by_name("clock"),
-            arity: 0,
-            func: Box::new(move |pos, _| Ok(Value::Number((host_clock)(pos)?))),
+            min_arity: 0,
+            max_arity: 0,
+            func: Box::new(move |pos, _, _| Ok(Value::Number((host_clock)(pos)?))),
             // func: Box::new(|pos, _| {
             //     if let Ok(now) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
             //         Ok(Value::Number(now.as_millis() as f64 / 1000f64))
@@ -117,10 +152,67 @@ impl EloxVM {
         };
 
         self.define_native(clock);
+
+        let str_ = NativeFunc {
+            name: self.identifiers.by_name("str"),
+            min_arity: 1,
+            max_arity: 1,
+            func: Box::new(|_, args, strings| Ok(Value::new_str(&format!("{}", args[0]), strings))),
+        };
+
+        self.define_native(str_);
+
+        let num = NativeFunc {
+            name: self.identifiers.by_name("num"),
+            min_arity: 1,
+            max_arity: 1,
+            func: Box::new(|_, args, _| {
+                Ok(match &args[0] {
+                    Value::Number(n) => Value::Number(*n),
+                    val => val
+                        .as_str()
+                        .and_then(parse_number)
+                        .map_or(Value::Nil, Value::Number),
+                })
+            }),
+        };
+
+        self.define_native(num);
+
+        for native in reflection_natives(&mut self.identifiers) {
+            self.define_native(native);
+        }
+
+        for native in assertion_natives(&mut self.identifiers, &self.tests) {
+            self.define_native(native);
+        }
+
+        let math = math_namespace(&mut self.identifiers);
+        self.globals.define(
+            self.identifiers.by_name("Math"),
+            Value::Object(Rc::new(Obj::Namespace(math))),
+        );
+
+        for class in [NativeClass::Array, NativeClass::String].iter() {
+            self.globals.define(
+                self.identifiers.by_name(class.name()),
+                Value::Object(Rc::new(Obj::NativeClass(*class))),
+            );
+        }
+
+        self.string_methods = string_methods(&mut self.identifiers);
+        self.array_methods = array_methods(&mut self.identifiers);
+    }
+
+    // drops the frames and values left by a run stopped by an error
+    fn unwind(&mut self, frames_count: usize, stack_size: usize) {
+        self.call_frames.truncate(frames_count);
+        self.frames_count = frames_count;
+        self.stack.pop_n(self.stack.size() - stack_size);
     }
 
     fn define_native(&mut self, func: NativeFunc) {
-        self.globals.insert(
+        self.globals.define(
             func.name,
             Value::Object(Rc::new(Obj::Native(Rc::new(func)))),
         );
@@ -175,36 +267,41 @@ impl EloxVM {
                         continue;
                     }
                 }
-                Inst::DefGlobal(id_handle) => {
+                Inst::JmpIfNil(offset) => {
+                    if let Value::Nil = self.stack.peek(0) {
+                        self.call_frames[self.frames_count - 1].ip = ip + offset;
+                        continue;
+                    }
+                }
+                Inst::JmpIfNotNil(offset) => {
+                    if !matches!(self.stack.peek(0), Value::Nil) {
+                        self.call_frames[self.frames_count - 1].ip = ip + offset;
+                        continue;
+                    }
+                }
+                Inst::DefGlobal(slot) => {
                     // we peek instead of popping to ensure that the VM still has
-                    // access to the value while inserting it to the globals HashMap
-                    // in the event of a gc while inserting
+                    // access to the value while storing it in the globals
+                    // in the event of a gc while storing
                     let val = self.stack.peek(0);
-                    self.globals.insert(*id_handle, val);
+                    self.globals.set(*slot, val);
                     self.stack.pop();
                 }
-                Inst::GetGlobal(id_handle) => {
-                    let var = self.globals.get(&id_handle);
-                    if let Some(var) = var {
+                Inst::GetGlobal(slot) => {
+                    if let Some(var) = self.globals.get(*slot) {
                         let var = var.clone();
                         self.stack.push(var);
                     } else {
-                        return Err(EloxError::Eval(EvalError::UndefinedVariable(
-                            self.pos(),
-                            self.identifiers.name(*id_handle),
-                        )));
+                        return Err(self.undefined_global(*slot));
                     }
                 }
-                Inst::SetGlobal(id_handle) => {
-                    if !self.globals.contains_key(&id_handle) {
-                        return Err(EloxError::Eval(EvalError::UndefinedVariable(
-                            self.pos(),
-                            self.identifiers.name(*id_handle),
-                        )));
+                Inst::SetGlobal(slot) => {
+                    if !self.globals.is_defined(*slot) {
+                        return Err(self.undefined_global(*slot));
                     }
 
                     let val = self.stack.peek(0);
-                    self.globals.insert(*id_handle, val);
+                    self.globals.set(*slot, val);
                 }
                 Inst::GetLocal(idx) => {
                     let offset = self.call_frames[self.frames_count - 1].stack_top;
@@ -216,8 +313,74 @@ impl EloxVM {
                     let val = self.stack.peek(0);
                     self.stack.set(*idx + offset, val);
                 }
+                Inst::MakeCell(idx) => {
+                    let idx = *idx + self.call_frames[self.frames_count - 1].stack_top;
+                    let val = self.stack.get(idx);
+                    self.stack.set(idx, Value::new_cell(val));
+                }
+                Inst::GetCell(idx) => {
+                    let offset = self.call_frames[self.frames_count - 1].stack_top;
+                    let cell = self.stack.get(*idx + offset);
+                    let val = cell.as_cell().expect("expected a cell").borrow().clone();
+                    self.stack.push(val);
+                }
+                Inst::SetCell(idx) => {
+                    let offset = self.call_frames[self.frames_count - 1].stack_top;
+                    let cell = self.stack.get(*idx + offset);
+                    *cell.as_cell().expect("expected a cell").borrow_mut() = self.stack.peek(0);
+                }
+                Inst::GetUpvalue(idx) => {
+                    let val = self.call_frames[self.frames_count - 1].upvalues[*idx]
+                        .as_cell()
+                        .expect("expected a cell")
+                        .borrow()
+                        .clone();
+                    self.stack.push(val);
+                }
+                Inst::SetUpvalue(idx) => {
+                    let val = self.stack.peek(0);
+                    *self.call_frames[self.frames_count - 1].upvalues[*idx]
+                        .as_cell()
+                        .expect("expected a cell")
+                        .borrow_mut() = val;
+                }
+                Inst::Closure(captures) => {
+                    let frame = &self.call_frames[self.frames_count - 1];
+                    let upvalues = captures
+                        .iter()
+                        .map(|capture| match capture {
+                            Capture::Local(idx) => self.stack.get(*idx + frame.stack_top),
+                            Capture::Upvalue(idx) => frame.upvalues[*idx].clone(),
+                        })
+                        .collect();
+
+                    let closure = match self.stack.pop() {
+                        Value::Object(obj) => match &*obj {
+                            Obj::Func(func) => Obj::Closure(Rc::clone(func), upvalues),
+                            _ => unreachable!("closures are created from functions"),
+                        },
+                        _ => unreachable!("closures are created from functions"),
+                    };
+
+                    self.stack.push(Value::Object(Rc::new(closure)));
+                }
+                Inst::Overload => {
+                    let func = self.stack.pop();
+                    let mut overloads = match self.stack.pop() {
+                        Value::Object(obj) => match &*obj {
+                            Obj::Overloads(overloads) => overloads.clone(),
+                            _ => vec![Value::Object(obj)],
+                        },
+                        previous => vec![previous],
+                    };
+
+                    overloads.push(func);
+                    self.stack
+                        .push(Value::Object(Rc::new(Obj::Overloads(overloads))));
+                }
                 Inst::Print => {
-                    println!("{}", self.stack.pop());
+                    let val = self.stack.pop();
+                    (self.host.print)(self.pos(), format!("{}", val))?;
                 }
                 Inst::Const(idx) => {
                     let val = self.call_frames[self.frames_count - 1]
@@ -233,6 +396,84 @@ impl EloxVM {
                         continue; // don't increment the ip
                     }
                 }
+                Inst::GetProp(prop) => {
+                    let prop = *prop;
+                    let val = self.stack.pop();
+                    let bound = self.get_property(val, prop)?;
+                    self.stack.push(bound);
+                }
+                Inst::Array(count) => {
+                    let values = self.stack.pop_n(*count);
+                    self.stack.push(Value::new_array(values));
+                }
+                Inst::ArrayPush => {
+                    let val = self.stack.pop();
+                    if let Some(values) = self.stack.peek(0).as_array() {
+                        values.borrow_mut().push(val);
+                    }
+                }
+                Inst::ArrayExtend => {
+                    let spread = self.stack.pop();
+                    let elements = match spread.as_array() {
+                        Some(elements) => elements.borrow().clone(),
+                        None => {
+                            return Err(EloxError::Eval(EvalError::SpreadOperandMustBeAnArray(
+                                self.pos(),
+                                spread.type_(),
+                            )))
+                        }
+                    };
+
+                    if let Some(values) = self.stack.peek(0).as_array() {
+                        values.borrow_mut().extend(elements);
+                    }
+                }
+                Inst::CallSpread => {
+                    let args = self.stack.pop();
+                    let args = args.as_array().map_or(vec![], |args| args.borrow().clone());
+                    let args_count = args.len();
+
+                    for arg in args {
+                        self.stack.push(arg);
+                    }
+
+                    let val = self.stack.peek(args_count);
+                    if self.call_value(val, args_count)? {
+                        continue; // don't increment the ip
+                    }
+                }
+                Inst::Unpack(count, rest) => {
+                    let val = self.stack.pop();
+                    let values = match val.as_array() {
+                        Some(values) => values.borrow().clone(),
+                        None => {
+                            return Err(EloxError::Eval(EvalError::CannotDestructureAsArray(
+                                self.pos(),
+                                val.type_(),
+                            )))
+                        }
+                    };
+
+                    // missing values are nil
+                    for i in 0..*count {
+                        let val = values.get(i).cloned().unwrap_or(Value::Nil);
+                        self.stack.push(val);
+                    }
+
+                    if *rest {
+                        let rest_values = values.iter().skip(*count).cloned().collect();
+                        self.stack.push(Value::new_array(rest_values));
+                    }
+                }
+                Inst::UnpackProps(props) => {
+                    let props = props.clone();
+                    let val = self.stack.pop();
+
+                    for prop in props {
+                        let prop_val = self.get_property(val.clone(), prop)?;
+                        self.stack.push(prop_val);
+                    }
+                }
                 Inst::Neg => {
                     let val = self.stack.pop();
                     if let Value::Number(nb) = val {
@@ -245,6 +486,18 @@ impl EloxVM {
                         )));
                     }
                 }
+                Inst::BitNot => {
+                    let val = self.stack.pop();
+                    if let Value::Number(nb) = val {
+                        self.stack.push(Value::Number(bitwise::not(nb)));
+                    } else {
+                        return Err(EloxError::Eval(EvalError::UnexpectedUnaryOperatorOperand(
+                            self.pos(),
+                            UnaryOperator::Tilde,
+                            val.type_(),
+                        )));
+                    }
+                }
                 Inst::Not => {
                     let val = self.stack.pop();
                     self.stack.push(Value::Boolean(!val.is_truthy()));
@@ -254,6 +507,20 @@ impl EloxVM {
                 Inst::Mult => binary_op!(*, self, BinaryOperator::Star, Number),
                 Inst::Div => binary_op!(/, self, BinaryOperator::Slash, Number),
                 Inst::Mod => binary_op!(%, self, BinaryOperator::Percent, Number),
+                Inst::Pow => binary_op!(fn f64::powf, self, BinaryOperator::StarStar),
+                Inst::IntDiv => binary_op!(
+                    fn |a: f64, b: f64| (a / b).floor(),
+                    self,
+                    BinaryOperator::SlashSlash
+                ),
+                Inst::BitAnd => binary_op!(fn bitwise::and, self, BinaryOperator::Ampersand),
+                Inst::BitOr => binary_op!(fn bitwise::or, self, BinaryOperator::Pipe),
+                Inst::BitXor => binary_op!(fn bitwise::xor, self, BinaryOperator::Caret),
+                Inst::Shl => binary_op!(fn bitwise::shl, self, BinaryOperator::LessLess),
+                Inst::Shr => binary_op!(fn bitwise::shr, self, BinaryOperator::GreaterGreater),
+                Inst::UShr => {
+                    binary_op!(fn bitwise::ushr, self, BinaryOperator::GreaterGreaterGreater)
+                }
                 Inst::True => self.stack.push(Value::Boolean(true)),
                 Inst::False => self.stack.push(Value::Boolean(false)),
                 Inst::Nil => self.stack.push(Value::Nil),
@@ -263,6 +530,21 @@ impl EloxVM {
                 Inst::Lss => binary_op!(<, self, BinaryOperator::Less, Boolean),
                 Inst::Gtq => binary_op!(>=, self, BinaryOperator::GreaterEqual, Boolean),
                 Inst::Leq => binary_op!(<=, self, BinaryOperator::LessEqual, Boolean),
+                Inst::Is => {
+                    // the VM has no classes yet, only the builtin ones can be tested
+                    let class = self.stack.pop();
+                    let val = self.stack.pop();
+
+                    match class.as_native_class() {
+                        Some(class) => self.stack.push(Value::Boolean(class.is_class_of(&val))),
+                        None => {
+                            return Err(EloxError::Eval(EvalError::IsOperandMustBeAClass(
+                                self.pos(),
+                                class.type_(),
+                            )))
+                        }
+                    }
+                }
             }
             self.call_frames[self.frames_count - 1].ip += 1;
         }
@@ -278,14 +560,40 @@ impl EloxVM {
         if let Value::Object(obj) = &val {
             match &**obj {
                 &Obj::Func(ref func) => {
-                    self.call(func, args_count)?;
+                    self.call(func, &[], args_count)?;
                     return Ok(true); // don't increment the ip
                 }
+                Obj::Closure(func, upvalues) => {
+                    self.call(func, upvalues, args_count)?;
+                    return Ok(true);
+                }
+                // overloads never accept the same number of arguments,
+                // the last declaration reports the arity error when none does
+                Obj::Overloads(overloads) => {
+                    let overload = overloads
+                        .iter()
+                        .find(|overload| overload.as_func().is_some_and(|f| f.arity == args_count))
+                        .unwrap_or_else(|| overloads.last().unwrap())
+                        .clone();
+
+                    return self.call_value(overload, args_count);
+                }
                 &Obj::Native(ref native) => {
-                    let res = (native.func)(self.pos(), self.stack.pop_n(native.arity))?;
+                    self.check_arity(native.name, native.min_arity, native.max_arity, args_count)?;
+
+                    let args = self.stack.pop_n(args_count);
+                    let res = (native.func)(self.pos(), args, &mut self.strings)?;
                     self.stack.pop(); // pop the native
                     self.stack.push(res);
                 }
+                Obj::BoundNative(this, method) => {
+                    self.check_arity(method.name, method.min_arity, method.max_arity, args_count)?;
+
+                    let args = self.stack.pop_n(args_count);
+                    let res = (method.func)(self.pos(), this, args, &mut self.strings)?;
+                    self.stack.pop(); // pop the bound method
+                    self.stack.push(res);
+                }
                 _ => {
                     return Err(EloxError::Eval(EvalError::ValueNotCallable(
                         self.pos(),
@@ -298,17 +606,70 @@ impl EloxVM {
         Ok(false)
     }
 
-    fn call(&mut self, func: &Rc<FuncObj>, args_count: usize) -> EloxResult {
-        if args_count != func.arity {
-            return Err(EloxError::Eval(EvalError::WrongNumberOfArgs(
+    fn undefined_global(&self, slot: usize) -> EloxError {
+        EloxError::Eval(EvalError::UndefinedVariable(
+            self.pos(),
+            self.identifiers.name(self.globals.name(slot)),
+        ))
+    }
+
+    fn check_arity(
+        &self,
+        name: IdentifierHandle,
+        min_arity: usize,
+        max_arity: usize,
+        args_count: usize,
+    ) -> EloxResult {
+        if args_count >= min_arity && args_count <= max_arity {
+            return Ok(());
+        }
+
+        let name = self.identifiers.name(name);
+        Err(EloxError::Eval(if min_arity == max_arity {
+            EvalError::WrongNumberOfArgs(self.pos(), min_arity, args_count, name)
+        } else {
+            EvalError::WrongNumberOfArgsBetween(self.pos(), min_arity, max_arity, args_count, name)
+        }))
+    }
+
+    fn get_property(&mut self, val: Value, prop: IdentifierHandle) -> Result<Value, EloxError> {
+        let methods = if val.as_str().is_some() {
+            Some(&self.string_methods)
+        } else if val.as_array().is_some() {
+            Some(&self.array_methods)
+        } else if let Some(namespace) = val.as_namespace() {
+            if let Some(constant) = namespace.constants.get(&prop) {
+                return Ok(constant.clone());
+            }
+
+            Some(&namespace.methods)
+        } else {
+            None
+        };
+
+        if let Some(methods) = methods {
+            if let Some(method) = methods.get(&prop) {
+                let method = Rc::clone(method);
+                return Ok(Value::Object(Rc::new(Obj::BoundNative(val, method))));
+            }
+
+            return Err(EloxError::Eval(EvalError::UndefinedProperty(
                 self.pos(),
-                func.arity,
-                args_count,
-                String::from("fn"),
+                self.identifiers.name(prop),
             )));
         }
 
-        let frame = CallFrame::new(func, (self.stack.size() - 1) - args_count, 0);
+        Err(EloxError::Eval(EvalError::OnlyInstancesHaveProperties(
+            self.pos(),
+            val.type_(),
+        )))
+    }
+
+    fn call(&mut self, func: &Rc<FuncObj>, upvalues: &[Value], args_count: usize) -> EloxResult {
+        let name = func.name.unwrap_or(Identifier::anonymous());
+        self.check_arity(name, func.arity, func.arity, args_count)?;
+
+        let frame = CallFrame::new(func, upvalues, (self.stack.size() - 1) - args_count, 0);
         self.call_frames.push(frame);
         self.frames_count += 1;
 
@@ -361,12 +722,22 @@ impl EloxVM {
 
         match ast {
             Ok(ast) => {
+                let mut resolver = Resolver::new(&Rc::new(self.identifiers.names()));
+
+                if let Err(err) = resolver.resolve(&ast) {
+                    return Err(EloxError::Resolution(err));
+                }
+
+                for warning in resolver.warnings() {
+                    let pos = *warning.position();
+                    (self.host.warn)(pos, format!("{}", warning), pos.line, pos.col)?;
+                }
+
                 let mut func = FuncObj::new(Some(FuncObj::main_func_name()), 0); // main func
-                let mut locals: Vec<Local> = vec![];
 
                 let mut compiler = Compiler::new(
-                    &mut locals,
-                    0,
+                    resolver.resolutions(),
+                    &mut self.globals,
                     &mut func,
                     FuncType::SCRIPT,
                     &mut self.identifiers,
@@ -393,10 +764,17 @@ impl EloxVM {
 }
 
 impl EloxRunner for EloxVM {
+    // the REPL keeps running lines on the same VM, so an error must not leave
+    // the frames and values of the line it stopped behind
     fn run(&mut self, source: &str) -> EloxResult {
-        self.compile(source)?;
-        self.launch()?;
-        Ok(())
+        let (frames_count, stack_size) = (self.frames_count, self.stack.size());
+        let res = self.compile(source).and_then(|_| self.launch());
+
+        if res.is_err() {
+            self.unwind(frames_count, stack_size);
+        }
+
+        res
     }
 
     fn throw_error(&mut self, err: impl ErrorPosition) -> EloxResult {
@@ -406,6 +784,34 @@ impl EloxRunner for EloxVM {
     }
 }
 
+impl EloxTestRunner for EloxVM {
+    fn registered_tests(&self) -> Vec<(String, Position)> {
+        self.tests
+            .borrow()
+            .iter()
+            .map(|(name, _, pos)| (name.clone(), *pos))
+            .collect()
+    }
+
+    // the test is called from a small script so that it runs like any other call
+    fn run_test(&mut self, idx: usize) -> EloxResult {
+        let (func, pos) = {
+            let tests = self.tests.borrow();
+            (tests[idx].1.clone(), tests[idx].2)
+        };
+
+        let mut caller = FuncObj::new(Some(FuncObj::main_func_name()), 0);
+        caller.chunk.write_constant(func, pos);
+        caller.chunk.write(Inst::Call(0), pos);
+        caller.chunk.write(Inst::Ret, pos);
+
+        let caller = Value::Object(Rc::new(Obj::Func(Rc::new(caller))));
+        self.stack.push(caller.clone());
+        self.call_value(caller, 0)?;
+        self.launch()
+    }
+}
+
 // peekable stack
 struct EloxVMStack {
     stack: Vec<Value>,
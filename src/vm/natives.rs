@@ -0,0 +1,564 @@
+use super::instructions::{Namespace, NativeFunc, NativeMethod, NativeMethodFn, Obj, Value};
+use crate::interpreter::eval_result::EvalError;
+use crate::interpreter::lox_array::element_index;
+use crate::interpreter::lox_math;
+use crate::interpreter::lox_string::{char_at, char_count, index_of, split, substring};
+use crate::parser::{Identifier, IdentifierHandle, IdentifierHandlesGenerator};
+use crate::runner::EloxError;
+use crate::scanner::token::Position;
+use fnv::FnvHashMap;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub type NativeMethods = FnvHashMap<IdentifierHandle, Rc<NativeMethod>>;
+
+fn number_arg(name: &str, arg: &Value, pos: Position) -> Result<f64, EloxError> {
+    match arg {
+        Value::Number(n) => Ok(*n),
+        _ => Err(EloxError::Eval(EvalError::UnexpectedArgumentType(
+            pos,
+            name.into(),
+            "number".into(),
+            arg.type_(),
+        ))),
+    }
+}
+
+// a finite number without a fractional part
+fn integer_arg(name: &str, arg: &Value, pos: Position) -> Result<f64, EloxError> {
+    match arg {
+        Value::Number(n) if lox_math::is_integer(*n) => Ok(*n),
+        _ => Err(EloxError::Eval(EvalError::UnexpectedArgumentType(
+            pos,
+            name.into(),
+            "integer".into(),
+            arg.type_(),
+        ))),
+    }
+}
+
+fn string_arg<'a>(name: &str, arg: &'a Value, pos: Position) -> Result<&'a str, EloxError> {
+    match arg.as_str() {
+        Some(s) => Ok(s),
+        None => Err(EloxError::Eval(EvalError::UnexpectedArgumentType(
+            pos,
+            name.into(),
+            "string".into(),
+            arg.type_(),
+        ))),
+    }
+}
+
+fn array_arg<'a>(
+    name: &str,
+    arg: &'a Value,
+    pos: Position,
+) -> Result<&'a RefCell<Vec<Value>>, EloxError> {
+    match arg.as_array() {
+        Some(values) => Ok(values),
+        None => Err(EloxError::Eval(EvalError::UnexpectedArgumentType(
+            pos,
+            name.into(),
+            "array".into(),
+            arg.type_(),
+        ))),
+    }
+}
+
+fn define_method(
+    methods: &mut NativeMethods,
+    name: IdentifierHandle,
+    min_arity: usize,
+    max_arity: usize,
+    func: Box<NativeMethodFn>,
+) {
+    methods.insert(
+        name,
+        Rc::new(NativeMethod {
+            name,
+            min_arity,
+            max_arity,
+            func,
+        }),
+    );
+}
+
+// methods of the native String class, the receiver is always an Obj::Str
+pub fn string_methods(identifiers: &mut IdentifierHandlesGenerator) -> NativeMethods {
+    let mut methods = FnvHashMap::default();
+
+    define_method(
+        &mut methods,
+        identifiers.by_name("length"),
+        0,
+        0,
+        Box::new(|pos, this, _, _| {
+            let s = string_arg("length", this, pos)?;
+            Ok(Value::Number(char_count(s) as f64))
+        }),
+    );
+
+    define_method(
+        &mut methods,
+        Identifier::get(),
+        1,
+        1,
+        Box::new(|pos, this, args, strings| {
+            let s = string_arg("#get", this, pos)?;
+            let idx = number_arg("#get", &args[0], pos)?;
+
+            match char_at(s, idx) {
+                Some(c) => Ok(Value::new_str(&c, strings)),
+                None => Err(EloxError::Eval(EvalError::StringIndexOutOfBounds(
+                    pos,
+                    idx as isize,
+                    char_count(s),
+                ))),
+            }
+        }),
+    );
+
+    define_method(
+        &mut methods,
+        identifiers.by_name("charAt"),
+        1,
+        1,
+        Box::new(|pos, this, args, strings| {
+            let s = string_arg("charAt", this, pos)?;
+            let idx = number_arg("charAt", &args[0], pos)?;
+
+            Ok(char_at(s, idx).map_or(Value::Nil, |c| Value::new_str(&c, strings)))
+        }),
+    );
+
+    define_method(
+        &mut methods,
+        identifiers.by_name("substring"),
+        1,
+        2,
+        Box::new(|pos, this, args, strings| {
+            let s = string_arg("substring", this, pos)?;
+            let start = number_arg("substring", &args[0], pos)?;
+            let end = match args.get(1) {
+                None | Some(Value::Nil) => char_count(s) as f64,
+                Some(end) => number_arg("substring", end, pos)?,
+            };
+
+            Ok(Value::new_str(&substring(s, start, end), strings))
+        }),
+    );
+
+    define_method(
+        &mut methods,
+        identifiers.by_name("indexOf"),
+        1,
+        1,
+        Box::new(|pos, this, args, _| {
+            let s = string_arg("indexOf", this, pos)?;
+            let needle = string_arg("indexOf", &args[0], pos)?;
+
+            Ok(index_of(s, needle).map_or(Value::Nil, |idx| Value::Number(idx as f64)))
+        }),
+    );
+
+    define_method(
+        &mut methods,
+        identifiers.by_name("split"),
+        0,
+        1,
+        Box::new(|pos, this, args, strings| {
+            let s = string_arg("split", this, pos)?;
+            let separator = match args.first() {
+                None => "",
+                Some(separator) => string_arg("split", separator, pos)?,
+            };
+            let parts = split(s, separator)
+                .iter()
+                .map(|part| Value::new_str(part, strings))
+                .collect();
+
+            Ok(Value::new_array(parts))
+        }),
+    );
+
+    define_method(
+        &mut methods,
+        identifiers.by_name("toUpperCase"),
+        0,
+        0,
+        Box::new(|pos, this, _, strings| {
+            let s = string_arg("toUpperCase", this, pos)?;
+            Ok(Value::new_str(&s.to_uppercase(), strings))
+        }),
+    );
+
+    define_method(
+        &mut methods,
+        identifiers.by_name("toLowerCase"),
+        0,
+        0,
+        Box::new(|pos, this, _, strings| {
+            let s = string_arg("toLowerCase", this, pos)?;
+            Ok(Value::new_str(&s.to_lowercase(), strings))
+        }),
+    );
+
+    define_method(
+        &mut methods,
+        identifiers.by_name("trim"),
+        0,
+        0,
+        Box::new(|pos, this, _, strings| {
+            let s = string_arg("trim", this, pos)?;
+            Ok(Value::new_str(s.trim(), strings))
+        }),
+    );
+
+    define_method(
+        &mut methods,
+        identifiers.by_name("replace"),
+        2,
+        2,
+        Box::new(|pos, this, args, strings| {
+            let s = string_arg("replace", this, pos)?;
+            let pattern = string_arg("replace", &args[0], pos)?;
+            let replacement = string_arg("replace", &args[1], pos)?;
+
+            Ok(Value::new_str(&s.replace(pattern, replacement), strings))
+        }),
+    );
+
+    methods
+}
+
+// methods of the native Array class, the receiver is always an Obj::Array
+pub fn array_methods(identifiers: &mut IdentifierHandlesGenerator) -> NativeMethods {
+    let mut methods = FnvHashMap::default();
+
+    define_method(
+        &mut methods,
+        identifiers.by_name("length"),
+        0,
+        0,
+        Box::new(|pos, this, _, _| {
+            let values = array_arg("length", this, pos)?;
+            Ok(Value::Number(values.borrow().len() as f64))
+        }),
+    );
+
+    define_method(
+        &mut methods,
+        identifiers.by_name("push"),
+        0,
+        usize::MAX,
+        Box::new(|pos, this, args, _| {
+            array_arg("push", this, pos)?.borrow_mut().extend(args);
+            Ok(this.clone())
+        }),
+    );
+
+    define_method(
+        &mut methods,
+        identifiers.by_name("join"),
+        0,
+        1,
+        Box::new(|pos, this, args, strings| {
+            let values = array_arg("join", this, pos)?.borrow();
+            let separator = args.first().map_or(String::new(), |sep| format!("{}", sep));
+            let joined = values
+                .iter()
+                .map(|val| format!("{}", val))
+                .collect::<Vec<_>>()
+                .join(&separator);
+
+            Ok(Value::new_str(&joined, strings))
+        }),
+    );
+
+    define_method(
+        &mut methods,
+        Identifier::get(),
+        1,
+        1,
+        Box::new(|pos, this, args, _| {
+            let values = array_arg("#get", this, pos)?.borrow();
+
+            match args[0] {
+                Value::Number(n) => {
+                    if n % 1f64 != 0f64 {
+                        return Ok(Value::Nil);
+                    }
+
+                    match element_index(n, values.len()) {
+                        Some(idx) => Ok(values[idx].clone()),
+                        None => Err(EloxError::Eval(EvalError::ArrayIndexOutOfBounds(
+                            pos,
+                            n as isize,
+                            values.len(),
+                        ))),
+                    }
+                }
+                _ => Ok(Value::Nil),
+            }
+        }),
+    );
+
+    define_method(
+        &mut methods,
+        Identifier::set(),
+        2,
+        2,
+        Box::new(|pos, this, args, _| {
+            let mut values = array_arg("#set", this, pos)?.borrow_mut();
+
+            match args[0] {
+                Value::Number(n) if n % 1f64 == 0f64 => match element_index(n, values.len()) {
+                    Some(idx) => {
+                        values[idx] = args[1].clone();
+                        Ok(Value::Boolean(true))
+                    }
+                    None => Ok(Value::Boolean(false)),
+                },
+                Value::Number(_) => Ok(Value::Boolean(false)),
+                _ => Ok(Value::Nil),
+            }
+        }),
+    );
+
+    methods
+}
+
+// the Math namespace, its functions are bound to the namespace like methods
+pub fn math_namespace(identifiers: &mut IdentifierHandlesGenerator) -> Namespace {
+    let mut methods = FnvHashMap::default();
+
+    for (name, f) in lox_math::UNARY_FUNCTIONS.iter() {
+        let f = *f;
+
+        define_method(
+            &mut methods,
+            identifiers.by_name(name),
+            1,
+            1,
+            Box::new(move |pos, _, args, _| Ok(Value::Number(f(number_arg(name, &args[0], pos)?)))),
+        );
+    }
+
+    for (name, f) in lox_math::BINARY_FUNCTIONS.iter() {
+        let f = *f;
+
+        define_method(
+            &mut methods,
+            identifiers.by_name(name),
+            2,
+            2,
+            Box::new(move |pos, _, args, _| {
+                let x = number_arg(name, &args[0], pos)?;
+                let y = number_arg(name, &args[1], pos)?;
+                Ok(Value::Number(f(x, y)))
+            }),
+        );
+    }
+
+    for (name, f) in lox_math::INTEGER_FUNCTIONS.iter() {
+        let f = *f;
+
+        define_method(
+            &mut methods,
+            identifiers.by_name(name),
+            2,
+            2,
+            Box::new(move |pos, _, args, _| {
+                let x = integer_arg(name, &args[0], pos)?;
+                let y = integer_arg(name, &args[1], pos)?;
+                Ok(Value::Number(f(x, y)))
+            }),
+        );
+    }
+
+    for (name, predicate) in lox_math::PREDICATES.iter() {
+        let predicate = *predicate;
+
+        define_method(
+            &mut methods,
+            identifiers.by_name(name),
+            1,
+            1,
+            Box::new(move |pos, _, args, _| {
+                Ok(Value::Boolean(predicate(number_arg(name, &args[0], pos)?)))
+            }),
+        );
+    }
+
+    for (name, fold) in lox_math::FOLDS.iter() {
+        let fold = *fold;
+
+        define_method(
+            &mut methods,
+            identifiers.by_name(name),
+            1,
+            usize::MAX,
+            Box::new(move |pos, _, args, _| {
+                let first = number_arg(name, &args[0], pos)?;
+
+                args[1..]
+                    .iter()
+                    .map(|val| number_arg(name, val, pos))
+                    .collect::<Result<Vec<f64>, EloxError>>()
+                    .map(|rest| Value::Number(rest.into_iter().fold(first, fold)))
+            }),
+        );
+    }
+
+    let constants = lox_math::CONSTANTS
+        .iter()
+        .map(|(name, value)| (identifiers.by_name(name), Value::Number(*value)))
+        .collect();
+
+    Namespace {
+        name: "Math".into(),
+        constants,
+        methods,
+    }
+}
+
+// the name returned by type(value), arrays and functions are told apart from other values
+fn type_name(value: &Value) -> String {
+    match value {
+        Value::Object(obj) => match &**obj {
+            Obj::Func(_) | Obj::Native(_) | Obj::BoundNative(_, _) => "function".into(),
+            _ => obj.type_(),
+        },
+        _ => value.type_(),
+    }
+}
+
+pub fn reflection_natives(identifiers: &mut IdentifierHandlesGenerator) -> Vec<NativeFunc> {
+    let mut natives = vec![NativeFunc {
+        name: identifiers.by_name("type"),
+        min_arity: 1,
+        max_arity: 1,
+        func: Box::new(|_, args, strings| Ok(Value::new_str(&type_name(&args[0]), strings))),
+    }];
+
+    // they work on instances and classes, which the VM doesn't compile yet,
+    // Range is what a..b and a..=b call
+    let unsupported = [
+        ("fields", 1),
+        ("hasField", 2),
+        ("getField", 2),
+        ("setField", 3),
+        ("methods", 1),
+        ("superclass", 1),
+        ("Range", 3),
+    ];
+
+    for (name, arity) in unsupported.iter() {
+        let name = *name;
+
+        natives.push(NativeFunc {
+            name: identifiers.by_name(name),
+            min_arity: *arity,
+            max_arity: *arity,
+            func: Box::new(move |pos, _, _| {
+                Err(EloxError::Eval(EvalError::NotSupportedOnTheVm(
+                    pos,
+                    name.into(),
+                )))
+            }),
+        });
+    }
+
+    natives
+}
+
+// the tests registered with test(name, fn), in registration order
+pub type TestRegistry = Rc<RefCell<Vec<(String, Value, Position)>>>;
+
+// strings are quoted so that "1" and 1 can be told apart in failure messages
+fn assertion_repr(value: &Value) -> String {
+    match value.as_str() {
+        Some(s) => format!("\"{}\"", s),
+        None => format!("{}", value),
+    }
+}
+
+fn is_callable(value: &Value) -> bool {
+    match value {
+        Value::Object(obj) => matches!(
+            **obj,
+            Obj::Func(_)
+                | Obj::Closure(_, _)
+                | Obj::Overloads(_)
+                | Obj::Native(_)
+                | Obj::BoundNative(_, _)
+        ),
+        _ => false,
+    }
+}
+
+pub fn assertion_natives(
+    identifiers: &mut IdentifierHandlesGenerator,
+    tests: &TestRegistry,
+) -> Vec<NativeFunc> {
+    let tests = Rc::clone(tests);
+
+    vec![
+        NativeFunc {
+            name: identifiers.by_name("assert"),
+            min_arity: 1,
+            max_arity: 2,
+            func: Box::new(|pos, args, _| {
+                if args[0].is_truthy() {
+                    return Ok(Value::Nil);
+                }
+
+                let message = match args.get(1) {
+                    None | Some(Value::Nil) => None,
+                    Some(message) => Some(format!("{}", message)),
+                };
+
+                Err(EloxError::Eval(EvalError::AssertionFailed(pos, message)))
+            }),
+        },
+        NativeFunc {
+            name: identifiers.by_name("assertEq"),
+            min_arity: 2,
+            max_arity: 2,
+            func: Box::new(|pos, args, _| {
+                if args[0] == args[1] {
+                    return Ok(Value::Nil);
+                }
+
+                Err(EloxError::Eval(EvalError::AssertionFailed(
+                    pos,
+                    Some(format!(
+                        "expected {}, got {}",
+                        assertion_repr(&args[1]),
+                        assertion_repr(&args[0]),
+                    )),
+                )))
+            }),
+        },
+        NativeFunc {
+            name: identifiers.by_name("test"),
+            min_arity: 2,
+            max_arity: 2,
+            func: Box::new(move |pos, args, _| {
+                let name = string_arg("test", &args[0], pos)?.to_owned();
+
+                if !is_callable(&args[1]) {
+                    return Err(EloxError::Eval(EvalError::UnexpectedArgumentType(
+                        pos,
+                        "test".into(),
+                        "function".into(),
+                        args[1].type_(),
+                    )));
+                }
+
+                tests.borrow_mut().push((name, args[1].clone(), pos));
+                Ok(Value::Nil)
+            }),
+        },
+    ]
+}
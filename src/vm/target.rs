@@ -29,7 +29,7 @@ impl WasmTarget {
             module: WasmModule::new(),
             code: vec![],
             used_funcs: FnvHashMap::default(),
-            imports_count: 1, // print
+            imports_count: 3, // print, pow, mod
         }
     }
 
@@ -80,7 +80,20 @@ impl WasmTarget {
             Inst::Mult => WasmInst::Mulf64,
             Inst::Div => WasmInst::Divf64,
             Inst::Neg => WasmInst::Negf64,
-            Inst::Mod => self.call(FuncUtil::ModF64),
+            // the host's remainder has the sign of the dividend like the interpreters, wasm has no f64 remainder
+            Inst::Mod => WasmInst::Call(2),
+            Inst::Pow => WasmInst::Call(1),
+            Inst::BitAnd => self.call(FuncUtil::BitAnd),
+            Inst::BitOr => self.call(FuncUtil::BitOr),
+            Inst::BitXor => self.call(FuncUtil::BitXor),
+            Inst::BitNot => self.call(FuncUtil::BitNot),
+            Inst::Shl => self.call(FuncUtil::Shl),
+            Inst::Shr => self.call(FuncUtil::Shr),
+            Inst::UShr => self.call(FuncUtil::UShr),
+            Inst::IntDiv => {
+                self.emit_multiple(&[WasmInst::Divf64, WasmInst::Floorf64]);
+                return;
+            }
             Inst::Pop => WasmInst::Drop_,
             Inst::True => WasmInst::Consti32(1),
             Inst::False => WasmInst::Consti32(0),
@@ -100,8 +113,14 @@ impl WasmTarget {
 
 #[derive(Hash, PartialEq, Eq)]
 enum FuncUtil {
-    ModF64,
     Add,
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    Shl,
+    Shr,
+    UShr,
 }
 
 struct WasmUtils;
@@ -109,29 +128,78 @@ struct WasmUtils;
 impl WasmUtils {
     pub fn get(func: &FuncUtil) -> WasmFunc {
         match func {
-            FuncUtil::ModF64 => WasmUtils::f64mod(),
             FuncUtil::Add => WasmUtils::add(),
+            FuncUtil::BitAnd => WasmUtils::bitwise(WasmInst::Andi32, false),
+            FuncUtil::BitOr => WasmUtils::bitwise(WasmInst::Ori32, false),
+            FuncUtil::BitXor => WasmUtils::bitwise(WasmInst::Xori32, false),
+            FuncUtil::BitNot => WasmUtils::bit_not(),
+            FuncUtil::Shl => WasmUtils::bitwise(WasmInst::Shli32, false),
+            FuncUtil::Shr => WasmUtils::bitwise(WasmInst::ShrSi32, false),
+            FuncUtil::UShr => WasmUtils::bitwise(WasmInst::ShrUi32, true),
         }
     }
 
-    fn f64mod() -> WasmFunc {
+    // ToInt32 of the number in the `param` local, like bitwise::to_int32: truncated and wrapped
+    // modulo 2^32, NaN and infinities give 0. The conversion cannot trap, `tmp` is a scratch local
+    fn to_int32(param: u32, tmp: u32) -> Vec<WasmInst> {
+        use WasmInst::*;
+        let two_pow_32 = 4_294_967_296f64;
+
+        vec![
+            GetLocal(param),
+            Truncf64,
+            SetLocal(tmp),
+            GetLocal(tmp),
+            GetLocal(tmp),
+            Constf64(two_pow_32),
+            Divf64,
+            Floorf64,
+            Constf64(two_pow_32),
+            Mulf64,
+            Subf64,
+            SetLocal(tmp),
+            // the remainder is NaN for NaN and infinite inputs
+            GetLocal(tmp),
+            Constf64(0f64),
+            GetLocal(tmp),
+            GetLocal(tmp),
+            Eqf64,
+            Select,
+            TruncF64ToI64,
+            WrapI64ToI32,
+        ]
+    }
+
+    // converts both f64 operands to i32, applies the operation and converts the result back
+    fn bitwise(op: WasmInst, unsigned_result: bool) -> WasmFunc {
         use ValueType::*;
         use WasmInst::*;
+
+        let mut body = WasmUtils::to_int32(0, 2);
+        body.extend(WasmUtils::to_int32(1, 2));
+        body.push(op);
+        body.push(if unsigned_result {
+            ConvertUI32ToF64
+        } else {
+            ConvertI32ToF64
+        });
+
         WasmFunc::new(
             FuncSignature::new(&[F64, F64], Some(F64)),
-            FuncBody::new(
-                Locals::from_types(&[]),
-                &[
-                    GetLocal(0),
-                    GetLocal(1),
-                    GetLocal(0),
-                    GetLocal(1),
-                    Divf64,
-                    Floorf64,
-                    Mulf64,
-                    Subf64,
-                ],
-            ),
+            FuncBody::new(Locals::from_types(&[F64]), &body),
+        )
+    }
+
+    fn bit_not() -> WasmFunc {
+        use ValueType::*;
+        use WasmInst::*;
+
+        let mut body = WasmUtils::to_int32(0, 1);
+        body.extend(vec![Consti32(-1), Xori32, ConvertI32ToF64]);
+
+        WasmFunc::new(
+            FuncSignature::new(&[F64], Some(F64)),
+            FuncBody::new(Locals::from_types(&[F64]), &body),
         )
     }
 
@@ -188,6 +256,10 @@ impl EloxTranslator for WasmTarget {
 
         self.module
             .import_func("host", "print", FuncSignature::new(&[F64], None));
+        self.module
+            .import_func("host", "pow", FuncSignature::new(&[F64, F64], Some(F64)));
+        self.module
+            .import_func("host", "mod", FuncSignature::new(&[F64, F64], Some(F64)));
 
         // minimum if all constants are numbers (1 type byte + 8 data bytes)
         let mut constants_data: Vec<u8> = Vec::with_capacity(9 * chunk.constants().len());
@@ -204,7 +276,13 @@ impl EloxTranslator for WasmTarget {
             &constants_data,
         ));
 
-        for inst in chunk.instructions() {
+        // the start function returns nothing, skip the implicit `return nil` of the script
+        let instructions = match chunk.instructions() {
+            [body @ .., Inst::Nil, Inst::Ret] => body,
+            all => all,
+        };
+
+        for inst in instructions {
             self.translate_inst(inst, chunk, 0);
         }
 
@@ -215,7 +293,11 @@ impl EloxTranslator for WasmTarget {
 
         self.module.set_start_func(start_func);
 
-        for used_func in self.used_funcs.keys() {
+        // the functions must be added in the order of the indices used by the calls
+        let mut used_funcs = self.used_funcs.iter().collect::<Vec<_>>();
+        used_funcs.sort_by_key(|(_, &idx)| idx);
+
+        for (used_func, _) in used_funcs {
             self.module.add_func(WasmUtils::get(used_func));
         }
 
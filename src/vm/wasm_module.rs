@@ -320,11 +320,23 @@ pub enum WasmInst {
     Mulf64,
     Divf64,
     Floorf64,
+    Truncf64,
+    Eqf64,
     Negf64,
     RemUi64,
     TruncF64ToI64,
     ConvertI64ToF64,
+    WrapI64ToI32,
+    ConvertI32ToF64,
+    ConvertUI32ToF64,
+    Andi32,
+    Ori32,
+    Xori32,
+    Shli32,
+    ShrSi32,
+    ShrUi32,
     Drop_,
+    Select,
     Call(FuncIdx),
     Loadf64(u32),
     Loadi32(u32),
@@ -348,11 +360,23 @@ impl WasmInst {
             Mulf64 => 0xa2,
             Divf64 => 0xa3,
             Floorf64 => 0x9c,
+            Truncf64 => 0x9d,
+            Eqf64 => 0x61,
             Negf64 => 0x9a,
             RemUi64 => 0x82,
             TruncF64ToI64 => 0xb0,
             ConvertI64ToF64 => 0xb9,
+            WrapI64ToI32 => 0xa7,
+            ConvertI32ToF64 => 0xb7,
+            ConvertUI32ToF64 => 0xb8,
+            Andi32 => 0x71,
+            Ori32 => 0x72,
+            Xori32 => 0x73,
+            Shli32 => 0x74,
+            ShrSi32 => 0x75,
+            ShrUi32 => 0x76,
             Drop_ => 0x1a,
+            Select => 0x1b,
             Call(_) => 0x10,
             Loadf64(_) => 0x2b,
             Loadi32(_) => 0x28,
@@ -936,7 +960,10 @@ mod tests {
                 0x01, 0x00, 0x00, 0x00, // wasm version
                 0x1, 0x7, 0x1, 0x60, 0x2, 0x7c, 0x7c, 0x1, 0x7c, // type section
                 0x3, 0x2, 0x1, 0x0, // func section
-                0xa, 0x9, 0x1, 0x7, 0x0, 0x20, 0x0, 0x20, 0x1, 0xa0, 0xb // code section
+                0x5, 0x3, 0x1, 0x0, 0x1, // memory section
+                0x6, 0x1, 0x0, // global section
+                0xa, 0x9, 0x1, 0x7, 0x0, 0x20, 0x0, 0x20, 0x1, 0xa0, 0xb, // code section
+                0xb, 0x1, 0x0 // data section
             ]
         );
     }
@@ -0,0 +1,515 @@
+extern crate byteorder;
+use byteorder::{ByteOrder, LittleEndian};
+
+use super::wasm_module::{ValueType, WasmInst};
+
+// A minimal interpreter for the modules emitted by WasmTarget,
+// it lets the compiled programs run in-process without a JS engine.
+// Only the instructions that the target emits are supported.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WasmValue {
+    I32(i32),
+    I64(i64),
+    F64(f64),
+}
+
+impl WasmValue {
+    fn zero(type_: ValueType) -> WasmValue {
+        match type_ {
+            ValueType::I32 => WasmValue::I32(0),
+            ValueType::I64 => WasmValue::I64(0),
+            ValueType::F64 => WasmValue::F64(0f64),
+        }
+    }
+}
+
+pub type WasmResult<T> = Result<T, String>;
+
+// the functions imported from the "host" module
+pub trait WasmHost {
+    fn print(&mut self, value: f64);
+}
+
+struct Signature {
+    params: Vec<ValueType>,
+    results: Vec<ValueType>,
+}
+
+struct Import {
+    field: String,
+    type_idx: usize,
+}
+
+struct Body {
+    locals: Vec<ValueType>,
+    code: Vec<WasmInst>,
+}
+
+#[derive(Default)]
+struct Module {
+    types: Vec<Signature>,
+    imports: Vec<Import>,
+    funcs: Vec<usize>, // type index of each defined function
+    bodies: Vec<Body>,
+    globals: Vec<WasmValue>,
+    start: Option<usize>,
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes, pos: 0 }
+    }
+
+    fn done(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn byte(&mut self) -> WasmResult<u8> {
+        match self.bytes.get(self.pos) {
+            Some(&byte) => {
+                self.pos += 1;
+                Ok(byte)
+            }
+            None => Err("unexpected end of module".into()),
+        }
+    }
+
+    fn take(&mut self, len: usize) -> WasmResult<&'a [u8]> {
+        if self.pos + len > self.bytes.len() {
+            return Err("unexpected end of module".into());
+        }
+
+        let slice = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn uleb(&mut self) -> WasmResult<usize> {
+        let mut result = 0usize;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.byte()?;
+            result |= ((byte & 0x7f) as usize) << shift;
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+    }
+
+    fn sleb(&mut self) -> WasmResult<i64> {
+        let mut result = 0i64;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.byte()?;
+            result |= ((byte & 0x7f) as i64) << shift;
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    result |= -1 << shift;
+                }
+
+                return Ok(result);
+            }
+        }
+    }
+
+    fn name(&mut self) -> WasmResult<String> {
+        let len = self.uleb()?;
+        Ok(String::from_utf8_lossy(self.take(len)?).into())
+    }
+
+    fn value_type(&mut self) -> WasmResult<ValueType> {
+        match self.byte()? {
+            0x7f => Ok(ValueType::I32),
+            0x7e => Ok(ValueType::I64),
+            0x7c => Ok(ValueType::F64),
+            byte => Err(format!("unsupported value type 0x{:x}", byte)),
+        }
+    }
+
+    fn value_types(&mut self) -> WasmResult<Vec<ValueType>> {
+        let count = self.uleb()?;
+        (0..count).map(|_| self.value_type()).collect()
+    }
+
+    fn inst(&mut self) -> WasmResult<WasmInst> {
+        use WasmInst::*;
+        let opcode = self.byte()?;
+
+        Ok(match opcode {
+            0x41 => Consti32(self.sleb()? as i32),
+            0x44 => Constf64(LittleEndian::read_f64(self.take(8)?)),
+            0x20 => GetLocal(self.uleb()? as u32),
+            0x21 => SetLocal(self.uleb()? as u32),
+            0x23 => GetGlobal(self.uleb()? as u32),
+            0x24 => SetGlobal(self.uleb()? as u32),
+            0x10 => Call(self.uleb()?),
+            0x0b => End,
+            0xa0 => Addf64,
+            0xa1 => Subf64,
+            0xa2 => Mulf64,
+            0xa3 => Divf64,
+            0x9c => Floorf64,
+            0x9d => Truncf64,
+            0x61 => Eqf64,
+            0x9a => Negf64,
+            0x82 => RemUi64,
+            0xb0 => TruncF64ToI64,
+            0xb9 => ConvertI64ToF64,
+            0xa7 => WrapI64ToI32,
+            0xb7 => ConvertI32ToF64,
+            0xb8 => ConvertUI32ToF64,
+            0x71 => Andi32,
+            0x72 => Ori32,
+            0x73 => Xori32,
+            0x74 => Shli32,
+            0x75 => ShrSi32,
+            0x76 => ShrUi32,
+            0x1a => Drop_,
+            0x1b => Select,
+            _ => return Err(format!("unsupported opcode 0x{:x}", opcode)),
+        })
+    }
+
+    // a constant expression terminated by End
+    fn init_expr(&mut self) -> WasmResult<WasmValue> {
+        let value = match self.inst()? {
+            WasmInst::Consti32(n) => WasmValue::I32(n),
+            WasmInst::Constf64(n) => WasmValue::F64(n),
+            _ => return Err("unsupported initializer".into()),
+        };
+
+        match self.inst()? {
+            WasmInst::End => Ok(value),
+            _ => Err("unterminated initializer".into()),
+        }
+    }
+}
+
+fn decode(bytes: &[u8]) -> WasmResult<Module> {
+    let mut reader = Reader::new(bytes);
+
+    if reader.take(8)? != [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00] {
+        return Err("not a wasm module".into());
+    }
+
+    let mut module = Module::default();
+
+    while !reader.done() {
+        let id = reader.byte()?;
+        let len = reader.uleb()?;
+        let mut section = Reader::new(reader.take(len)?);
+
+        match id {
+            // type
+            1 => {
+                for _ in 0..section.uleb()? {
+                    if section.byte()? != 0x60 {
+                        return Err("expected a function type".into());
+                    }
+
+                    let params = section.value_types()?;
+                    let results = section.value_types()?;
+                    module.types.push(Signature { params, results });
+                }
+            }
+            // import
+            2 => {
+                for _ in 0..section.uleb()? {
+                    section.name()?;
+                    let field = section.name()?;
+
+                    if section.byte()? != 0 {
+                        return Err("only functions can be imported".into());
+                    }
+
+                    let type_idx = section.uleb()?;
+                    module.imports.push(Import { field, type_idx });
+                }
+            }
+            // function
+            3 => {
+                for _ in 0..section.uleb()? {
+                    module.funcs.push(section.uleb()?);
+                }
+            }
+            // global
+            6 => {
+                for _ in 0..section.uleb()? {
+                    section.value_type()?;
+                    section.byte()?; // mutability
+                    module.globals.push(section.init_expr()?);
+                }
+            }
+            // start
+            8 => module.start = Some(section.uleb()?),
+            // code
+            10 => {
+                for _ in 0..section.uleb()? {
+                    let size = section.uleb()?;
+                    let mut body = Reader::new(section.take(size)?);
+                    let mut locals = Vec::new();
+
+                    for _ in 0..body.uleb()? {
+                        let count = body.uleb()?;
+                        let type_ = body.value_type()?;
+                        locals.extend((0..count).map(|_| type_));
+                    }
+
+                    let mut code = Vec::new();
+
+                    while !body.done() {
+                        code.push(body.inst()?);
+                    }
+
+                    module.bodies.push(Body { locals, code });
+                }
+            }
+            // custom, memory, export and data sections do not affect execution
+            _ => {}
+        }
+    }
+
+    Ok(module)
+}
+
+struct Machine<'a, H: WasmHost> {
+    module: &'a Module,
+    globals: Vec<WasmValue>,
+    host: &'a mut H,
+}
+
+impl<'a, H: WasmHost> Machine<'a, H> {
+    fn signature(&self, func_idx: usize) -> WasmResult<&'a Signature> {
+        let module = self.module;
+        let type_idx = if func_idx < module.imports.len() {
+            module.imports[func_idx].type_idx
+        } else {
+            match module.funcs.get(func_idx - module.imports.len()) {
+                Some(&type_idx) => type_idx,
+                None => return Err(format!("undefined function {}", func_idx)),
+            }
+        };
+
+        module
+            .types
+            .get(type_idx)
+            .ok_or_else(|| format!("undefined type {}", type_idx))
+    }
+
+    fn call(&mut self, func_idx: usize, args: Vec<WasmValue>) -> WasmResult<Option<WasmValue>> {
+        if func_idx < self.module.imports.len() {
+            return self.call_import(func_idx, &args);
+        }
+
+        let module = self.module;
+        let signature = self.signature(func_idx)?;
+        let body = &module.bodies[func_idx - module.imports.len()];
+        let mut locals = args;
+        locals.extend(body.locals.iter().map(|&type_| WasmValue::zero(type_)));
+        let mut stack: Vec<WasmValue> = Vec::new();
+
+        for inst in &body.code {
+            self.exec(inst, &mut locals, &mut stack)?;
+        }
+
+        Ok(if signature.results.is_empty() {
+            None
+        } else {
+            Some(pop(&mut stack)?)
+        })
+    }
+
+    fn call_import(
+        &mut self,
+        func_idx: usize,
+        args: &[WasmValue],
+    ) -> WasmResult<Option<WasmValue>> {
+        let field = self.module.imports[func_idx].field.as_str();
+
+        match (field, args) {
+            ("print", &[WasmValue::F64(value)]) => {
+                self.host.print(value);
+                Ok(None)
+            }
+            ("pow", &[WasmValue::F64(a), WasmValue::F64(b)]) => Ok(Some(WasmValue::F64(a.powf(b)))),
+            ("mod", &[WasmValue::F64(a), WasmValue::F64(b)]) => Ok(Some(WasmValue::F64(a % b))),
+            _ => Err(format!("unknown import '{}'", field)),
+        }
+    }
+
+    fn exec(
+        &mut self,
+        inst: &WasmInst,
+        locals: &mut [WasmValue],
+        stack: &mut Vec<WasmValue>,
+    ) -> WasmResult<()> {
+        use WasmInst::*;
+        use WasmValue::*;
+
+        let value = match inst {
+            Consti32(n) => I32(*n),
+            Constf64(n) => F64(*n),
+            GetLocal(idx) => *locals
+                .get(*idx as usize)
+                .ok_or_else(|| format!("undefined local {}", idx))?,
+            SetLocal(idx) => {
+                let value = pop(stack)?;
+                match locals.get_mut(*idx as usize) {
+                    Some(local) => *local = value,
+                    None => return Err(format!("undefined local {}", idx)),
+                }
+                return Ok(());
+            }
+            GetGlobal(idx) => *self
+                .globals
+                .get(*idx as usize)
+                .ok_or_else(|| format!("undefined global {}", idx))?,
+            SetGlobal(idx) => {
+                let value = pop(stack)?;
+                match self.globals.get_mut(*idx as usize) {
+                    Some(global) => *global = value,
+                    None => return Err(format!("undefined global {}", idx)),
+                }
+                return Ok(());
+            }
+            End => return Ok(()),
+            Addf64 | Subf64 | Mulf64 | Divf64 => {
+                let b = pop_f64(stack)?;
+                let a = pop_f64(stack)?;
+                F64(match inst {
+                    Addf64 => a + b,
+                    Subf64 => a - b,
+                    Mulf64 => a * b,
+                    _ => a / b,
+                })
+            }
+            Floorf64 => F64(pop_f64(stack)?.floor()),
+            Truncf64 => F64(pop_f64(stack)?.trunc()),
+            Eqf64 => {
+                let b = pop_f64(stack)?;
+                let a = pop_f64(stack)?;
+                I32(i32::from(a == b))
+            }
+            Negf64 => F64(-pop_f64(stack)?),
+            RemUi64 => {
+                let b = pop_i64(stack)? as u64;
+                let a = pop_i64(stack)? as u64;
+                if b == 0 {
+                    return Err("integer divide by zero".into());
+                }
+                I64((a % b) as i64)
+            }
+            TruncF64ToI64 => {
+                let n = pop_f64(stack)?;
+                if n.is_nan() {
+                    return Err("invalid conversion to integer".into());
+                }
+                if n.trunc() < i64::MIN as f64 || n.trunc() >= i64::MAX as f64 {
+                    return Err("integer overflow".into());
+                }
+                I64(n as i64)
+            }
+            ConvertI64ToF64 => F64(pop_i64(stack)? as f64),
+            WrapI64ToI32 => I32(pop_i64(stack)? as i32),
+            ConvertI32ToF64 => F64(f64::from(pop_i32(stack)?)),
+            ConvertUI32ToF64 => F64(f64::from(pop_i32(stack)? as u32)),
+            Andi32 | Ori32 | Xori32 | Shli32 | ShrSi32 | ShrUi32 => {
+                let b = pop_i32(stack)?;
+                let a = pop_i32(stack)?;
+                I32(match inst {
+                    Andi32 => a & b,
+                    Ori32 => a | b,
+                    Xori32 => a ^ b,
+                    Shli32 => a.wrapping_shl(b as u32),
+                    ShrSi32 => a.wrapping_shr(b as u32),
+                    _ => (a as u32).wrapping_shr(b as u32) as i32,
+                })
+            }
+            Drop_ => {
+                pop(stack)?;
+                return Ok(());
+            }
+            Select => {
+                let condition = pop_i32(stack)?;
+                let b = pop(stack)?;
+                let a = pop(stack)?;
+                if condition != 0 {
+                    a
+                } else {
+                    b
+                }
+            }
+            Call(func_idx) => {
+                let arity = self.signature(*func_idx)?.params.len();
+                if stack.len() < arity {
+                    return Err("stack underflow".into());
+                }
+
+                let args = stack.split_off(stack.len() - arity);
+                match self.call(*func_idx, args)? {
+                    Some(value) => value,
+                    None => return Ok(()),
+                }
+            }
+            Loadf64(_) | Loadi32(_) | Storef64(_) | Storei32(_) => {
+                return Err("memory instructions are not supported".into())
+            }
+        };
+
+        stack.push(value);
+        Ok(())
+    }
+}
+
+fn pop(stack: &mut Vec<WasmValue>) -> WasmResult<WasmValue> {
+    stack.pop().ok_or_else(|| "stack underflow".into())
+}
+
+fn pop_f64(stack: &mut Vec<WasmValue>) -> WasmResult<f64> {
+    match pop(stack)? {
+        WasmValue::F64(n) => Ok(n),
+        value => Err(format!("type mismatch: expected f64, found {:?}", value)),
+    }
+}
+
+fn pop_i64(stack: &mut Vec<WasmValue>) -> WasmResult<i64> {
+    match pop(stack)? {
+        WasmValue::I64(n) => Ok(n),
+        value => Err(format!("type mismatch: expected i64, found {:?}", value)),
+    }
+}
+
+fn pop_i32(stack: &mut Vec<WasmValue>) -> WasmResult<i32> {
+    match pop(stack)? {
+        WasmValue::I32(n) => Ok(n),
+        value => Err(format!("type mismatch: expected i32, found {:?}", value)),
+    }
+}
+
+// instantiates a module and runs its start function
+pub fn run_module<H: WasmHost>(bytes: &[u8], host: &mut H) -> WasmResult<()> {
+    let module = decode(bytes)?;
+    let mut machine = Machine {
+        globals: module.globals.clone(),
+        module: &module,
+        host,
+    };
+
+    if let Some(start) = module.start {
+        machine.call(start, vec![])?;
+    }
+
+    Ok(())
+}
@@ -182,7 +182,7 @@ def run_tests(dir, target):
         sub_path = join(dir, path)
         if isdir(sub_path):
             skipped += run_tests(sub_path, target)
-        else:
+        elif sub_path.endswith('.elox'):
             skipped += int(not run_test(sub_path, target))
 
     print('All tests passed in ' + relpath(dir) +
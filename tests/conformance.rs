@@ -0,0 +1,225 @@
+extern crate elox;
+
+use elox::runner::capture::{run_captured, Backend, Captured, ErrorKind};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+// Runs every .elox file under tests/ and checks the same annotations as test.py:
+//   // !expect: output
+//   // !expect runtime error: message
+//   // !expect syntax error: message
+// A `// #[!target]` header on the first line skips the file on that backend.
+// The WASM target only handles numeric programs, so files opt in with `// #[wasm]`.
+
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+struct Expectations {
+    header: Option<(bool, String)>, // negated, target
+    output: Vec<(String, usize)>,   // expected line, line number
+    error: Option<(ErrorKind, String, usize)>,
+}
+
+// mirrors the `// ?marker ?(.*)` patterns of test.py, the marker may follow other comments
+fn annotation<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+    line.match_indices("//").find_map(|(idx, _)| {
+        let rest = &line[idx + 2..];
+        let rest = rest.strip_prefix(' ').unwrap_or(rest);
+        let rest = rest.strip_prefix(marker)?;
+
+        Some(rest.strip_prefix(' ').unwrap_or(rest))
+    })
+}
+
+fn header(line: &str) -> Option<(bool, String)> {
+    let target = annotation(line, "#[")?.split(']').next()?.trim();
+
+    match target.strip_prefix('!') {
+        Some(target) => Some((true, target.into())),
+        None => Some((false, target.into())),
+    }
+}
+
+fn parse(source: &str) -> Result<Expectations, String> {
+    let mut expectations = Expectations {
+        header: None,
+        output: vec![],
+        error: None,
+    };
+
+    for (idx, line) in source.lines().enumerate() {
+        let line_nb = idx + 1;
+
+        if line_nb == 1 {
+            expectations.header = header(line);
+        }
+
+        if let Some(output) = annotation(line, "!expect:") {
+            expectations.output.push((output.into(), line_nb));
+        }
+
+        let errors = [
+            ("!expect runtime error:", ErrorKind::Runtime),
+            ("!expect syntax error:", ErrorKind::Syntax),
+        ];
+
+        for (marker, kind) in errors.iter() {
+            if let Some(message) = annotation(line, marker) {
+                if expectations.error.is_some() {
+                    return Err("cannot expect multiple errors".into());
+                }
+
+                expectations.error = Some((*kind, message.into(), line_nb));
+            }
+        }
+    }
+
+    Ok(expectations)
+}
+
+impl Expectations {
+    fn runs_on(&self, backend: Backend) -> bool {
+        match (&self.header, backend) {
+            (Some((false, target)), Backend::Wasm) => target == backend.name(),
+            (_, Backend::Wasm) => false,
+            (Some((true, target)), _) => target != backend.name(),
+            _ => true,
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.output.len() + self.error.iter().count()
+    }
+
+    fn diff(&self, captured: &Captured) -> Vec<String> {
+        let mut failures = vec![];
+
+        match (&self.error, &captured.error) {
+            (Some((kind, message, line)), Some(err)) => {
+                if err.kind != *kind || &err.message != message {
+                    failures.push(format!(
+                        "expected {} '{}' on line {}, got {}",
+                        kind, message, line, err
+                    ));
+                }
+            }
+            (Some((kind, message, line)), None) => failures.push(format!(
+                "expected {} '{}' on line {} and got none",
+                kind, message, line
+            )),
+            (None, Some(err)) => failures.push(format!("unexpected {}", err)),
+            (None, None) => {}
+        }
+
+        // a printed string spanning several lines is compared line by line, like stdout
+        let output = captured
+            .output
+            .iter()
+            .flat_map(|printed| printed.split('\n'))
+            .collect::<Vec<_>>();
+
+        for (idx, line) in output.iter().enumerate() {
+            match self.output.get(idx) {
+                Some((expected, line_nb)) if expected != *line => failures.push(format!(
+                    "expected output '{}' on line {}, got '{}'",
+                    expected, line_nb, line
+                )),
+                None => failures.push(format!("got output '{}' when none was expected", line)),
+                _ => {}
+            }
+        }
+
+        for (expected, line_nb) in self.output.iter().skip(output.len()) {
+            failures.push(format!(
+                "missing expected output '{}' on line {}",
+                expected, line_nb
+            ));
+        }
+
+        failures
+    }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).expect("could not read the tests directory") {
+        let path = entry.expect("could not read a test path").path();
+
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "elox") {
+            files.push(path);
+        }
+    }
+}
+
+fn run_corpus(backend: Backend) {
+    let mut files = vec![];
+    collect_files(Path::new("tests"), &mut files);
+    files.sort();
+
+    let mut failures = vec![];
+    let (mut expectations, mut skipped) = (0, 0);
+
+    for path in files {
+        let source = fs::read_to_string(&path).expect("could not read a test file");
+
+        let expected = match parse(&source) {
+            Ok(expected) => expected,
+            Err(err) => {
+                failures.push(format!("{}: {}", path.display(), err));
+                continue;
+            }
+        };
+
+        if !expected.runs_on(backend) {
+            skipped += 1;
+            continue;
+        }
+
+        expectations += expected.count();
+
+        for failure in expected.diff(&run_captured(backend, &source)) {
+            failures.push(format!("{}: {}", path.display(), failure));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} failure(s) on {}:\n{}",
+        failures.len(),
+        backend,
+        failures.join("\n")
+    );
+
+    println!(
+        "{}: {} expectations, {} skipped",
+        backend, expectations, skipped
+    );
+}
+
+// the interpreters recurse deeply in debug builds
+fn run_corpus_on_large_stack(backend: Backend) {
+    let handle = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || run_corpus(backend))
+        .expect("could not spawn the test thread");
+
+    if let Err(err) = handle.join() {
+        std::panic::resume_unwind(err);
+    }
+}
+
+#[test]
+fn tree_walker() {
+    run_corpus_on_large_stack(Backend::TreeWalker);
+}
+
+#[test]
+fn vm() {
+    run_corpus_on_large_stack(Backend::Vm);
+}
+
+#[test]
+fn wasm() {
+    run_corpus_on_large_stack(Backend::Wasm);
+}
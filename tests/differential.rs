@@ -0,0 +1,50 @@
+extern crate elox;
+
+use elox::runner::capture::Backend;
+use elox::runner::differential::{fuzz, run_differential};
+use std::thread;
+
+// Runs generated programs on every backend and fails on the first divergence.
+// `elox fuzz [count] [seed]` explores more seeds.
+
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+const PROGRAMS: u64 = 400;
+
+fn on_large_stack<F: FnOnce() + Send + 'static>(f: F) {
+    let handle = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(f)
+        .expect("could not spawn the test thread");
+
+    if let Err(err) = handle.join() {
+        std::panic::resume_unwind(err);
+    }
+}
+
+#[test]
+fn generated_programs() {
+    on_large_stack(|| {
+        let failures = fuzz(0, PROGRAMS);
+
+        assert!(
+            failures.is_empty(),
+            "{} divergent program(s):\n{}",
+            failures.len(),
+            failures
+                .iter()
+                .map(|failure| format!("{}", failure))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    });
+}
+
+#[test]
+fn unary_operand_errors() {
+    on_large_stack(|| {
+        let source = "var a = true;\nprint 1 + -(a or a);\nprint ~(nil ?? \"a\");\n";
+        let divergences = run_differential(source, &[Backend::TreeWalker, Backend::Vm]);
+
+        assert!(divergences.is_empty(), "{:?}", divergences);
+    });
+}
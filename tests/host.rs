@@ -0,0 +1,107 @@
+extern crate elox;
+
+use elox::interpreter::host::Host;
+use elox::runner::interp::EloxInterpreter;
+use elox::runner::EloxRunner;
+use elox::vm::EloxVM;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// both runners must only talk to the outside world through the host they are given
+
+type Events = Rc<RefCell<Vec<String>>>;
+
+fn recording_host(events: &Events) -> Host {
+    let (printed, errors) = (Rc::clone(events), Rc::clone(events));
+
+    Host {
+        print: Rc::new(move |_, msg| {
+            printed.borrow_mut().push(format!("print {}", msg));
+            Ok(())
+        }),
+        error: Rc::new(move |_, err, line, col| {
+            errors
+                .borrow_mut()
+                .push(format!("error [{}:{}] {}", line, col, err));
+            Ok(())
+        }),
+        clock: Rc::new(|_| Ok(42f64)),
+        ..Host::default()
+    }
+}
+
+fn run<R: EloxRunner>(runner: &mut R, source: &str) {
+    if let Err(err) = runner.run(source) {
+        assert!(runner.throw_error(err).is_ok());
+    }
+}
+
+fn expected_events() -> Vec<String> {
+    vec![
+        "print 42".into(),
+        "error [2:14] Undefined variable: 'missing'".into(),
+    ]
+}
+
+const SOURCE: &str = "print clock();\nprint missing;\n";
+
+#[test]
+fn tree_walker_uses_the_host() {
+    let events = Events::default();
+    run(&mut EloxInterpreter::new(recording_host(&events)), SOURCE);
+
+    assert_eq!(*events.borrow(), expected_events());
+}
+
+#[test]
+fn vm_uses_the_host() {
+    let events = Events::default();
+    run(&mut EloxVM::with_host(recording_host(&events)), SOURCE);
+
+    assert_eq!(*events.borrow(), expected_events());
+}
+
+#[test]
+fn scripts_get_the_args_given_to_the_host() {
+    let events = Events::default();
+    let host = Host {
+        args: Rc::new(|| vec!["-v".into(), "input.txt".into()]),
+        ..recording_host(&events)
+    };
+    run(&mut EloxInterpreter::new(host), "print args();\n");
+
+    assert_eq!(*events.borrow(), vec!["print [-v, input.txt]".to_string()]);
+}
+
+// like the REPL, which runs every line on the same runner
+#[test]
+fn vm_runs_the_next_line_after_an_error() {
+    let events = Events::default();
+    let mut vm = EloxVM::with_host(recording_host(&events));
+
+    let lines = ["var a = 1;", "print missing;", "print a + 1;"];
+
+    for line in lines.iter() {
+        run(&mut vm, line);
+    }
+
+    assert_eq!(
+        *events.borrow(),
+        vec!["error [1:14] Undefined variable: 'missing'", "print 2"]
+    );
+}
+
+// both backends point at the pattern, not at the value being destructured
+#[test]
+fn destructuring_errors_point_at_the_pattern() {
+    let source = "var x = 3;\nvar [a, b] = x;\n";
+    let expected = vec!["error [2:6] Cannot destructure a value of type 'number' as an array"];
+
+    let events = Events::default();
+    run(&mut EloxInterpreter::new(recording_host(&events)), source);
+    assert_eq!(*events.borrow(), expected);
+
+    let events = Events::default();
+    run(&mut EloxVM::with_host(recording_host(&events)), source);
+    assert_eq!(*events.borrow(), expected);
+}
@@ -0,0 +1,29 @@
+extern crate elox;
+
+use elox::interpreter::host::Host;
+use elox::runner::interp::EloxInterpreter;
+use elox::runner::test_runner::{run_tests, TestSummary};
+use elox::vm::EloxVM;
+use std::path::Path;
+
+// the same test files pass on both runners
+const EXPECTED: TestSummary = TestSummary {
+    passed: 6,
+    failed: 0,
+};
+
+#[test]
+fn tree_walker_runs_the_unit_tests() {
+    let summary = run_tests(Path::new("tests/unit"), || {
+        EloxInterpreter::new(Host::default())
+    });
+
+    assert_eq!(summary.unwrap(), EXPECTED);
+}
+
+#[test]
+fn vm_runs_the_unit_tests() {
+    let summary = run_tests(Path::new("tests/unit"), EloxVM::new);
+
+    assert_eq!(summary.unwrap(), EXPECTED);
+}
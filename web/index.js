@@ -1,5 +1,6 @@
 import {
-  run
+  run_with_backend,
+  Backend
 } from '../pkg/elox';
 import CodeFlask from 'codeflask';
 import map_demo from '../demos/map.elox';
@@ -65,10 +66,11 @@ select.addEventListener('change', () => {
   loadDemo(demos[select.value]);
 });
 
+const backend_select = document.querySelector('#backend_selector');
 const run_btn = document.querySelector('#run');
 
 run_btn.addEventListener('click', () => {
   messages = '';
   result.updateCode(messages);
-  run(editor.getCode());
+  run_with_backend(editor.getCode(), Backend[backend_select.value]);
 });
\ No newline at end of file